@@ -0,0 +1,30 @@
+//! Access to the system's clipboard, shared by all plugins.
+
+use arboard::Clipboard;
+use std::sync::{Mutex, OnceLock};
+
+/// Created on first use, then kept alive so copied text stays available
+/// on platforms where the clipboard's contents are owned by the process.
+static CLIPBOARD: OnceLock<Option<Mutex<Clipboard>>> = OnceLock::new();
+
+/// Puts the given text on the system's clipboard.
+/// Errors are logged, as there is nothing the caller could do about them.
+pub fn set_text(text: &str) {
+	let Some(clipboard) = CLIPBOARD.get_or_init(create_clipboard) else {
+		return;
+	};
+
+	let mut guard = clipboard.lock().expect("thread holding the mutex can't panic");
+	if let Err(err) = guard.set_text(text) {
+		log::error!("unable to copy to clipboard: {err}");
+	}
+}
+
+fn create_clipboard() -> Option<Mutex<Clipboard>> {
+	log::trace!("spawning clipboard instance");
+
+	Clipboard::new()
+		.map_err(|err| log::error!("unable to initialize clipboard: {err}"))
+		.ok()
+		.map(Mutex::new)
+}
@@ -41,7 +41,8 @@ impl QueryEngine {
 	}
 
 	/// Adds the provider to the engine's collection.
-	pub fn register(&mut self, provider: Box<dyn Provider>, keyword: Option<String>) -> &mut Self {
+	pub fn register(&mut self, mut provider: Box<dyn Provider>, keyword: Option<String>) -> &mut Self {
+		provider.set_sender(self.sender.clone());
 		let info = ProviderInfo { provider, keyword };
 
 		self.providers.push(info);
@@ -2,6 +2,7 @@
 //! Contains traits and structs needed to write a plugin, as well as
 //! core functionality for querying and scoring.
 
+pub mod clipboard;
 pub mod config;
 mod engine;
 mod frontend;
@@ -9,8 +10,13 @@ pub mod hotkeys;
 pub mod paths;
 pub mod performance;
 pub mod plugin;
+#[cfg(all(unix, feature = "process"))]
+pub mod process;
 mod provider;
 pub mod scoring;
+pub mod storage;
+#[cfg(all(unix, any(test, feature = "test-util")))]
+pub mod test_util;
 
 pub use engine::{QueryEngine, QueryResult};
 pub use frontend::{Frontend, FrontendExitStatus, FrontendMessage};
@@ -2,7 +2,7 @@ use std::{env, path::PathBuf};
 
 const APP_NAME: &str = "gravel";
 
-fn get_home() -> PathBuf {
+pub fn get_home() -> PathBuf {
 	#[cfg(unix)]
 	let home = env::var("HOME").expect("$HOME should always be set");
 
@@ -20,7 +20,11 @@ pub fn get_gravel_config_dir() -> PathBuf {
 	get_xdg_config_home().join(APP_NAME)
 }
 
-fn get_xdg_config_home() -> PathBuf {
+pub fn get_gravel_data_dir() -> PathBuf {
+	get_xdg_data_home().join(APP_NAME)
+}
+
+pub fn get_xdg_config_home() -> PathBuf {
 	if let Ok(path) = env::var("XDG_CONFIG_HOME") {
 		return path.into();
 	}
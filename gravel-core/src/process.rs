@@ -0,0 +1,193 @@
+//! Starts processes independently of gravel, so that restarting or
+//! killing gravel doesn't affect them.
+//!
+//! Optionally, each process is placed in its own transient systemd user scope,
+//! just like desktop environments do, so that resource accounting treats
+//! them as separate applications.
+
+use nix::fcntl::OFlag;
+use nix::unistd::{fork, pipe2, setsid, ForkResult};
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::fd::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use zbus::blocking::Connection;
+use zbus::zvariant::{OwnedObjectPath, Value};
+
+const SYSTEMD_DESTINATION: &str = "org.freedesktop.systemd1";
+const SYSTEMD_PATH: &str = "/org/freedesktop/systemd1";
+const SYSTEMD_MANAGER: &str = "org.freedesktop.systemd1.Manager";
+
+/// Spawns the command detached from gravel, see [`spawn_detached`].
+///
+/// If `systemd_scope` is set, the process is then moved into a new transient
+/// systemd user scope named after `app_id`. If that fails, a warning is
+/// logged and the process keeps running as if it was spawned normally.
+pub fn launch(command: &mut Command, app_id: &str, systemd_scope: bool) -> io::Result<()> {
+	let pid = spawn_detached(command)?;
+
+	if systemd_scope {
+		match Connection::session().and_then(|c| move_to_scope(&c, app_id, pid)) {
+			Ok(unit) => log::debug!("started process {pid} in {unit}"),
+			Err(err) => log::warn!("unable to move process {pid} into a systemd scope: {err}"),
+		}
+	}
+
+	Ok(())
+}
+
+/// Spawns the command without any inherited streams, in a new session and
+/// forked twice, so it is adopted by init and unaffected by gravel exiting.
+///
+/// Errors from exec in the final process are still reported.
+/// Returns the final process' PID.
+pub fn spawn_detached(command: &mut Command) -> io::Result<u32> {
+	command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+
+	// the intermediate process reports the final PID through this pipe
+	let (reader, writer) = pipe2(OFlag::O_CLOEXEC)?;
+	let writer_fd = writer.as_raw_fd();
+
+	// SAFETY: only async-signal-safe functions are called between fork and exec.
+	unsafe {
+		command.pre_exec(move || {
+			setsid()?;
+
+			match fork()? {
+				ForkResult::Parent { child } => {
+					let pid = child.as_raw().to_ne_bytes();
+					nix::libc::write(writer_fd, pid.as_ptr().cast(), pid.len());
+					nix::libc::_exit(0)
+				}
+				ForkResult::Child => Ok(()),
+			}
+		});
+	}
+
+	// the intermediate process exits right away, reap it
+	command.spawn()?.wait()?;
+	drop(writer);
+
+	let mut pid = [0; 4];
+	File::from(reader).read_exact(&mut pid)?;
+
+	Ok(i32::from_ne_bytes(pid) as u32)
+}
+
+/// Moves the process into a new transient scope in the systemd instance
+/// behind the connection and returns the scope's name.
+pub fn move_to_scope(connection: &Connection, app_id: &str, pid: u32) -> zbus::Result<String> {
+	let unit = format!("app-gravel-{}-{pid}.scope", escape_unit_name(app_id));
+
+	let properties = vec![
+		("PIDs", Value::from(vec![pid])),
+		("CollectMode", Value::from("inactive-or-failed")),
+	];
+
+	let aux: Vec<(&str, Vec<(&str, Value)>)> = vec![];
+
+	connection
+		.call_method(
+			Some(SYSTEMD_DESTINATION),
+			SYSTEMD_PATH,
+			Some(SYSTEMD_MANAGER),
+			"StartTransientUnit",
+			&(&unit, "fail", properties, aux),
+		)?
+		.body::<OwnedObjectPath>()?;
+
+	Ok(unit)
+}
+
+/// Escapes the string like `systemd-escape` does, so it can be used
+/// as part of a unit name.
+fn escape_unit_name(value: &str) -> String {
+	let mut result = String::with_capacity(value.len());
+
+	for (i, byte) in value.bytes().enumerate() {
+		match byte {
+			b'/' => result.push('-'),
+			b'.' if i == 0 => result.push_str("\\x2e"),
+			b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b':' | b'_' | b'.' => result.push(byte as char),
+			_ => result.push_str(&format!("\\x{byte:02x}")),
+		}
+	}
+
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::mock_bus;
+	use rstest::rstest;
+	use std::sync::{Arc, Mutex};
+	use zbus::dbus_interface;
+	use zbus::zvariant::OwnedValue;
+
+	type Units = Arc<Mutex<Vec<(String, Vec<String>)>>>;
+
+	/// Stands in for systemd's manager, recording all requested units.
+	struct MockManager {
+		units: Units,
+	}
+
+	#[dbus_interface(name = "org.freedesktop.systemd1.Manager")]
+	impl MockManager {
+		fn start_transient_unit(
+			&self,
+			name: String,
+			_mode: String,
+			properties: Vec<(String, OwnedValue)>,
+			_aux: Vec<(String, Vec<(String, OwnedValue)>)>,
+		) -> OwnedObjectPath {
+			let keys = properties.into_iter().map(|(k, _)| k).collect();
+			self.units.lock().unwrap().push((name, keys));
+
+			OwnedObjectPath::try_from("/org/freedesktop/systemd1/job/1").unwrap()
+		}
+	}
+
+	/// Connects to a mock systemd over a private peer-to-peer connection.
+	fn mock_systemd() -> (Connection, Connection, Units) {
+		let units = Units::default();
+		let manager = MockManager { units: units.clone() };
+		let (client, server) = mock_bus(move |builder| builder.serve_at(SYSTEMD_PATH, manager));
+
+		(client, server, units)
+	}
+
+	#[test]
+	fn should_move_to_scope() {
+		let (client, _server, units) = mock_systemd();
+
+		let unit = move_to_scope(&client, "org.gnome.Nautilus", 1234).unwrap();
+
+		assert_eq!("app-gravel-org.gnome.Nautilus-1234.scope", unit);
+
+		let expected = (unit, vec![String::from("PIDs"), String::from("CollectMode")]);
+		assert_eq!(vec![expected], *units.lock().unwrap());
+	}
+
+	#[rstest]
+	#[case("firefox", "firefox")]
+	#[case("vendor-foo", "vendor\\x2dfoo")]
+	#[case(".hidden", "\\x2ehidden")]
+	#[case("my app", "my\\x20app")]
+	fn should_escape_unit_name(#[case] value: &str, #[case] expected: &str) {
+		assert_eq!(expected, escape_unit_name(value));
+	}
+
+	#[test]
+	fn should_report_missing_program() {
+		let result = spawn_detached(&mut Command::new("gravel-does-not-exist"));
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn should_spawn() {
+		let pid = spawn_detached(&mut Command::new("true")).unwrap();
+		assert_ne!(0, pid);
+	}
+}
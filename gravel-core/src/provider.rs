@@ -1,9 +1,15 @@
 use crate::frontend::FrontendMessage;
+use std::path::{Path, PathBuf};
 use std::sync::{mpsc::Sender, Arc};
 
 /// A provider takes a query and provides some relevant results.
 pub trait Provider {
 	fn query(&self, query: &str) -> ProviderResult;
+
+	/// Called once when the provider is registered. Providers that produce
+	/// results asynchronously can keep the sender to ask the frontend to
+	/// query again with [`FrontendMessage::Refresh`].
+	fn set_sender(&mut self, _sender: Sender<FrontendMessage>) {}
 }
 
 /// A collection of hits.
@@ -38,6 +44,18 @@ pub trait Hit: Sync + Send {
 	fn get_subtitle(&self) -> &str;
 	fn get_override_score(&self) -> Option<u32>;
 	fn action(&self, sender: &Sender<FrontendMessage>);
+
+	/// Path to an image file shown alongside the hit, if the frontend supports it.
+	fn get_icon(&self) -> Option<&Path> {
+		None
+	}
+
+	/// Prompt asking to confirm the action, for hits that can't be undone.
+	/// The frontend shows it when the hit is first selected and only runs
+	/// the action if the hit is selected again.
+	fn get_confirmation(&self) -> Option<&str> {
+		None
+	}
 }
 
 /// Reference implementation for [`Hit`].
@@ -46,7 +64,9 @@ pub trait Hit: Sync + Send {
 pub struct SimpleHit {
 	title: Box<str>,
 	subtitle: Box<str>,
+	icon: Option<PathBuf>,
 	override_score: Option<u32>,
+	confirmation: Option<Box<str>>,
 
 	// I think inlining it is easier to read in this case, due to T.
 	#[allow(clippy::type_complexity)]
@@ -63,7 +83,9 @@ impl SimpleHit {
 		Self {
 			title: title.into(),
 			subtitle: subtitle.into(),
+			icon: None,
 			override_score: None,
+			confirmation: None,
 			action_func: Box::new(func),
 		}
 	}
@@ -73,6 +95,19 @@ impl SimpleHit {
 		self.override_score = Some(score);
 		self
 	}
+
+	#[must_use]
+	pub fn with_icon(mut self, icon: Option<PathBuf>) -> Self {
+		self.icon = icon;
+		self
+	}
+
+	/// Requires the hit to be selected twice, see [`Hit::get_confirmation`].
+	#[must_use]
+	pub fn with_confirmation(mut self, prompt: Option<String>) -> Self {
+		self.confirmation = prompt.map(Into::into);
+		self
+	}
 }
 
 impl Hit for SimpleHit {
@@ -91,4 +126,12 @@ impl Hit for SimpleHit {
 	fn get_override_score(&self) -> Option<u32> {
 		self.override_score
 	}
+
+	fn get_icon(&self) -> Option<&Path> {
+		self.icon.as_deref()
+	}
+
+	fn get_confirmation(&self) -> Option<&str> {
+		self.confirmation.as_deref()
+	}
 }
@@ -45,7 +45,13 @@ fn get_scored_hit(hit: Arc<dyn Hit>, query: &str) -> Option<ScoredHit> {
 }
 
 fn get_score(hit: &dyn Hit, query: &str) -> Option<u32> {
-	MATCHER.fuzzy_match(hit.get_title(), query).map(|s| s as u32)
+	get_text_score(hit.get_title(), query)
+}
+
+/// Scores the text the same way hit titles are scored. Useful for providers
+/// that interpret parts of the query themselves and override the score.
+pub fn get_text_score(text: &str, query: &str) -> Option<u32> {
+	MATCHER.fuzzy_match(text, query).map(|s| s as u32)
 }
 
 fn compare_hits(a: &ScoredHit, b: &ScoredHit) -> Ordering {
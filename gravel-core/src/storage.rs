@@ -0,0 +1,75 @@
+//! Helpers for files that plugins keep in gravel's data directory.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Writes the file, creating its parent directories if necessary.
+///
+/// The data is written to a temporary file first, which then replaces the
+/// file, so a crash can't leave it truncated.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+	write_with(path, data, &mut OpenOptions::new())
+}
+
+/// Like [`write_atomic`], but only the current user can read the file.
+#[cfg(unix)]
+pub fn write_private(path: &Path, data: &[u8]) -> io::Result<()> {
+	use std::os::unix::fs::OpenOptionsExt;
+
+	write_with(path, data, OpenOptions::new().mode(0o600))
+}
+
+/// Like [`write_atomic`]. Windows has no file permissions in the unix sense,
+/// the user's profile is private already.
+#[cfg(windows)]
+pub fn write_private(path: &Path, data: &[u8]) -> io::Result<()> {
+	write_atomic(path, data)
+}
+
+fn write_with(path: &Path, data: &[u8], options: &mut OpenOptions) -> io::Result<()> {
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+
+	let temp = path.with_extension("tmp");
+	options
+		.write(true)
+		.create(true)
+		.truncate(true)
+		.open(&temp)?
+		.write_all(data)?;
+
+	fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_write_atomic() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("data/history.json");
+
+		write_atomic(&path, b"old").unwrap();
+		write_atomic(&path, b"new").unwrap();
+
+		assert_eq!(b"new", &*fs::read(&path).unwrap());
+		assert!(!path.with_extension("tmp").exists());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn should_write_private() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("key");
+
+		write_private(&path, b"secret").unwrap();
+
+		let mode = fs::metadata(&path).unwrap().permissions().mode();
+		assert_eq!(0o600, mode & 0o777);
+	}
+}
@@ -0,0 +1,29 @@
+//! Helpers for testing plugins, available with the `test-util` feature.
+
+use std::os::unix::net::UnixStream;
+use std::thread;
+use zbus::blocking::{Connection, ConnectionBuilder};
+use zbus::Guid;
+
+/// Connects to a mock D-Bus service over a private peer-to-peer connection.
+///
+/// `serve` adds the mocked interfaces to the server side, usually with
+/// [`ConnectionBuilder::serve_at`]. Returns the client and the server,
+/// which has to be kept alive for as long as the client is used.
+pub fn mock_bus<F>(serve: F) -> (Connection, Connection)
+where
+	F: for<'a> FnOnce(ConnectionBuilder<'a>) -> zbus::Result<ConnectionBuilder<'a>> + Send + 'static,
+{
+	let (client, server) = UnixStream::pair().unwrap();
+
+	let server = thread::spawn(move || {
+		let guid = Guid::generate();
+		let builder = ConnectionBuilder::unix_stream(server).server(&guid).p2p();
+
+		serve(builder).unwrap().build().unwrap()
+	});
+
+	let client = ConnectionBuilder::unix_stream(client).p2p().build().unwrap();
+
+	(client, server.join().unwrap())
+}
@@ -104,17 +104,26 @@ fn build_hit(i: i32, config: &Config) -> HitUi {
 	group.set_color(config.colors.hit_highlight);
 	group.set_frame(FrameType::FlatBox);
 
-	let mut title = Frame::default()
+	let mut icon = Frame::default()
 		.with_pos(config.layout.padding, y)
-		.with_size(config.layout.hit_width, config.layout.hit_title_height)
+		.with_size(config.layout.icon_column_width, config.layout.hit_height)
+		.with_align(Align::Center | Align::Inside | Align::Clip);
+
+	if !config.layout.show_icons {
+		icon.hide();
+	}
+
+	let mut title = Frame::default()
+		.with_pos(config.layout.hit_text_x, y)
+		.with_size(config.layout.hit_text_width, config.layout.hit_title_height)
 		.with_align(Align::BottomLeft | Align::Inside | Align::Clip);
 
 	title.set_label_size(config.layout.hit_title_font_size);
 	title.set_label_color(config.colors.hit_title);
 
 	let mut subtitle = Frame::default()
-		.with_pos(config.layout.padding, y + config.layout.hit_title_height)
-		.with_size(config.layout.hit_width, config.layout.hit_subtitle_height)
+		.with_pos(config.layout.hit_text_x, y + config.layout.hit_title_height)
+		.with_size(config.layout.hit_text_width, config.layout.hit_subtitle_height)
 		.with_align(Align::TopLeft | Align::Inside | Align::Clip);
 
 	subtitle.set_label_size(config.layout.hit_subtitle_font_size);
@@ -123,7 +132,12 @@ fn build_hit(i: i32, config: &Config) -> HitUi {
 	group.show();
 	group.end();
 
-	HitUi { group, title, subtitle }
+	HitUi {
+		group,
+		icon,
+		title,
+		subtitle,
+	}
 }
 
 /// Handles events on the window.
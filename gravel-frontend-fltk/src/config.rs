@@ -32,6 +32,11 @@ pub struct Layout {
 	pub max_hits: i32,
 	pub hit_width: i32,
 	pub hit_height: i32,
+	pub hit_text_x: i32,
+	pub hit_text_width: i32,
+	pub show_icons: bool,
+	pub icon_size: i32,
+	pub icon_column_width: i32,
 	pub query_width: i32,
 	pub query_height: i32,
 	pub scrollbar_x: i32,
@@ -139,6 +144,8 @@ pub mod deserialize {
 		pub hit_title_font_size: i32,
 		pub hit_subtitle_height: i32,
 		pub hit_subtitle_font_size: i32,
+		pub show_icons: bool,
+		pub icon_size: i32,
 		pub query_font_size: i32,
 		pub scrollbar_width: i32,
 		pub scrollbar_padding: i32,
@@ -152,16 +159,27 @@ pub mod deserialize {
 			let hit_title_font_size = (value.hit_title_font_size as f32 * value.scale) as i32;
 			let hit_subtitle_height = (value.hit_subtitle_height as f32 * value.scale) as i32;
 			let hit_subtitle_font_size = (value.hit_subtitle_font_size as f32 * value.scale) as i32;
+			let icon_size = (value.icon_size as f32 * value.scale) as i32;
 			let query_font_size = (value.query_font_size as f32 * value.scale) as i32;
 			let scrollbar_width = (value.scrollbar_width as f32 * value.scale) as i32;
 			let scrollbar_padding = (value.scrollbar_padding as f32 * value.scale) as i32;
 			let padding = (value.padding as f32 * value.scale) as i32;
 			let window_width = (value.window_width as f32 * value.scale) as i32;
 			let max_hits = value.max_hits;
+			let show_icons = value.show_icons;
 
 			let hit_width = window_width - padding * 2;
 			let hit_height = hit_title_height + hit_subtitle_height;
 
+			// icons get a square column to the left of the text
+			let icon_column_width = match show_icons {
+				true => hit_height,
+				false => 0,
+			};
+
+			let hit_text_x = padding + icon_column_width;
+			let hit_text_width = hit_width - icon_column_width;
+
 			let query_width = hit_width;
 			let query_height = hit_height;
 
@@ -176,6 +194,11 @@ pub mod deserialize {
 				max_hits,
 				hit_width,
 				hit_height,
+				hit_text_x,
+				hit_text_width,
+				show_icons,
+				icon_size,
+				icon_column_width,
 				query_width,
 				query_height,
 				scrollbar_x,
@@ -0,0 +1,40 @@
+use fltk::{image::SharedImage, prelude::*};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Loads and scales hit icons, keeping them around so scrolling
+/// through the same hits doesn't hit the disk again.
+pub struct IconCache {
+	size: i32,
+	images: HashMap<PathBuf, Option<SharedImage>>,
+}
+
+impl IconCache {
+	pub fn new(size: i32) -> Self {
+		Self {
+			size,
+			images: HashMap::new(),
+		}
+	}
+
+	/// Returns the scaled image, or [`None`] if it can't be loaded.
+	/// Failures are cached as well.
+	pub fn get(&mut self, path: &Path) -> Option<SharedImage> {
+		let size = self.size;
+
+		self.images
+			.entry(path.to_owned())
+			.or_insert_with(|| load(path, size))
+			.clone()
+	}
+}
+
+fn load(path: &Path, size: i32) -> Option<SharedImage> {
+	let mut image = SharedImage::load(path)
+		.map_err(|err| log::debug!("unable to load icon {path:?}: {err}"))
+		.ok()?;
+
+	image.scale(size, size, true, true);
+
+	Some(image)
+}
@@ -1,5 +1,5 @@
-use crate::{builder, config::*, native, scroll::Scroll, structs::*};
-use fltk::{enums::*, prelude::*};
+use crate::{builder, config::*, icons::IconCache, native, scroll::Scroll, structs::*};
+use fltk::{enums::*, image::SharedImage, prelude::*};
 use gravel_core::{scoring::ScoredHit, *};
 use std::sync::mpsc::Receiver;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -10,8 +10,11 @@ pub struct FltkFrontend {
 	engine: QueryEngine,
 	result: QueryResult,
 	scroll: Scroll,
+	icons: IconCache,
 	visible: bool,
 	last_hide_time: SystemTime,
+	/// Position of the hit waiting to be selected again, see [`Hit::get_confirmation`].
+	confirming: Option<i32>,
 }
 
 impl Frontend for FltkFrontend {
@@ -27,6 +30,7 @@ impl FltkFrontend {
 		let ui = builder::build(&config);
 		let max_view_size = config.layout.max_hits;
 		let visible = !config.behaviour.start_hidden;
+		let icons = IconCache::new(config.layout.icon_size);
 
 		if visible {
 			native::activate_window(&ui.window);
@@ -38,8 +42,10 @@ impl FltkFrontend {
 			ui,
 			result: QueryResult::empty(),
 			scroll: Scroll::new(0, max_view_size),
+			icons,
 			visible,
 			last_hide_time: UNIX_EPOCH,
+			confirming: None,
 		}
 	}
 
@@ -63,6 +69,11 @@ impl FltkFrontend {
 	}
 
 	fn handle_message(&mut self, message: Message) -> Option<FrontendExitStatus> {
+		// anything but typing or selecting the hit again cancels a pending confirmation
+		if !matches!(message, Message::Query | Message::Confirm) {
+			self.cancel_confirmation();
+		}
+
 		match message {
 			Message::Query => self.query(),
 			Message::ForceQuery => self.force_query(),
@@ -173,17 +184,32 @@ impl FltkFrontend {
 	fn force_query(&mut self) {
 		self.result = self.engine.query(&self.ui.input.value());
 		self.ui.input.clear_changed();
+		self.confirming = None;
 
 		self.update_window_height();
 		self.update_hits();
 	}
 
-	/// Runs the action of the selected hit.
-	fn confirm(&self) {
-		if !self.result.hits.is_empty() {
-			let cursor = self.scroll.cursor();
-			let hit = &self.result.hits[cursor as usize];
-			self.engine.run_hit_action(&*hit.hit);
+	/// Runs the action of the selected hit. Hits that require confirmation
+	/// show their prompt instead, and only run once selected again.
+	fn confirm(&mut self) {
+		let cursor = self.scroll.cursor();
+		let Some(hit) = self.result.hits.get(cursor as usize) else {
+			return;
+		};
+
+		if hit.hit.get_confirmation().is_some() && self.confirming.replace(cursor) != Some(cursor) {
+			self.update_hits();
+			return;
+		}
+
+		self.confirming = None;
+		self.engine.run_hit_action(&*hit.hit);
+	}
+
+	fn cancel_confirmation(&mut self) {
+		if self.confirming.take().is_some() {
+			self.update_hits();
 		}
 	}
 
@@ -219,12 +245,21 @@ impl FltkFrontend {
 
 	/// Writes the hit data to the UI elements.
 	fn update_hits(&mut self) {
+		let show_scores = self.config.behaviour.show_scores;
+
 		for (i, hit_ui) in self.ui.hits.iter_mut().enumerate() {
 			let position = self.scroll.scroll() + i as i32;
 			let selected = position == self.scroll.cursor();
+			let confirming = self.confirming == Some(position);
 
 			let hit = self.result.hits.get(position as usize);
-			update_hit(hit_ui, hit, selected, self.config.behaviour.show_scores);
+
+			let icon = match self.config.layout.show_icons {
+				true => hit.and_then(|h| h.hit.get_icon()).and_then(|p| self.icons.get(p)),
+				false => None,
+			};
+
+			update_hit(hit_ui, hit, icon, selected, confirming, show_scores);
 		}
 
 		self.update_scrollbar();
@@ -271,11 +306,24 @@ impl FltkFrontend {
 
 /// Writes the given [`HitData`] to the given [`HitUi`].
 ///
-/// `selected` highlights the hit.
-fn update_hit(hit_ui: &mut HitUi, hit: Option<&ScoredHit>, selected: bool, show_score: bool) {
+/// `selected` highlights the hit, `confirming` replaces its subtitle
+/// with the confirmation prompt.
+fn update_hit(
+	hit_ui: &mut HitUi,
+	hit: Option<&ScoredHit>,
+	icon: Option<SharedImage>,
+	selected: bool,
+	confirming: bool,
+	show_score: bool,
+) {
 	let title = hit.map_or("", |h| h.hit.get_title());
-	let subtitle = hit.map_or("", |h| h.hit.get_subtitle());
+	let subtitle = match (hit, confirming) {
+		(Some(h), true) => h.hit.get_confirmation().unwrap_or_default(),
+		(Some(h), false) => h.hit.get_subtitle(),
+		(None, _) => "",
+	};
 
+	hit_ui.icon.set_image(icon);
 	hit_ui.title.set_label(title);
 
 	if show_score {
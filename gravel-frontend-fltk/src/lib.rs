@@ -6,6 +6,7 @@ use implementation::FltkFrontend;
 
 mod builder;
 mod config;
+mod icons;
 mod implementation;
 mod scroll;
 mod scrollbar;
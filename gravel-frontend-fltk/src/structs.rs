@@ -16,6 +16,7 @@ pub struct Ui {
 /// Holds UI elements for displaying a single hit.
 pub struct HitUi {
 	pub group: Group,
+	pub icon: Frame,
 	pub title: Frame,
 	pub subtitle: Frame,
 }
@@ -0,0 +1,117 @@
+use crate::database::{query_entries, DatabaseCopy};
+use crate::index::{Entry, Kind};
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const HISTORY_QUERY: &str = "
+	SELECT url, title FROM urls
+	WHERE hidden = 0
+	ORDER BY visit_count DESC, last_visit_time DESC
+	LIMIT ?1";
+
+#[derive(Deserialize, Debug)]
+struct BookmarksFile {
+	roots: BTreeMap<String, Node>,
+}
+
+/// A bookmark or folder.
+#[derive(Deserialize, Debug)]
+struct Node {
+	#[serde(default)]
+	name: String,
+	url: Option<String>,
+	#[serde(default)]
+	children: Vec<Node>,
+}
+
+/// Returns whether the directory is a Chromium profile.
+pub(crate) fn is_profile(path: &Path) -> bool {
+	path.join("Bookmarks").is_file() || path.join("History").is_file()
+}
+
+/// Reads the profile's bookmarks and its most frequently visited pages.
+/// Either may be missing, for example when nothing was bookmarked yet.
+pub(crate) fn read_profile(path: &Path, max_history: usize) -> Result<Vec<Entry>> {
+	let mut entries = vec![];
+
+	let bookmarks_path = path.join("Bookmarks");
+	if bookmarks_path.is_file() {
+		entries.extend(parse_bookmarks(&fs::read(bookmarks_path)?)?);
+	}
+
+	let history_path = path.join("History");
+	if history_path.is_file() {
+		let copy = DatabaseCopy::new(&history_path)?;
+		let history = query_entries(&copy.connection, HISTORY_QUERY, &[max_history as i64], Kind::History)?;
+		entries.extend(history);
+	}
+
+	Ok(entries)
+}
+
+fn parse_bookmarks(json: &[u8]) -> Result<Vec<Entry>> {
+	let file = serde_json::from_slice::<BookmarksFile>(json)?;
+	let mut entries = vec![];
+
+	for root in file.roots.into_values() {
+		collect_bookmarks(root, &mut entries);
+	}
+
+	Ok(entries)
+}
+
+fn collect_bookmarks(node: Node, entries: &mut Vec<Entry>) {
+	let entry = node
+		.url
+		.and_then(|url| Entry::new(url, Some(node.name), Kind::Bookmark));
+	entries.extend(entry);
+
+	for child in node.children {
+		collect_bookmarks(child, entries);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_parse_bookmarks() {
+		let json = br#"{
+			"checksum": "0",
+			"roots": {
+				"bookmark_bar": {
+					"name": "Bookmarks bar",
+					"type": "folder",
+					"children": [
+						{ "name": "gravel", "type": "url", "url": "https://github.com/thorio/gravel" },
+						{
+							"name": "Folder",
+							"type": "folder",
+							"children": [{ "name": "Example", "type": "url", "url": "https://example.com/" }]
+						},
+						{ "name": "Script", "type": "url", "url": "javascript:alert(1)" }
+					]
+				},
+				"other": { "name": "Other bookmarks", "type": "folder", "children": [] }
+			},
+			"version": 1
+		}"#;
+
+		let entries = parse_bookmarks(json).unwrap();
+		let actual = entries
+			.iter()
+			.map(|e| (e.title.as_str(), e.url.as_str()))
+			.collect::<Vec<_>>();
+
+		let expected = vec![
+			("gravel", "https://github.com/thorio/gravel"),
+			("Example", "https://example.com/"),
+		];
+
+		assert_eq!(expected, actual);
+	}
+}
@@ -0,0 +1,74 @@
+use crate::index::{Entry, Kind};
+use anyhow::{Context, Result};
+use rusqlite::{params_from_iter, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, process};
+
+static COPY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A copy of a browser's database, deleted when dropped.
+///
+/// Browsers keep their databases locked while running, so they are copied
+/// along with their write-ahead log and read from the copy instead.
+pub(crate) struct DatabaseCopy {
+	directory: PathBuf,
+	pub connection: Connection,
+}
+
+impl DatabaseCopy {
+	pub fn new(path: &Path) -> Result<Self> {
+		let id = COPY_COUNTER.fetch_add(1, Ordering::Relaxed);
+		let directory = env::temp_dir().join(format!("gravel-browser-{}-{id}", process::id()));
+		fs::create_dir_all(&directory)?;
+
+		// create it first, so the directory is removed even if copying fails
+		let mut copy = Self {
+			connection: Connection::open_in_memory()?,
+			directory,
+		};
+
+		let file_name = path.file_name().context("database path has no file name")?;
+		let copy_path = copy.directory.join(file_name);
+		fs::copy(path, &copy_path)?;
+
+		// recent changes may still be in the write-ahead log
+		let wal_path = append_to_name(path, "-wal");
+		if wal_path.exists() {
+			fs::copy(&wal_path, append_to_name(&copy_path, "-wal"))?;
+		}
+
+		copy.connection = Connection::open(&copy_path)?;
+		Ok(copy)
+	}
+}
+
+impl Drop for DatabaseCopy {
+	fn drop(&mut self) {
+		if let Err(err) = fs::remove_dir_all(&self.directory) {
+			log::warn!("unable to remove database copy {:?}: {err}", self.directory);
+		}
+	}
+}
+
+fn append_to_name(path: &Path, suffix: &str) -> PathBuf {
+	let mut name = path.as_os_str().to_owned();
+	name.push(suffix);
+
+	PathBuf::from(name)
+}
+
+/// Runs a query returning URLs and titles, skipping URLs that can't be opened.
+pub(crate) fn query_entries(connection: &Connection, query: &str, params: &[i64], kind: Kind) -> Result<Vec<Entry>> {
+	let mut statement = connection.prepare(query)?;
+
+	let rows = statement.query_map(params_from_iter(params), |row| {
+		let url = row.get::<_, String>(0)?;
+		let title = row.get::<_, Option<String>>(1)?;
+
+		Ok(Entry::new(url, title, kind))
+	})?;
+
+	let entries = rows.collect::<Result<Vec<_>, _>>()?;
+	Ok(entries.into_iter().flatten().collect())
+}
@@ -0,0 +1,76 @@
+use crate::database::{query_entries, DatabaseCopy};
+use crate::index::{Entry, Kind};
+use anyhow::Result;
+use rusqlite::Connection;
+use std::path::Path;
+
+const BOOKMARKS_QUERY: &str = "
+	SELECT p.url, b.title FROM moz_bookmarks b
+	JOIN moz_places p ON p.id = b.fk
+	WHERE b.type = 1";
+
+const HISTORY_QUERY: &str = "
+	SELECT url, title FROM moz_places
+	WHERE hidden = 0 AND last_visit_date IS NOT NULL
+	ORDER BY frecency DESC
+	LIMIT ?1";
+
+/// Returns whether the directory is a Firefox profile.
+pub(crate) fn is_profile(path: &Path) -> bool {
+	path.join("places.sqlite").is_file()
+}
+
+/// Reads the profile's bookmarks and its most frequently visited pages.
+pub(crate) fn read_profile(path: &Path, max_history: usize) -> Result<Vec<Entry>> {
+	let copy = DatabaseCopy::new(&path.join("places.sqlite"))?;
+
+	read_places(&copy.connection, max_history)
+}
+
+fn read_places(connection: &Connection, max_history: usize) -> Result<Vec<Entry>> {
+	let bookmarks = query_entries(connection, BOOKMARKS_QUERY, &[], Kind::Bookmark)?;
+	let history = query_entries(connection, HISTORY_QUERY, &[max_history as i64], Kind::History)?;
+
+	Ok(bookmarks.into_iter().chain(history).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_read_places() {
+		let connection = Connection::open_in_memory().unwrap();
+
+		connection
+			.execute_batch(
+				"
+				CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, title TEXT, hidden INTEGER, frecency INTEGER, last_visit_date INTEGER);
+				CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER, title TEXT);
+				INSERT INTO moz_places VALUES (1, 'https://github.com/thorio/gravel', 'gravel', 0, 100, 1);
+				INSERT INTO moz_places VALUES (2, 'https://example.com/', NULL, 0, 500, 1);
+				INSERT INTO moz_places VALUES (3, 'https://example.com/hidden', 'hidden', 1, 900, 1);
+				INSERT INTO moz_places VALUES (4, 'place:sort=8', 'Recent Tags', 0, 0, NULL);
+				INSERT INTO moz_places VALUES (5, 'https://example.com/never', 'never visited', 0, 0, NULL);
+				INSERT INTO moz_bookmarks VALUES (1, 1, 1, 'Gravel Launcher');
+				INSERT INTO moz_bookmarks VALUES (2, 2, NULL, 'Folder');
+				INSERT INTO moz_bookmarks VALUES (3, 1, 4, 'Recent Tags');
+				",
+			)
+			.unwrap();
+
+		let entries = read_places(&connection, 10).unwrap();
+		let actual = entries
+			.iter()
+			.map(|e| (e.title.as_str(), e.url.as_str(), e.kind))
+			.collect::<Vec<_>>();
+
+		let expected = vec![
+			("Gravel Launcher", "https://github.com/thorio/gravel", Kind::Bookmark),
+			("https://example.com/", "https://example.com/", Kind::History),
+			("gravel", "https://github.com/thorio/gravel", Kind::History),
+		];
+
+		assert_eq!(expected, actual);
+	}
+}
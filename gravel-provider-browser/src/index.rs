@@ -0,0 +1,108 @@
+use crate::{chromium, firefox, ProfilesConfig};
+use glob::glob;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Only URLs with these schemes are shown, skipping Firefox's `place:`
+/// queries, bookmarklets and the like.
+const SCHEMES: &[&str] = &["http://", "https://", "file://"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+	Bookmark,
+	History,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+	pub title: String,
+	pub url: String,
+	pub kind: Kind,
+}
+
+impl Entry {
+	/// Creates an entry, titled with the URL if there is no title.
+	/// Returns nothing for URLs that can't be opened.
+	pub fn new(url: String, title: Option<String>, kind: Kind) -> Option<Self> {
+		if !SCHEMES.iter().any(|s| url.starts_with(s)) {
+			return None;
+		}
+
+		let title = title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| url.clone());
+		Some(Self { title, url, kind })
+	}
+}
+
+/// Reads the bookmarks and history of all profiles found.
+/// Bookmarks come first, and each URL is only included once.
+pub(crate) fn load(config: &ProfilesConfig, max_history: usize) -> Vec<Entry> {
+	let firefox = find_profiles(&config.firefox_profiles, firefox::is_profile)
+		.into_iter()
+		.flat_map(|path| get_entries(&path, firefox::read_profile(&path, max_history)));
+
+	let chromium = find_profiles(&config.chromium_profiles, chromium::is_profile)
+		.into_iter()
+		.flat_map(|path| get_entries(&path, chromium::read_profile(&path, max_history)));
+
+	let (bookmarks, history): (Vec<_>, Vec<_>) = firefox.chain(chromium).partition(|e| e.kind == Kind::Bookmark);
+
+	let mut urls = HashSet::new();
+	bookmarks
+		.into_iter()
+		.chain(history)
+		.filter(|e| urls.insert(e.url.clone()))
+		.collect()
+}
+
+fn get_entries(path: &Path, result: anyhow::Result<Vec<Entry>>) -> Vec<Entry> {
+	match result {
+		Ok(entries) => {
+			log::debug!("read {} entries from browser profile {path:?}", entries.len());
+			entries
+		}
+		Err(err) => {
+			log::warn!("unable to read browser profile {path:?}: {err:#}");
+			vec![]
+		}
+	}
+}
+
+/// Expands the globs, returning the directories that are profiles.
+fn find_profiles(patterns: &[String], is_profile: fn(&Path) -> bool) -> Vec<PathBuf> {
+	patterns
+		.iter()
+		.filter_map(|p| expand_glob(p))
+		.flatten()
+		.filter_map(Result::ok)
+		.filter(|path| is_profile(path))
+		.collect()
+}
+
+fn expand_glob(pattern: &str) -> Option<glob::Paths> {
+	// unset variables are expected, like `$LOCALAPPDATA` for browsers that aren't installed
+	let expanded = shellexpand::full(pattern)
+		.map_err(|err| log::debug!("couldn't expand '{pattern}': {err}"))
+		.ok()?;
+
+	glob(&expanded)
+		.map_err(|err| log::error!("couldn't expand glob '{pattern}': {err}"))
+		.ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("https://example.com/", Some("Example"), Some("Example"))]
+	#[case("https://example.com/", None, Some("https://example.com/"))]
+	#[case("https://example.com/", Some(" "), Some("https://example.com/"))]
+	#[case("file:///home/user/notes.html", Some("notes"), Some("notes"))]
+	#[case("place:sort=8", Some("Recent Tags"), None)]
+	#[case("javascript:alert(1)", Some("Script"), None)]
+	fn should_create_entry(#[case] url: &str, #[case] title: Option<&str>, #[case] expected: Option<&str>) {
+		let entry = Entry::new(url.to_owned(), title.map(str::to_owned), Kind::Bookmark);
+		assert_eq!(expected, entry.as_ref().map(|e| e.title.as_str()), "{url}");
+	}
+}
@@ -0,0 +1,147 @@
+//! Browser provider.
+//! Searches the bookmarks and history of Firefox and Chromium-based browsers
+//! and opens the selected page in the default browser.
+//!
+//! Profiles are found in the browsers' standard locations, which can be
+//! configured. Firefox profiles are read from `places.sqlite`, Chromium
+//! profiles from their `Bookmarks` file and `History` database.
+//!
+//! Browsers keep their databases locked while running, so they are copied
+//! to a temporary directory before reading. The index is built on a
+//! separate thread on startup and rebuilt periodically.
+
+use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::get_text_score, *};
+use index::{Entry, Kind};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::sync::{mpsc::Sender, Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+mod chromium;
+mod database;
+mod firefox;
+mod index;
+
+const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
+
+pub fn register_plugins(registry: &mut PluginRegistry) {
+	let definition = plugin("browser").with_provider(Box::new(get_provider));
+
+	registry.register(definition);
+}
+
+fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
+	let config = Arc::new(config_adapter.get::<Config>(DEFAULT_CONFIG));
+
+	let entries = Arc::new(RwLock::new(vec![]));
+	start_indexing(config.clone(), entries.clone());
+
+	Box::new(BrowserProvider { config, entries })
+}
+
+/// Reads the profiles on a new thread, then again after each refresh interval.
+fn start_indexing(config: Arc<Config>, entries: Arc<RwLock<Vec<Entry>>>) {
+	thread::spawn(move || loop {
+		let loaded = index::load(get_profiles_config(&config), config.max_history);
+		log::debug!("indexed {} bookmarks and history entries", loaded.len());
+
+		*entries.write().expect("thread holding the lock can't panic") = loaded;
+
+		match config.refresh_interval {
+			Some(seconds) => thread::sleep(Duration::from_secs(seconds)),
+			None => break,
+		}
+	});
+}
+
+#[cfg(target_os = "linux")]
+fn get_profiles_config(config: &Config) -> &ProfilesConfig {
+	&config.linux
+}
+
+#[cfg(windows)]
+fn get_profiles_config(config: &Config) -> &ProfilesConfig {
+	&config.windows
+}
+
+struct BrowserProvider {
+	config: Arc<Config>,
+	entries: Arc<RwLock<Vec<Entry>>>,
+}
+
+impl Provider for BrowserProvider {
+	fn query(&self, query: &str) -> ProviderResult {
+		let entries = self.entries.read().expect("thread holding the lock can't panic");
+		let text = query.trim();
+
+		let hits = entries
+			.iter()
+			.enumerate()
+			.filter_map(|(i, entry)| Some((Reverse(get_score(entry, text)?), i)))
+			.k_smallest(self.config.max_hits)
+			.map(|(Reverse(score), i)| self.get_hit(&entries[i], score))
+			.collect();
+
+		ProviderResult::new(hits)
+	}
+}
+
+impl BrowserProvider {
+	fn get_hit(&self, entry: &Entry, score: u32) -> Arc<dyn Hit> {
+		let subtitles = &self.config.subtitles;
+		let kind = match entry.kind {
+			Kind::Bookmark => &subtitles.bookmark,
+			Kind::History => &subtitles.history,
+		};
+
+		let url = entry.url.clone();
+		let subtitle = format!("{kind} - {url}");
+		let hit = SimpleHit::new(&*entry.title, subtitle, move |_, s| do_open(&url, s));
+
+		Arc::new(hit.with_score(score))
+	}
+}
+
+/// Matches the query against both title and URL, using the better score.
+fn get_score(entry: &Entry, query: &str) -> Option<u32> {
+	let title = get_text_score(&entry.title, query);
+	let url = get_text_score(&entry.url, query);
+
+	title.max(url)
+}
+
+fn do_open(url: &str, sender: &Sender<FrontendMessage>) {
+	log::debug!("opening URL '{url}'");
+
+	if let Err(err) = open::that(url) {
+		log::error!("unable to open URL: {err}");
+	}
+
+	sender.send(FrontendMessage::Hide).ok();
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+	pub max_hits: usize,
+	pub max_history: usize,
+	pub refresh_interval: Option<u64>,
+	pub subtitles: SubtitlesConfig,
+	#[cfg(target_os = "linux")]
+	pub linux: ProfilesConfig,
+	#[cfg(windows)]
+	pub windows: ProfilesConfig,
+}
+
+#[derive(Deserialize, Debug)]
+struct SubtitlesConfig {
+	pub bookmark: String,
+	pub history: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProfilesConfig {
+	pub firefox_profiles: Vec<String>,
+	pub chromium_profiles: Vec<String>,
+}
@@ -0,0 +1,182 @@
+//! Evaluation of expressions, using variables and functions defined in the
+//! config, like `tax = 0.19` or `net(x) = x / (1 + tax)`.
+
+use crate::format::Number;
+use crate::integer;
+use anyhow::{bail, Result};
+use mexprp::{Answer, Calculation, Context, EvalError, MathError, Term};
+use num_bigint::BigInt;
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub struct Calculator {
+	/// Variables with integer values, so calculations using them stay exact.
+	integers: HashMap<String, BigInt>,
+	context: Context<f64>,
+}
+
+impl Default for Calculator {
+	fn default() -> Self {
+		Self {
+			integers: HashMap::new(),
+			context: Context::new(),
+		}
+	}
+}
+
+impl Calculator {
+	/// Creates a calculator with the given definitions. Each definition may
+	/// use the ones before it.
+	pub fn new(definitions: &[String]) -> Self {
+		let mut calculator = Self::default();
+
+		for definition in definitions {
+			if let Err(err) = calculator.define(definition) {
+				log::error!("invalid calculator definition '{definition}': {err}");
+			}
+		}
+
+		calculator
+	}
+
+	fn define(&mut self, definition: &str) -> Result<()> {
+		let Some((head, body)) = definition.split_once('=') else {
+			bail!("expected `name = expression` or `name(parameters) = expression`");
+		};
+
+		let (head, body) = (head.trim(), body.trim().to_owned());
+
+		let Some((name, parameters)) = head.strip_suffix(')').and_then(|h| h.split_once('(')) else {
+			let Some(value) = self.eval(&body).filter(|_| is_identifier(head)) else {
+				bail!("unable to evaluate '{body}' or invalid name '{head}'");
+			};
+
+			self.set_variable(head, &value);
+			return Ok(());
+		};
+
+		let parameters = parameters.split(',').map(|p| p.trim().to_owned()).collect::<Vec<_>>();
+
+		if !is_identifier(name.trim()) || !parameters.iter().all(|p| is_identifier(p)) {
+			bail!("invalid function name or parameters");
+		}
+
+		// catch syntax errors now, rather than every time the function is used
+		let mut test_scope = self.context.clone();
+		parameters.iter().for_each(|p| test_scope.set_var(p, 1.0));
+
+		if let Err(EvalError::ParseError { .. }) = mexprp::eval_ctx(&body, &test_scope) {
+			bail!("unable to parse '{body}'");
+		}
+
+		// functions can only use definitions before them, so they can't recurse forever
+		let scope = self.context.clone();
+
+		let function = move |args: &[Term<f64>], ctx: &Context<f64>| -> Calculation<f64> {
+			if args.len() != parameters.len() {
+				return Err(MathError::IncorrectArguments);
+			}
+
+			let mut scope = scope.clone();
+
+			for (parameter, arg) in parameters.iter().zip(args) {
+				match arg.eval_ctx(ctx)? {
+					Answer::Single(value) => scope.set_var(parameter, value),
+					Answer::Multiple(_) => return Err(MathError::IncorrectArguments),
+				}
+			}
+
+			match mexprp::eval_ctx(&body, &scope) {
+				Ok(answer) => Ok(answer),
+				Err(EvalError::MathError { error }) => Err(error),
+				Err(EvalError::ParseError { .. }) => Err(MathError::IncorrectArguments),
+			}
+		};
+
+		self.context.set_func(name.trim(), function);
+		Ok(())
+	}
+
+	pub fn set_variable(&mut self, name: &str, value: &Number) {
+		match value {
+			Number::Integer(integer) => self.integers.insert(name.to_owned(), integer.clone()),
+			Number::Float(_) => self.integers.remove(name),
+		};
+
+		self.context.set_var(name, value.to_f64());
+	}
+
+	/// Calculates integers exactly where possible, everything else as floats.
+	pub fn eval(&self, expression: &str) -> Option<Number> {
+		if let Some(result) = integer::eval(expression, &self.integers) {
+			return Some(Number::Integer(result));
+		}
+
+		let result = match mexprp::eval_ctx(&integer::expand_literals(expression), &self.context) {
+			Ok(Answer::Single(result)) => result,
+			Ok(Answer::Multiple(results)) => results.into_iter().next()?,
+			_ => return None,
+		};
+
+		result.is_finite().then_some(Number::Float(result))
+	}
+
+	pub fn eval_number(&self, expression: &str) -> Option<f64> {
+		self.eval(expression).map(|n| n.to_f64())
+	}
+}
+
+fn is_identifier(name: &str) -> bool {
+	name.starts_with(char::is_alphabetic) && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	fn get_calculator() -> Calculator {
+		let definitions = [
+			"tax = 0.25",
+			"big = 2 ^ 70",
+			"gross(x) = x * (1 + tax)",
+			"hypot(a, b) = sqrt(a^2 + b^2)",
+			"twice(x) = 2 * gross(x)",
+		];
+
+		Calculator::new(&definitions.map(str::to_owned))
+	}
+
+	#[rstest]
+	#[case("tax * 100", Number::Float(25.0))]
+	#[case("big + 1", Number::Integer(BigInt::from(2).pow(70) + 1))]
+	#[case("gross(100)", Number::Float(125.0))]
+	#[case("hypot(3, 4)", Number::Float(5.0))]
+	#[case("twice(50)", Number::Float(125.0))]
+	fn should_use_definitions(#[case] expression: &str, #[case] expected: Number) {
+		assert_eq!(Some(expected), get_calculator().eval(expression), "{expression}");
+	}
+
+	#[rstest]
+	#[case("= 5")]
+	#[case("5 = 5")]
+	#[case("x")]
+	#[case("f(1) = 1")]
+	#[case("f(x = x + (")]
+	#[case("f(x) = x +* (")]
+	fn should_reject_definition(#[case] definition: &str) {
+		assert!(Calculator::default().define(definition).is_err(), "{definition}");
+	}
+
+	#[test]
+	fn should_use_ans() {
+		let mut calculator = get_calculator();
+
+		calculator.set_variable("ans", &Number::Integer(BigInt::from(2).pow(80)));
+		let expected = Number::Integer(BigInt::from(2).pow(81));
+		assert_eq!(Some(expected), calculator.eval("ans * 2"));
+
+		calculator.set_variable("ans", &Number::Float(0.5));
+		assert_eq!(Some(Number::Float(1.5)), calculator.eval("ans + 1"));
+	}
+}
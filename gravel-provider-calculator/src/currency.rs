@@ -0,0 +1,87 @@
+use crate::units::{Dimension, Unit};
+use anyhow::Result;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Exchange rates read from a file, like the ones returned by
+/// https://api.frankfurter.app/latest.
+#[derive(Deserialize, Debug)]
+struct RatesFile {
+	base: String,
+	date: Option<String>,
+	/// How much of each currency one unit of the base currency buys.
+	rates: HashMap<String, f64>,
+}
+
+#[derive(Clone, Default)]
+pub struct Rates {
+	pub currencies: Vec<Unit>,
+	pub date: Option<String>,
+}
+
+/// Reads the rates file, then reuses it until the file changes.
+pub struct RatesCache {
+	path: Option<PathBuf>,
+	cached: RefCell<Option<(SystemTime, Rates)>>,
+}
+
+impl RatesCache {
+	pub fn new(path: Option<PathBuf>) -> Self {
+		Self {
+			path,
+			cached: RefCell::new(None),
+		}
+	}
+
+	pub fn get(&self) -> Rates {
+		let Some(path) = &self.path else {
+			return Rates::default();
+		};
+
+		// a missing file just means currency conversion isn't set up
+		let Ok(modified) = fs::metadata(path).and_then(|m| m.modified()) else {
+			return Rates::default();
+		};
+
+		let mut cached = self.cached.borrow_mut();
+
+		match &*cached {
+			Some((time, rates)) if *time == modified => rates.clone(),
+			_ => {
+				let rates = read_rates(path).unwrap_or_else(|err| {
+					log::error!("unable to read currency rates from {path:?}: {err:#}");
+					Rates::default()
+				});
+
+				log::debug!("read {} currency rates from {path:?}", rates.currencies.len());
+				cached.insert((modified, rates)).1.clone()
+			}
+		}
+	}
+}
+
+fn read_rates(path: &Path) -> Result<Rates> {
+	let file = serde_json::from_slice::<RatesFile>(&fs::read(path)?)?;
+
+	let currencies = file
+		.rates
+		.into_iter()
+		.filter(|(_, rate)| *rate > 0.0)
+		.chain([(file.base, 1.0)])
+		.map(|(symbol, rate)| Unit {
+			symbol: symbol.to_uppercase(),
+			dimension: Dimension::Currency,
+			factor: 1.0 / rate,
+			offset: 0.0,
+		})
+		.collect();
+
+	Ok(Rates {
+		currencies,
+		date: file.date,
+	})
+}
@@ -0,0 +1,380 @@
+//! Formatting of calculated numbers, in decimal or scientific notation,
+//! or in another base for queries like `255 in hex`.
+//!
+//! Results can also be offered in alternate forms, like `1/3` for `1 / 3`.
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{FromPrimitive, ToPrimitive};
+use serde::Deserialize;
+
+/// More digits than this are beyond the precision of [`f64`].
+const MAX_PRECISION: u32 = 15;
+
+/// Floats at least this large are shown in scientific notation by [`Notation::Auto`].
+const SCIENTIFIC_THRESHOLD: f64 = 1e21;
+
+/// Fractions with larger denominators aren't shown, they wouldn't be readable.
+const MAX_DENOMINATOR: i64 = 1_000_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+	/// Result of a calculation that only involved integers, which is exact.
+	Integer(BigInt),
+	Float(f64),
+}
+
+impl Number {
+	pub fn to_f64(&self) -> f64 {
+		match self {
+			Number::Integer(integer) => integer.to_f64().unwrap_or(f64::NAN),
+			Number::Float(float) => *float,
+		}
+	}
+
+	/// Returns the integer, or the float if it has no fractional part.
+	fn to_integer(&self) -> Option<BigInt> {
+		match self {
+			Number::Integer(integer) => Some(integer.clone()),
+			Number::Float(float) if float.fract() == 0.0 => BigInt::from_f64(*float),
+			Number::Float(_) => None,
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Notation {
+	/// Scientific notation for floats too large or small to show otherwise.
+	#[default]
+	Auto,
+	Scientific,
+	Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+	Binary,
+	Octal,
+	Decimal,
+	Hexadecimal,
+}
+
+impl Base {
+	fn radix(self) -> u32 {
+		match self {
+			Self::Binary => 2,
+			Self::Octal => 8,
+			Self::Decimal => 10,
+			Self::Hexadecimal => 16,
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"bin" | "binary" => Some(Self::Binary),
+			"oct" | "octal" => Some(Self::Octal),
+			"dec" | "decimal" => Some(Self::Decimal),
+			"hex" | "hexadecimal" => Some(Self::Hexadecimal),
+			_ => None,
+		}
+	}
+}
+
+/// Ways to show a result in addition to the configured notation.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Form {
+	Fraction,
+	Hex,
+	Oct,
+	Bin,
+	Scientific,
+}
+
+#[derive(Debug, Clone)]
+pub struct Format {
+	/// Digits after the decimal point.
+	pub precision: u32,
+	pub thousands_separator: Option<String>,
+	pub notation: Notation,
+}
+
+impl Default for Format {
+	fn default() -> Self {
+		Self {
+			precision: 10,
+			thousands_separator: None,
+			notation: Notation::Auto,
+		}
+	}
+}
+
+impl Format {
+	/// Formats the number without thousands separators, so it can be copied.
+	pub fn format(&self, number: &Number) -> String {
+		let precision = self.precision.min(MAX_PRECISION);
+
+		match (number, self.notation) {
+			(Number::Integer(integer), Notation::Scientific) => format_scientific_integer(integer, precision),
+			(Number::Integer(integer), _) => integer.to_string(),
+			(Number::Float(float), Notation::Scientific) => format_scientific(*float, precision),
+			(Number::Float(float), Notation::Auto) if needs_scientific(*float, precision) => {
+				format_scientific(*float, precision)
+			}
+			(Number::Float(float), _) => (round(*float, precision) + 0.0).to_string(),
+		}
+	}
+
+	/// Formats the number in the given base, with a prefix like `0x`.
+	/// Only works for integers.
+	pub fn format_base(&self, number: &Number, base: Base) -> Option<String> {
+		let integer = number.to_integer()?;
+		let sign = get_sign(&integer);
+		let magnitude = integer.magnitude();
+
+		let formatted = match base {
+			Base::Binary => format!("{sign}0b{magnitude:b}"),
+			Base::Octal => format!("{sign}0o{magnitude:o}"),
+			Base::Decimal => return Some(self.format(&Number::Integer(integer))),
+			Base::Hexadecimal => format!("{sign}0x{magnitude:x}"),
+		};
+
+		Some(formatted)
+	}
+
+	/// Formats the number in an alternate form. Returns nothing if the form
+	/// doesn't apply to the number or would look just like the regular result.
+	pub fn format_form(&self, number: &Number, form: Form) -> Option<String> {
+		let base = match form {
+			Form::Fraction => return format_fraction(number),
+			Form::Scientific => {
+				let scientific = Format {
+					notation: Notation::Scientific,
+					..self.clone()
+				};
+
+				let formatted = scientific.format(number);
+				return Some(formatted).filter(|f| *f != self.format(number));
+			}
+			Form::Hex => Base::Hexadecimal,
+			Form::Oct => Base::Octal,
+			Form::Bin => Base::Binary,
+		};
+
+		// single digits like `0x9` add nothing over `9`
+		if number.to_integer()?.magnitude() < &BigUint::from(base.radix()) {
+			return None;
+		}
+
+		self.format_base(number, base)
+	}
+
+	/// Inserts the thousands separator into the integer part of a formatted number.
+	pub fn group_thousands(&self, formatted: &str) -> String {
+		let Some(separator) = &self.thousands_separator else {
+			return formatted.to_owned();
+		};
+
+		let (sign, unsigned) = match formatted.strip_prefix('-') {
+			Some(unsigned) => ("-", unsigned),
+			None => ("", formatted),
+		};
+
+		// scientific notation and prefixed bases are left alone
+		let end = unsigned.find(|c: char| !c.is_ascii_digit()).unwrap_or(unsigned.len());
+		if unsigned[end..].contains(|c: char| c.is_ascii_alphabetic()) {
+			return formatted.to_owned();
+		}
+
+		let (digits, rest) = unsigned.split_at(end);
+		let mut grouped = String::with_capacity(formatted.len() + digits.len() / 3 * separator.len());
+
+		for (i, digit) in digits.chars().enumerate() {
+			if i > 0 && (digits.len() - i) % 3 == 0 {
+				grouped.push_str(separator);
+			}
+
+			grouped.push(digit);
+		}
+
+		format!("{sign}{grouped}{rest}")
+	}
+}
+
+pub(crate) fn round(number: f64, precision: u32) -> f64 {
+	let factor = 10_f64.powi(precision as i32);
+	let scaled = number * factor;
+
+	// such numbers have no fractional digits left to round
+	if !scaled.is_finite() || scaled.abs() >= 2_f64.powi(f64::MANTISSA_DIGITS as i32) {
+		return number;
+	}
+
+	scaled.round() / factor
+}
+
+/// Finds a simple fraction that equals the number, like `2/3`.
+fn format_fraction(number: &Number) -> Option<String> {
+	let Number::Float(value) = *number else {
+		return None;
+	};
+
+	if value.fract() == 0.0 || value.abs() >= MAX_DENOMINATOR as f64 {
+		return None;
+	}
+
+	// approximate using continued fractions, accepting only results that round to the same float
+	let magnitude = value.abs();
+	let (mut numerator, mut previous_numerator) = (1_i64, 0_i64);
+	let (mut denominator, mut previous_denominator) = (0_i64, 1_i64);
+	let mut remainder = magnitude;
+
+	loop {
+		let term = remainder.floor() as i64;
+		let next_numerator = term.checked_mul(numerator)?.checked_add(previous_numerator)?;
+		let next_denominator = term.checked_mul(denominator)?.checked_add(previous_denominator)?;
+
+		if next_denominator > MAX_DENOMINATOR {
+			return None;
+		}
+
+		(numerator, previous_numerator) = (next_numerator, numerator);
+		(denominator, previous_denominator) = (next_denominator, denominator);
+
+		if (numerator as f64 / denominator as f64 - magnitude).abs() <= f64::EPSILON * magnitude {
+			let sign = if value < 0.0 { "-" } else { "" };
+			return Some(format!("{sign}{numerator}/{denominator}"));
+		}
+
+		remainder = 1.0 / (remainder - term as f64);
+
+		if !remainder.is_finite() {
+			return None;
+		}
+	}
+}
+
+/// Whether the number is too large to show exactly, or so small that rounding would lose it.
+fn needs_scientific(number: f64, precision: u32) -> bool {
+	let magnitude = number.abs();
+
+	magnitude >= SCIENTIFIC_THRESHOLD || (magnitude != 0.0 && round(magnitude, precision) == 0.0)
+}
+
+fn format_scientific(number: f64, precision: u32) -> String {
+	let formatted = format!("{:.*e}", precision as usize, number);
+	let (mantissa, exponent) = formatted.split_once('e').expect("exponent is always present");
+
+	format!("{}e{exponent}", trim_fraction(mantissa))
+}
+
+/// Formats integers in scientific notation from their digits, so large ones don't lose precision.
+fn format_scientific_integer(integer: &BigInt, precision: u32) -> String {
+	let digits = integer.magnitude().to_string();
+	let sign = get_sign(integer);
+	let kept = (precision as usize + 1).min(digits.len());
+
+	let mut mantissa = digits[..kept].to_owned();
+	let mut exponent = digits.len() - 1;
+
+	if digits[kept..].starts_with(['5', '6', '7', '8', '9']) {
+		let rounded = (mantissa.parse::<BigInt>().expect("only digits") + 1u32).to_string();
+
+		// rounding up 999 results in 1000, which has one digit too many
+		if rounded.len() > mantissa.len() {
+			exponent += 1;
+		}
+
+		mantissa = rounded[..kept].to_owned();
+	}
+
+	let (first, fraction) = mantissa.split_at(1);
+	let mantissa = trim_fraction(&format!("{first}.{fraction}")).to_owned();
+
+	format!("{sign}{mantissa}e{exponent}")
+}
+
+fn get_sign(integer: &BigInt) -> &'static str {
+	match integer.sign() {
+		Sign::Minus => "-",
+		_ => "",
+	}
+}
+
+fn trim_fraction(number: &str) -> &str {
+	match number.contains('.') {
+		true => number.trim_end_matches('0').trim_end_matches('.'),
+		false => number,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	fn get_format(precision: u32, separator: Option<&str>, notation: Notation) -> Format {
+		Format {
+			precision,
+			thousands_separator: separator.map(str::to_owned),
+			notation,
+		}
+	}
+
+	#[rstest]
+	#[case(Number::Float(1.0 / 3.0), 2, Notation::Auto, "0.33")]
+	#[case(Number::Float(-0.0001), 2, Notation::Decimal, "0")]
+	#[case(Number::Float(1e-12), 10, Notation::Auto, "1e-12")]
+	#[case(Number::Float(1.5e22), 10, Notation::Auto, "1.5e22")]
+	#[case(Number::Float(1234.5678), 2, Notation::Scientific, "1.23e3")]
+	#[case(Number::Integer(BigInt::from(10).pow(30)), 10, Notation::Auto, "1000000000000000000000000000000")]
+	#[case(Number::Integer(BigInt::from(123456)), 2, Notation::Scientific, "1.23e5")]
+	#[case(Number::Integer(BigInt::from(-99960)), 2, Notation::Scientific, "-1e5")]
+	#[case(Number::Integer(BigInt::from(5)), 10, Notation::Scientific, "5e0")]
+	fn should_format(
+		#[case] number: Number,
+		#[case] precision: u32,
+		#[case] notation: Notation,
+		#[case] expected: &str,
+	) {
+		assert_eq!(expected, get_format(precision, None, notation).format(&number));
+	}
+
+	#[rstest]
+	#[case(Number::Integer(BigInt::from(255)), Base::Hexadecimal, Some("0xff"))]
+	#[case(Number::Integer(BigInt::from(-8)), Base::Octal, Some("-0o10"))]
+	#[case(Number::Float(10.0), Base::Binary, Some("0b1010"))]
+	#[case(Number::Integer(BigInt::from(255)), Base::Decimal, Some("255"))]
+	#[case(Number::Float(0.5), Base::Hexadecimal, None)]
+	fn should_format_base(#[case] number: Number, #[case] base: Base, #[case] expected: Option<&str>) {
+		assert_eq!(expected, Format::default().format_base(&number, base).as_deref());
+	}
+
+	#[rstest]
+	#[case(Number::Float(1.0 / 3.0), Form::Fraction, Some("1/3"))]
+	#[case(Number::Float(0.1 + 0.2), Form::Fraction, Some("3/10"))]
+	#[case(Number::Float(-2.75), Form::Fraction, Some("-11/4"))]
+	#[case(Number::Float(std::f64::consts::PI), Form::Fraction, None)]
+	#[case(Number::Integer(BigInt::from(3)), Form::Fraction, None)]
+	#[case(Number::Integer(BigInt::from(255)), Form::Hex, Some("0xff"))]
+	#[case(Number::Integer(BigInt::from(9)), Form::Hex, None)]
+	#[case(Number::Integer(BigInt::from(-2)), Form::Bin, Some("-0b10"))]
+	#[case(Number::Float(0.5), Form::Oct, None)]
+	#[case(Number::Float(1234.5), Form::Scientific, Some("1.2345e3"))]
+	#[case(Number::Float(1.5e22), Form::Scientific, None)]
+	fn should_format_form(#[case] number: Number, #[case] form: Form, #[case] expected: Option<&str>) {
+		assert_eq!(expected, Format::default().format_form(&number, form).as_deref());
+	}
+
+	#[rstest]
+	#[case("1234567.891", Some(","), "1,234,567.891")]
+	#[case("-123456", Some(" "), "-123 456")]
+	#[case("123", Some(","), "123")]
+	#[case("1.5e22", Some(","), "1.5e22")]
+	#[case("0xffffff", Some(","), "0xffffff")]
+	#[case("1234567", None, "1234567")]
+	fn should_group_thousands(#[case] formatted: &str, #[case] separator: Option<&str>, #[case] expected: &str) {
+		let format = get_format(10, separator, Notation::Auto);
+		assert_eq!(expected, format.group_thousands(formatted));
+	}
+}
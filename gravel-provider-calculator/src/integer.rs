@@ -0,0 +1,340 @@
+//! Exact evaluation of integer expressions, which supports hexadecimal,
+//! octal and binary literals as well as bitwise operators.
+//!
+//! Operators, from lowest to highest precedence:
+//! `|`, `xor`, `&`, `<<` `>>`, `+` `-`, `*` `/` `%`, unary `-` `~`, `^` (power).
+//!
+//! Anything that can't be calculated exactly, such as `1 / 3`, is left to
+//! the floating point calculator.
+//!
+//! Variables can be used as long as their value is an integer.
+
+use num_bigint::BigInt;
+use num_traits::Zero;
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// Largest exponent or shift allowed, so a typo can't make us
+/// calculate a number with billions of digits.
+const MAX_EXPONENT: u32 = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+	Number(BigInt),
+	Operator(&'static str),
+}
+
+const OPERATORS: &[&str] = &["<<", ">>", "+", "-", "*", "/", "%", "^", "&", "|", "~", "(", ")"];
+
+pub fn eval(expression: &str, variables: &HashMap<String, BigInt>) -> Option<BigInt> {
+	let tokens = tokenize(expression, variables)?;
+	let mut parser = Parser { tokens, position: 0 };
+
+	let value = parser.parse_or()?;
+
+	match parser.position == parser.tokens.len() {
+		true => Some(value),
+		false => None,
+	}
+}
+
+/// Replaces hexadecimal, octal and binary literals with their decimal
+/// value, so that other calculators understand them.
+pub fn expand_literals(expression: &str) -> String {
+	let mut result = String::with_capacity(expression.len());
+	let mut chars = expression.char_indices().peekable();
+	let mut previous = None;
+
+	while let Some(&(i, c)) = chars.peek() {
+		let is_word_start = !previous.is_some_and(|p: char| p.is_alphanumeric() || p == '.');
+
+		if c == '0' && is_word_start {
+			let mut lookahead = chars.clone();
+
+			if let Some(number) = parse_prefixed(expression, i, &mut lookahead) {
+				result.push_str(&number.to_string());
+				chars = lookahead;
+				previous = Some('0');
+				continue;
+			}
+		}
+
+		chars.next();
+		result.push(c);
+		previous = Some(c);
+	}
+
+	result
+}
+
+fn tokenize(expression: &str, variables: &HashMap<String, BigInt>) -> Option<Vec<Token>> {
+	let mut tokens = vec![];
+	let mut chars = expression.char_indices().peekable();
+
+	while let Some(&(i, c)) = chars.peek() {
+		if c.is_whitespace() {
+			chars.next();
+			continue;
+		}
+
+		if c.is_ascii_digit() {
+			let number = match c {
+				'0' => parse_prefixed(expression, i, &mut chars),
+				_ => None,
+			};
+
+			let number = number.or_else(|| parse_digits(expression, &mut chars, 10))?;
+			tokens.push(Token::Number(number));
+			continue;
+		}
+
+		if c.is_alphabetic() {
+			let word = take_while(expression, &mut chars, |c| c.is_alphanumeric() || c == '_');
+
+			// functions and other variables are left to the floating point calculator
+			match word {
+				"xor" => tokens.push(Token::Operator("xor")),
+				_ => tokens.push(Token::Number(variables.get(word)?.clone())),
+			}
+
+			continue;
+		}
+
+		let operator = OPERATORS.iter().find(|o| expression[i..].starts_with(**o))?;
+		tokens.push(Token::Operator(operator));
+
+		for _ in 0..operator.len() {
+			chars.next();
+		}
+	}
+
+	Some(tokens)
+}
+
+/// Parses literals like `0xff`, `0o17` or `0b1010`, starting at the current position.
+fn parse_prefixed(expression: &str, start: usize, chars: &mut Peekable<CharIndices>) -> Option<BigInt> {
+	let radix = match expression[start..].get(..2)? {
+		"0x" | "0X" => 16,
+		"0o" | "0O" => 8,
+		"0b" | "0B" => 2,
+		_ => return None,
+	};
+
+	// only consume the prefix if digits follow, `0b` alone is no literal
+	let digits = expression[start + 2..].chars().next()?;
+	if !digits.is_digit(radix) {
+		return None;
+	}
+
+	chars.next();
+	chars.next();
+
+	parse_digits(expression, chars, radix)
+}
+
+fn parse_digits(expression: &str, chars: &mut Peekable<CharIndices>, radix: u32) -> Option<BigInt> {
+	let digits = take_while(expression, chars, |c| c.is_alphanumeric() || c == '_' || c == '.');
+
+	// reject floats like `1.5` and things like `2pi`, the floating point calculator handles those
+	if !digits.chars().all(|c| c.is_digit(radix) || c == '_') {
+		return None;
+	}
+
+	BigInt::parse_bytes(digits.replace('_', "").as_bytes(), radix)
+}
+
+fn take_while<'a>(expression: &'a str, chars: &mut Peekable<CharIndices>, predicate: impl Fn(char) -> bool) -> &'a str {
+	let start = chars.peek().map_or(expression.len(), |(i, _)| *i);
+	let mut end = start;
+
+	while let Some(&(i, c)) = chars.peek() {
+		if !predicate(c) {
+			break;
+		}
+
+		end = i + c.len_utf8();
+		chars.next();
+	}
+
+	&expression[start..end]
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	position: usize,
+}
+
+impl Parser {
+	fn peek_operator(&self, operators: &[&str]) -> Option<&'static str> {
+		match self.tokens.get(self.position) {
+			Some(Token::Operator(o)) if operators.contains(o) => Some(*o),
+			_ => None,
+		}
+	}
+
+	/// Parses a chain of left-associative binary operators.
+	fn parse_binary(
+		&mut self,
+		operators: &[&str],
+		next: fn(&mut Self) -> Option<BigInt>,
+		apply: fn(&str, BigInt, BigInt) -> Option<BigInt>,
+	) -> Option<BigInt> {
+		let mut value = next(self)?;
+
+		while let Some(operator) = self.peek_operator(operators) {
+			self.position += 1;
+			value = apply(operator, value, next(self)?)?;
+		}
+
+		Some(value)
+	}
+
+	fn parse_or(&mut self) -> Option<BigInt> {
+		self.parse_binary(&["|"], Self::parse_xor, |_, a, b| Some(a | b))
+	}
+
+	fn parse_xor(&mut self) -> Option<BigInt> {
+		self.parse_binary(&["xor"], Self::parse_and, |_, a, b| Some(a ^ b))
+	}
+
+	fn parse_and(&mut self) -> Option<BigInt> {
+		self.parse_binary(&["&"], Self::parse_shift, |_, a, b| Some(a & b))
+	}
+
+	fn parse_shift(&mut self) -> Option<BigInt> {
+		self.parse_binary(&["<<", ">>"], Self::parse_sum, |operator, a, b| {
+			let amount = to_exponent(&b)?;
+
+			match operator {
+				"<<" => Some(a << amount),
+				_ => Some(a >> amount),
+			}
+		})
+	}
+
+	fn parse_sum(&mut self) -> Option<BigInt> {
+		self.parse_binary(&["+", "-"], Self::parse_product, |operator, a, b| match operator {
+			"+" => Some(a + b),
+			_ => Some(a - b),
+		})
+	}
+
+	fn parse_product(&mut self) -> Option<BigInt> {
+		self.parse_binary(&["*", "/", "%"], Self::parse_unary, |operator, a, b| match operator {
+			"*" => Some(a * b),
+			_ if b.is_zero() => None,
+			"/" if !(&a % &b).is_zero() => None,
+			"/" => Some(a / b),
+			_ => Some(a % b),
+		})
+	}
+
+	fn parse_unary(&mut self) -> Option<BigInt> {
+		let Some(operator) = self.peek_operator(&["-", "~", "+"]) else {
+			return self.parse_power();
+		};
+
+		self.position += 1;
+		let value = self.parse_unary()?;
+
+		match operator {
+			"-" => Some(-value),
+			"~" => Some(!value),
+			_ => Some(value),
+		}
+	}
+
+	/// Right-associative and binds tighter than unary minus, so `-2 ^ 2` is -4.
+	fn parse_power(&mut self) -> Option<BigInt> {
+		let base = self.parse_atom()?;
+
+		if self.peek_operator(&["^"]).is_none() {
+			return Some(base);
+		}
+
+		self.position += 1;
+		let exponent = to_exponent(&self.parse_unary()?)?;
+
+		// keep the result reasonably small
+		if base.bits() * exponent as u64 > MAX_EXPONENT as u64 * 64 {
+			return None;
+		}
+
+		Some(base.pow(exponent))
+	}
+
+	fn parse_atom(&mut self) -> Option<BigInt> {
+		let token = self.tokens.get(self.position)?.clone();
+		self.position += 1;
+
+		match token {
+			Token::Number(number) => Some(number),
+			Token::Operator("(") => {
+				let value = self.parse_or()?;
+				self.peek_operator(&[")"])?;
+				self.position += 1;
+
+				Some(value)
+			}
+			Token::Operator(_) => None,
+		}
+	}
+}
+
+fn to_exponent(value: &BigInt) -> Option<u32> {
+	u32::try_from(value).ok().filter(|e| *e <= MAX_EXPONENT)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("0xff + 1", "256")]
+	#[case("0b1010 | 0b0101", "15")]
+	#[case("0o17 & 0xff", "15")]
+	#[case("0xf0 xor 0xff", "15")]
+	#[case("1 << 4", "16")]
+	#[case("256 >> 2", "64")]
+	#[case("~0", "-1")]
+	#[case("-2 ^ 3", "-8")]
+	#[case("2 ^ 3 ^ 2", "512")]
+	#[case("2 ^ 100", "1267650600228229401496703205376")]
+	#[case("123456789012345678901234567890 * 10", "1234567890123456789012345678900")]
+	#[case("1_000_000 / 1000", "1000")]
+	#[case("(1 + 2) * 3 % 5", "4")]
+	#[case("ans * 2", "84")]
+	fn should_eval(#[case] expression: &str, #[case] expected: &str) {
+		let variables = HashMap::from([("ans".to_owned(), BigInt::from(42))]);
+		let actual = eval(expression, &variables).map(|n| n.to_string());
+		assert_eq!(Some(expected), actual.as_deref(), "{expression}");
+	}
+
+	#[rstest]
+	#[case("1 / 3")]
+	#[case("1.5 + 1")]
+	#[case("2pi")]
+	#[case("sqrt(4)")]
+	#[case("1 / 0")]
+	#[case("2 ^ -1")]
+	#[case("2 ^ 1000000000")]
+	#[case("1 1")]
+	#[case("(1 + 2")]
+	#[case("0xfg")]
+	#[case("x + 1")]
+	fn should_not_eval(#[case] expression: &str) {
+		assert_eq!(None, eval(expression, &HashMap::new()), "{expression}");
+	}
+
+	#[rstest]
+	#[case("0xff * 1.5", "255 * 1.5")]
+	#[case("sin(0b11)", "sin(3)")]
+	#[case("10x0", "10x0")]
+	#[case("0.0x1", "0.0x1")]
+	#[case("0b", "0b")]
+	fn should_expand_literals(#[case] expression: &str, #[case] expected: &str) {
+		assert_eq!(expected, expand_literals(expression));
+	}
+}
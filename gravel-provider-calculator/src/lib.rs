@@ -3,16 +3,37 @@
 //! Whenever the input can be parsed as a mathematical expression, shows the
 //! result as the first hit.
 //!
-//! Selecting the hit copies the calculated value to the system's clipboard.
+//! Integer calculations are exact, no matter how large the numbers get, and
+//! support hexadecimal, octal and binary literals like `0xff`, as well as the
+//! bitwise operators `&`, `|`, `xor`, `~`, `<<` and `>>`. Results can be shown
+//! in another base with queries like `255 in hex`.
+//!
+//! Also converts between units, for queries like `5 km in miles`,
+//! `100 F to C` or `2h30m in minutes`. Currencies can be converted as well,
+//! using exchange rates from a file that is read again whenever it changes.
+//!
+//! Variables and functions can be defined in the config, and `ans` refers to
+//! the last result that was copied. Results are also offered in alternate
+//! forms, like `1/3` or `0xff`, as additional hits.
+//!
+//! Selecting a hit copies the calculated value to the system's clipboard.
 
-use arboard::Clipboard;
+use calculator::Calculator;
+use currency::RatesCache;
+use format::{Base, Form, Format, Notation, Number};
 use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::MAX_SCORE, *};
-use mexprp::Answer;
 use serde::Deserialize;
 use std::{
-	cell::OnceCell,
+	path::PathBuf,
 	sync::{mpsc::Sender, Arc, Mutex},
 };
+use units::Dimension;
+
+mod calculator;
+mod currency;
+mod format;
+mod integer;
+mod units;
 
 const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
 
@@ -24,89 +45,153 @@ pub fn register_plugins(registry: &mut PluginRegistry) {
 
 fn get_provider(config: &PluginConfigAdapter) -> Box<dyn Provider> {
 	let plugin_config = config.get::<Config>(DEFAULT_CONFIG);
+	let rates_path = plugin_config.currency_rates.as_deref().and_then(expand_path);
+
+	let format = Format {
+		precision: plugin_config.precision,
+		thousands_separator: plugin_config.thousands_separator.clone(),
+		notation: plugin_config.notation,
+	};
 
 	Box::new(CalculatorProvider {
+		calculator: Calculator::new(&plugin_config.definitions),
 		config: plugin_config,
-		clipboard: OnceCell::new(),
+		format,
+		rates: RatesCache::new(rates_path),
+		ans: Arc::new(Mutex::new(None)),
 	})
 }
 
-fn create_clipboard() -> Option<Arc<Mutex<Clipboard>>> {
-	log::trace!("spawning clipboard instance");
-
-	match Clipboard::new() {
-		Err(err) => {
-			log::error!("unable to initialize clipboard: {err}");
-			None
-		}
-		Ok(clipboard) => Some(Arc::new(Mutex::new(clipboard))),
-	}
+fn expand_path(path: &str) -> Option<PathBuf> {
+	shellexpand::full(path)
+		.map(|p| PathBuf::from(&*p))
+		.map_err(|err| log::error!("couldn't expand currency_rates '{path}': {err}"))
+		.ok()
 }
 
 struct CalculatorProvider {
 	config: Config,
-	clipboard: OnceCell<Option<Arc<Mutex<Clipboard>>>>,
+	format: Format,
+	rates: RatesCache,
+	calculator: Calculator,
+	/// The last result that was copied.
+	ans: Arc<Mutex<Option<Number>>>,
 }
 
 impl CalculatorProvider {
-	fn get_clipboard(&self) -> Option<Arc<Mutex<Clipboard>>> {
-		self.clipboard.get_or_init(create_clipboard).clone()
+	/// Returns the calculator with `ans` set, if there is a last result.
+	fn get_calculator(&self) -> Calculator {
+		let mut calculator = self.calculator.clone();
+
+		if let Some(ans) = &*self.ans.lock().expect("thread holding the mutex can't panic") {
+			calculator.set_variable("ans", ans);
+		}
+
+		calculator
 	}
-}
 
-impl Provider for CalculatorProvider {
-	fn query(&self, query: &str) -> ProviderResult {
-		let query = query.trim();
-		let result = eval(query);
+	/// Returns a hit for the result, followed by one for each alternate form.
+	fn get_result_hits(&self, number: Number, value: String) -> Vec<Arc<dyn Hit>> {
+		let forms = &self.config.forms;
+		let alternates = forms.iter().filter_map(|f| self.format.format_form(&number, *f));
+
+		[value]
+			.into_iter()
+			.chain(alternates)
+			.enumerate()
+			.map(|(i, value)| {
+				let hit = self.get_result_hit(number.clone(), value, self.config.subtitle.clone());
+				Arc::new(hit.with_score(MAX_SCORE - i as u32)) as Arc<dyn Hit>
+			})
+			.collect()
+	}
+
+	/// Shows the result of queries like `255 in hex` in the requested base.
+	fn convert_base(&self, calculator: &Calculator, query: &str) -> Option<SimpleHit> {
+		let (expression, base) = units::split_query(query)?;
+		let base = Base::from_name(base.trim())?;
+		let number = calculator.eval(expression)?;
+		let value = self.format.format_base(&number, base)?;
+
+		Some(self.get_result_hit(number, value, self.config.subtitle.clone()))
+	}
+
+	/// Converts unit queries, titling the hit with the target unit's symbol.
+	fn convert(&self, calculator: &Calculator, query: &str) -> Option<SimpleHit> {
+		let rates = self.rates.get();
+		let (value, unit) = units::convert(query, &rates.currencies, calculator)?;
+		let number = Number::Float(value);
+		let value = self.format.format(&number);
 
-		let Some(result) = result else {
-			return ProviderResult::empty();
+		let subtitle = match (unit.dimension, rates.date) {
+			(Dimension::Currency, Some(date)) => format!("{} - rates from {date}", self.config.subtitle),
+			_ => self.config.subtitle.clone(),
 		};
 
-		if query == result || matches!(query, "e" | "pi" | "i") {
-			return ProviderResult::empty();
-		}
+		let title = format!("{} {}", self.format.group_thousands(&value), unit.symbol);
+		Some(self.get_hit(title, number, value, subtitle))
+	}
 
-		let clipboard = self.get_clipboard();
+	/// Titles the hit with the value, with thousands separators added.
+	fn get_result_hit(&self, number: Number, value: String, subtitle: String) -> SimpleHit {
+		self.get_hit(self.format.group_thousands(&value), number, value, subtitle)
+	}
 
-		let hit = SimpleHit::new(result, self.config.subtitle.clone(), move |h, s| {
-			do_copy(clipboard.clone(), h, s)
-		})
-		.with_score(MAX_SCORE);
+	/// Copies the value when selected, remembering the number as `ans`.
+	fn get_hit(&self, title: String, number: Number, value: String, subtitle: String) -> SimpleHit {
+		let ans = self.ans.clone();
 
-		ProviderResult::single(Arc::new(hit))
+		let hit = SimpleHit::new(title, subtitle, move |_, s| {
+			*ans.lock().expect("thread holding the mutex can't panic") = Some(number.clone());
+			do_copy(&value, s)
+		});
+
+		hit.with_score(MAX_SCORE)
 	}
 }
 
-fn eval(expression: &str) -> Option<String> {
-	match mexprp::eval(expression) {
-		Ok(Answer::Single(result)) => Some(result),
-		Ok(Answer::Multiple(results)) => results.into_iter().next(),
-		_ => None,
+impl Provider for CalculatorProvider {
+	fn query(&self, query: &str) -> ProviderResult {
+		let query = query.trim();
+		let calculator = self.get_calculator();
+
+		if let Some(number) = calculator.eval(query) {
+			let value = self.format.format(&number);
+
+			return match query == value || matches!(query, "e" | "pi" | "i") {
+				true => ProviderResult::empty(),
+				false => ProviderResult::new(self.get_result_hits(number, value)),
+			};
+		}
+
+		let hit = self
+			.convert_base(&calculator, query)
+			.or_else(|| self.convert(&calculator, query));
+
+		match hit {
+			Some(hit) => ProviderResult::single(Arc::new(hit)),
+			None => ProviderResult::empty(),
+		}
 	}
-	.map(|r| round(r, 10).to_string())
 }
 
-fn do_copy(clipboard: Option<Arc<Mutex<Clipboard>>>, hit: &SimpleHit, sender: &Sender<FrontendMessage>) {
-	let value = hit.get_title();
+fn do_copy(value: &str, sender: &Sender<FrontendMessage>) {
 	log::debug!("copying value to clipboard: {value}");
 
-	if let Some(clipboard_mutex) = clipboard {
-		let mut guard = clipboard_mutex.lock().expect("thread holding the mutex can't panic");
-		guard.set_text(value).ok();
-	}
+	clipboard::set_text(value);
 
 	sender.send(FrontendMessage::Hide).ok();
 }
 
-fn round(number: f64, precision: u32) -> f64 {
-	let factor = 10_u64.pow(precision) as f64;
-	(number * factor).round() / factor
-}
-
 #[derive(Deserialize, Debug)]
 struct Config {
 	pub subtitle: String,
+	pub currency_rates: Option<String>,
+	pub precision: u32,
+	pub thousands_separator: Option<String>,
+	pub notation: Notation,
+	pub forms: Vec<Form>,
+	pub definitions: Vec<String>,
 }
 
 #[cfg(test)]
@@ -129,8 +214,18 @@ mod tests {
 	#[case("round(2pi)", "6")]
 	#[case("sqrt(2)", "1.4142135624")]
 	#[case("sin(asin(0.5))", "0.5")]
+	#[case("0xff + 1", "256")]
+	#[case("0b1010 | 0b0101", "15")]
+	#[case("0xf0 xor 0xff", "15")]
+	#[case("1 << 10", "1024")]
+	#[case("~0o7", "-8")]
+	#[case("0xff * 1.5", "382.5")]
+	#[case("2 ^ 64 + 1", "18446744073709551617")]
+	#[case("10 ^ 30 * 1.5", "1.5e30")]
+	#[case("1 / 10 ^ 12", "1e-12")]
 	fn should_eval(#[case] expression: &str, #[case] expected: &str) {
-		let actual = eval(expression);
+		let calculator = Calculator::default();
+		let actual = calculator.eval(expression).map(|n| Format::default().format(&n));
 		assert_eq!(Some(expected), actual.as_deref(), "{expression}");
 	}
 
@@ -139,8 +234,9 @@ mod tests {
 	#[case("1 1")]
 	#[case("1 / 0")]
 	#[case("x + 5")]
+	#[case("0xfg")]
 	fn should_fail(#[case] expression: &str) {
-		let actual = eval(expression);
+		let actual = Calculator::default().eval(expression);
 		assert_eq!(None, actual, "{expression}");
 	}
 }
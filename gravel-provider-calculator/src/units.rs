@@ -0,0 +1,326 @@
+//! Conversion between units, for queries like `5 km in miles`.
+
+use crate::calculator::Calculator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+	Length,
+	Area,
+	Volume,
+	Mass,
+	Time,
+	Speed,
+	Data,
+	Temperature,
+	Currency,
+}
+
+const DIMENSIONS: &[Dimension] = &[
+	Dimension::Length,
+	Dimension::Area,
+	Dimension::Volume,
+	Dimension::Mass,
+	Dimension::Time,
+	Dimension::Speed,
+	Dimension::Data,
+	Dimension::Temperature,
+	Dimension::Currency,
+];
+
+/// Words separating the quantity from the target unit.
+const SEPARATORS: &[&str] = &[" in ", " to ", " as "];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+	pub symbol: String,
+	pub dimension: Dimension,
+	/// Size of the unit, in the dimension's base unit.
+	pub factor: f64,
+	/// Added after scaling, only used for temperatures.
+	pub offset: f64,
+}
+
+impl Unit {
+	fn to_base(&self, value: f64) -> f64 {
+		value * self.factor + self.offset
+	}
+
+	fn to_unit(&self, value: f64) -> f64 {
+		(value - self.offset) / self.factor
+	}
+}
+
+struct UnitDefinition {
+	/// The first name is used as the symbol.
+	names: &'static [&'static str],
+	dimension: Dimension,
+	factor: f64,
+	offset: f64,
+}
+
+const fn unit(names: &'static [&'static str], dimension: Dimension, factor: f64) -> UnitDefinition {
+	UnitDefinition {
+		names,
+		dimension,
+		factor,
+		offset: 0.0,
+	}
+}
+
+const FOOT: f64 = 0.3048;
+const MILE: f64 = 1609.344;
+const GALLON: f64 = 3.785411784;
+
+#[rustfmt::skip]
+const UNITS: &[UnitDefinition] = {
+	use Dimension::*;
+
+	&[
+		// base: meter
+		unit(&["m", "meter", "meters", "metre", "metres"], Length, 1.0),
+		unit(&["km", "kilometer", "kilometers", "kilometre", "kilometres"], Length, 1e3),
+		unit(&["cm", "centimeter", "centimeters", "centimetre", "centimetres"], Length, 1e-2),
+		unit(&["mm", "millimeter", "millimeters", "millimetre", "millimetres"], Length, 1e-3),
+		unit(&["µm", "um", "micrometer", "micrometers", "micron", "microns"], Length, 1e-6),
+		unit(&["nm", "nanometer", "nanometers"], Length, 1e-9),
+		unit(&["in", "inch", "inches", "\""], Length, FOOT / 12.0),
+		unit(&["ft", "foot", "feet", "'"], Length, FOOT),
+		unit(&["yd", "yard", "yards"], Length, FOOT * 3.0),
+		unit(&["mi", "mile", "miles"], Length, MILE),
+		unit(&["nmi", "nautical mile", "nautical miles"], Length, 1852.0),
+
+		// base: square meter
+		unit(&["m²", "m2", "sqm", "square meter", "square meters"], Area, 1.0),
+		unit(&["km²", "km2", "square kilometer", "square kilometers"], Area, 1e6),
+		unit(&["cm²", "cm2", "square centimeter", "square centimeters"], Area, 1e-4),
+		unit(&["ha", "hectare", "hectares"], Area, 1e4),
+		unit(&["ft²", "ft2", "sqft", "square foot", "square feet"], Area, FOOT * FOOT),
+		unit(&["ac", "acre", "acres"], Area, 4046.8564224),
+		unit(&["mi²", "mi2", "square mile", "square miles"], Area, MILE * MILE),
+
+		// base: liter
+		unit(&["l", "L", "liter", "liters", "litre", "litres"], Volume, 1.0),
+		unit(&["ml", "mL", "milliliter", "milliliters", "millilitre", "millilitres"], Volume, 1e-3),
+		unit(&["cl", "cL", "centiliter", "centiliters"], Volume, 1e-2),
+		unit(&["dl", "dL", "deciliter", "deciliters"], Volume, 1e-1),
+		unit(&["m³", "m3", "cubic meter", "cubic meters"], Volume, 1e3),
+		unit(&["gal", "gallon", "gallons"], Volume, GALLON),
+		unit(&["qt", "quart", "quarts"], Volume, GALLON / 4.0),
+		unit(&["pt", "pint", "pints"], Volume, GALLON / 8.0),
+		unit(&["cup", "cups"], Volume, GALLON / 16.0),
+		unit(&["fl oz", "floz", "fluid ounce", "fluid ounces"], Volume, GALLON / 128.0),
+
+		// base: kilogram
+		unit(&["kg", "kilogram", "kilograms", "kilo", "kilos"], Mass, 1.0),
+		unit(&["g", "gram", "grams"], Mass, 1e-3),
+		unit(&["mg", "milligram", "milligrams"], Mass, 1e-6),
+		unit(&["t", "tonne", "tonnes", "ton", "tons"], Mass, 1e3),
+		unit(&["lb", "lbs", "pound", "pounds"], Mass, 0.45359237),
+		unit(&["oz", "ounce", "ounces"], Mass, 0.45359237 / 16.0),
+		unit(&["st", "stone", "stones"], Mass, 0.45359237 * 14.0),
+
+		// base: second
+		unit(&["s", "sec", "secs", "second", "seconds"], Time, 1.0),
+		unit(&["ms", "millisecond", "milliseconds"], Time, 1e-3),
+		unit(&["µs", "us", "microsecond", "microseconds"], Time, 1e-6),
+		unit(&["ns", "nanosecond", "nanoseconds"], Time, 1e-9),
+		unit(&["min", "m", "mins", "minute", "minutes"], Time, 60.0),
+		unit(&["h", "hr", "hrs", "hour", "hours"], Time, 3600.0),
+		unit(&["d", "day", "days"], Time, 86400.0),
+		unit(&["wk", "w", "week", "weeks"], Time, 604800.0),
+		unit(&["yr", "y", "year", "years"], Time, 31557600.0),
+
+		// base: meters per second
+		unit(&["m/s", "mps"], Speed, 1.0),
+		unit(&["km/h", "kmh", "kph"], Speed, 1.0 / 3.6),
+		unit(&["mph"], Speed, MILE / 3600.0),
+		unit(&["kn", "kt", "knot", "knots"], Speed, 1852.0 / 3600.0),
+		unit(&["ft/s", "fps"], Speed, FOOT),
+
+		// base: byte
+		unit(&["B", "byte", "bytes"], Data, 1.0),
+		unit(&["bit", "bits"], Data, 1.0 / 8.0),
+		unit(&["kB", "KB", "kilobyte", "kilobytes"], Data, 1e3),
+		unit(&["MB", "megabyte", "megabytes"], Data, 1e6),
+		unit(&["GB", "gigabyte", "gigabytes"], Data, 1e9),
+		unit(&["TB", "terabyte", "terabytes"], Data, 1e12),
+		unit(&["PB", "petabyte", "petabytes"], Data, 1e15),
+		unit(&["KiB", "kibibyte", "kibibytes"], Data, 1024.0),
+		unit(&["MiB", "mebibyte", "mebibytes"], Data, 1048576.0),
+		unit(&["GiB", "gibibyte", "gibibytes"], Data, 1073741824.0),
+		unit(&["TiB", "tebibyte", "tebibytes"], Data, 1099511627776.0),
+		unit(&["PiB", "pebibyte", "pebibytes"], Data, 1125899906842624.0),
+		unit(&["kbit", "kilobit", "kilobits"], Data, 1e3 / 8.0),
+		unit(&["Mbit", "megabit", "megabits"], Data, 1e6 / 8.0),
+		unit(&["Gbit", "gigabit", "gigabits"], Data, 1e9 / 8.0),
+
+		// base: kelvin
+		unit(&["K", "kelvin"], Temperature, 1.0),
+		UnitDefinition { names: &["°C", "C", "celsius", "degC"], dimension: Temperature, factor: 1.0, offset: 273.15 },
+		UnitDefinition { names: &["°F", "F", "fahrenheit", "degF"], dimension: Temperature, factor: 5.0 / 9.0, offset: 273.15 - 32.0 * 5.0 / 9.0 },
+	]
+};
+
+/// Converts queries like `5 km in miles` or `2h30m to minutes`, returning
+/// the value in the target unit. `currencies` are looked up in addition to
+/// the built-in units.
+pub fn convert(query: &str, currencies: &[Unit], calculator: &Calculator) -> Option<(f64, Unit)> {
+	let (source, target) = split_query(query)?;
+
+	// names like `m` are shared by multiple units, so try each possible dimension
+	DIMENSIONS.iter().find_map(|dimension| {
+		let target = find_unit(target.trim(), *dimension, currencies)?;
+		let value = parse_quantity(source.trim(), *dimension, currencies, calculator)?;
+
+		Some((target.to_unit(value), target))
+	})
+}
+
+/// Splits the query at the last separator word.
+pub fn split_query(query: &str) -> Option<(&str, &str)> {
+	let (index, separator) = SEPARATORS
+		.iter()
+		.filter_map(|s| Some((query.rfind(s)?, s)))
+		.max_by_key(|(i, _)| *i)?;
+
+	Some((&query[..index], &query[index + separator.len()..]))
+}
+
+/// Parses the quantity and returns its value in the dimension's base unit.
+fn parse_quantity(text: &str, dimension: Dimension, currencies: &[Unit], calculator: &Calculator) -> Option<f64> {
+	parse_compound(text, dimension, currencies).or_else(|| parse_expression(text, dimension, currencies, calculator))
+}
+
+/// Parses sums like `2h30m` or `5 ft 3 in`.
+fn parse_compound(text: &str, dimension: Dimension, currencies: &[Unit]) -> Option<f64> {
+	let mut rest = text.trim_start();
+	let mut total = 0.0;
+	let mut parts = 0;
+
+	while !rest.is_empty() {
+		let number_len = rest
+			.find(|c: char| !c.is_ascii_digit() && c != '.')
+			.unwrap_or(rest.len());
+		let number = rest[..number_len].parse::<f64>().ok()?;
+		rest = rest[number_len..].trim_start();
+
+		let unit_len = rest
+			.find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+			.unwrap_or(rest.len());
+		let unit = find_unit(&rest[..unit_len], dimension, currencies)?;
+		rest = rest[unit_len..].trim_start();
+
+		total += unit.to_base(number);
+		parts += 1;
+	}
+
+	// offsets don't add up, `5°C 3°C` makes no sense
+	match parts {
+		0 => None,
+		1 => Some(total),
+		_ if dimension == Dimension::Temperature => None,
+		_ => Some(total),
+	}
+}
+
+/// Parses an expression followed by a unit, like `2 * 1.5 km`.
+fn parse_expression(text: &str, dimension: Dimension, currencies: &[Unit], calculator: &Calculator) -> Option<f64> {
+	// try the longest possible unit first, so `5 min` isn't read as `5 m` followed by garbage
+	text.char_indices().skip(1).find_map(|(i, _)| {
+		let unit = find_unit(text[i..].trim(), dimension, currencies)?;
+		let value = calculator.eval_number(&text[..i])?;
+
+		Some(unit.to_base(value))
+	})
+}
+
+/// Finds a unit of the dimension by name. Falls back to ignoring the case,
+/// as long as only a single unit matches.
+pub fn find_unit(name: &str, dimension: Dimension, currencies: &[Unit]) -> Option<Unit> {
+	if name.is_empty() {
+		return None;
+	}
+
+	if dimension == Dimension::Currency {
+		return currencies.iter().find(|u| u.symbol.eq_ignore_ascii_case(name)).cloned();
+	}
+
+	let units = UNITS.iter().filter(|u| u.dimension == dimension);
+
+	if let Some(unit) = units.clone().find(|u| u.names.contains(&name)) {
+		return Some(unit.into());
+	}
+
+	let mut matches = units.filter(|u| u.names.iter().any(|n| n.eq_ignore_ascii_case(name)));
+
+	match (matches.next(), matches.next()) {
+		(Some(unit), None) => Some(unit.into()),
+		_ => None,
+	}
+}
+
+impl From<&UnitDefinition> for Unit {
+	fn from(definition: &UnitDefinition) -> Self {
+		Self {
+			symbol: definition.names[0].to_owned(),
+			dimension: definition.dimension,
+			factor: definition.factor,
+			offset: definition.offset,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::format::round;
+	use rstest::rstest;
+
+	fn get_currencies() -> Vec<Unit> {
+		[("EUR", 1.0), ("USD", 1.25)]
+			.into_iter()
+			.map(|(symbol, rate)| Unit {
+				symbol: symbol.to_owned(),
+				dimension: Dimension::Currency,
+				factor: 1.0 / rate,
+				offset: 0.0,
+			})
+			.collect()
+	}
+
+	#[rstest]
+	#[case("5 km in miles", 3.1068559612, "mi")]
+	#[case("100 F to C", 37.7777777778, "°C")]
+	#[case("0 c in fahrenheit", 32.0, "°F")]
+	#[case("3 GiB in MB", 3221.225472, "MB")]
+	#[case("3 gib in mb", 3221.225472, "MB")]
+	#[case("2h30m in minutes", 150.0, "min")]
+	#[case("1h 30min 30s to h", 1.5083333333, "h")]
+	#[case("5 ft 3 in in cm", 160.02, "cm")]
+	#[case("2 * 1.5km in m", 3000.0, "m")]
+	#[case("90 m to h", 1.5, "h")]
+	#[case("1 gallon as l", 3.785411784, "l")]
+	#[case("100 km/h in mph", 62.1371192237, "mph")]
+	#[case("10 usd in eur", 8.0, "EUR")]
+	#[case("10 EUR to USD", 12.5, "USD")]
+	fn should_convert(#[case] query: &str, #[case] expected: f64, #[case] expected_symbol: &str) {
+		let (value, unit) = convert(query, &get_currencies(), &Calculator::default()).unwrap();
+
+		assert_eq!(expected, round(value, 10), "{query}");
+		assert_eq!(expected_symbol, unit.symbol, "{query}");
+	}
+
+	#[rstest]
+	#[case("5 km")]
+	#[case("5 km in kg")]
+	#[case("log in")]
+	#[case("5 km in")]
+	#[case("five km in miles")]
+	#[case("3 mb in Mbit in")]
+	#[case("5°C 3°C in F")]
+	#[case("10 gbp in eur")]
+	fn should_not_convert(#[case] query: &str) {
+		let calculator = Calculator::default();
+		assert_eq!(None, convert(query, &get_currencies(), &calculator), "{query}");
+	}
+}
@@ -0,0 +1,210 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use gravel_core::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NONCE_LEN: usize = 12;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+	pub text: String,
+	/// Seconds since the unix epoch.
+	pub copied: u64,
+}
+
+impl Entry {
+	pub fn new(text: String) -> Self {
+		let copied = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_secs();
+
+		Self { text, copied }
+	}
+
+	/// Time since the entry was last copied.
+	pub fn age(&self) -> Duration {
+		let copied = UNIX_EPOCH + Duration::from_secs(self.copied);
+		SystemTime::now().duration_since(copied).unwrap_or_default()
+	}
+}
+
+/// Clipboard entries, newest first.
+pub struct History {
+	entries: VecDeque<Entry>,
+	max_entries: usize,
+}
+
+impl History {
+	pub fn new(mut entries: Vec<Entry>, max_entries: usize) -> Self {
+		entries.truncate(max_entries);
+
+		Self {
+			entries: entries.into(),
+			max_entries,
+		}
+	}
+
+	pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+		self.entries.iter()
+	}
+
+	pub fn latest(&self) -> Option<&str> {
+		self.entries.front().map(|e| e.text.as_str())
+	}
+
+	/// Adds the text as the newest entry. Copying the same text
+	/// again moves its existing entry to the front.
+	pub fn push(&mut self, text: String) {
+		self.entries.retain(|e| e.text != text);
+		self.entries.push_front(Entry::new(text));
+		self.entries.truncate(self.max_entries);
+	}
+}
+
+/// Reads and writes the history file, optionally encrypted.
+pub struct Store {
+	path: PathBuf,
+	cipher: Option<ChaCha20Poly1305>,
+}
+
+impl Store {
+	pub fn new(path: PathBuf, key: Option<&Key>) -> Self {
+		Self {
+			path,
+			cipher: key.map(ChaCha20Poly1305::new),
+		}
+	}
+
+	pub fn load(&self) -> Result<Vec<Entry>> {
+		let data = match fs::read(&self.path) {
+			Ok(data) => data,
+			Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+			Err(err) => return Err(err.into()),
+		};
+
+		let json = match &self.cipher {
+			Some(cipher) => decrypt(cipher, &data)?,
+			None => data,
+		};
+
+		serde_json::from_slice(&json).context("history file is corrupt, or encrypted with a different key")
+	}
+
+	pub fn save(&self, history: &History) -> Result<()> {
+		let json = serde_json::to_vec(&history.entries)?;
+
+		let data = match &self.cipher {
+			Some(cipher) => encrypt(cipher, &json)?,
+			None => json,
+		};
+
+		write_private(&self.path, &data)
+	}
+}
+
+/// Reads the key from the file, or generates and saves a new one if it doesn't exist.
+pub fn get_key(path: &Path) -> Result<Key> {
+	match fs::read(path) {
+		Ok(key) if key.len() == 32 => return Ok(*Key::from_slice(&key)),
+		Ok(_) => bail!("key file {path:?} is invalid"),
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+		Err(err) => return Err(err.into()),
+	}
+
+	log::info!("generating new clipboard history key in {path:?}");
+
+	let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+	write_private(path, &key)?;
+
+	Ok(key)
+}
+
+fn encrypt(cipher: &ChaCha20Poly1305, plaintext: &[u8]) -> Result<Vec<u8>> {
+	let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+	let ciphertext = cipher
+		.encrypt(&nonce, plaintext)
+		.map_err(|_| anyhow!("unable to encrypt history"))?;
+
+	Ok([nonce.as_slice(), ciphertext.as_slice()].concat())
+}
+
+fn decrypt(cipher: &ChaCha20Poly1305, data: &[u8]) -> Result<Vec<u8>> {
+	if data.len() < NONCE_LEN {
+		bail!("history file is too short");
+	}
+
+	let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+
+	cipher
+		.decrypt(Nonce::from_slice(nonce), ciphertext)
+		.map_err(|_| anyhow!("unable to decrypt history, the key might have changed"))
+}
+
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+	storage::write_private(path, data).with_context(|| format!("unable to write {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::tempdir;
+
+	fn get_history() -> History {
+		let mut history = History::new(vec![], 3);
+
+		for text in ["one", "two", "three", "two", "four"] {
+			history.push(text.to_owned());
+		}
+
+		history
+	}
+
+	#[test]
+	fn should_push() {
+		let texts = get_history().entries().map(|e| e.text.clone()).collect::<Vec<_>>();
+
+		assert_eq!(vec!["four", "two", "three"], texts);
+	}
+
+	#[test]
+	fn should_round_trip() {
+		let dir = tempdir().unwrap();
+		let history = get_history();
+		let store = Store::new(dir.path().join("history"), None);
+		store.save(&history).unwrap();
+
+		assert_eq!(history.entries, store.load().unwrap());
+	}
+
+	#[test]
+	fn should_encrypt() {
+		let dir = tempdir().unwrap();
+		let key = get_key(&dir.path().join("key")).unwrap();
+		let history = get_history();
+		let store = Store::new(dir.path().join("history"), Some(&key));
+		store.save(&history).unwrap();
+
+		let data = fs::read(&store.path).unwrap();
+		assert!(!String::from_utf8_lossy(&data).contains("three"));
+		assert_eq!(history.entries, store.load().unwrap());
+
+		let other_key = ChaCha20Poly1305::generate_key(&mut OsRng);
+		assert!(Store::new(store.path.clone(), Some(&other_key)).load().is_err());
+	}
+
+	#[test]
+	fn should_load_missing() {
+		let dir = tempdir().unwrap();
+
+		assert_eq!(
+			Vec::<Entry>::new(),
+			Store::new(dir.path().join("history"), None).load().unwrap()
+		);
+	}
+}
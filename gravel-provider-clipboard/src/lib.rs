@@ -0,0 +1,180 @@
+//! Clipboard history provider.
+//! Records text copied while gravel runs and allows you to copy it again.
+//!
+//! The clipboard is checked for new text periodically. Contents that
+//! password managers mark as secret, using types such as
+//! `x-kde-passwordManagerHint`, are not recorded.
+//!
+//! The history is kept across restarts and can optionally be encrypted,
+//! using a key that is generated on first use.
+//!
+//! The query is matched against the full text of each entry. Without a
+//! query, the entries are listed newest first.
+
+use gravel_core::paths::{get_gravel_config_dir, get_gravel_data_dir};
+use gravel_core::scoring::{get_text_score, MAX_SCORE};
+use gravel_core::{config::PluginConfigAdapter, plugin::*, *};
+use history::{Entry, History, Store};
+use serde::Deserialize;
+use std::sync::{mpsc::Sender, Arc, Mutex};
+use std::time::Duration;
+
+mod history;
+mod recorder;
+
+#[cfg_attr(target_os = "linux", path = "linux.rs")]
+#[cfg_attr(windows, path = "windows.rs")]
+mod implementation;
+
+const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
+
+/// Characters of an entry shown in its hit's title.
+const TITLE_LENGTH: usize = 80;
+
+pub fn register_plugins(registry: &mut PluginRegistry) {
+	let definition = plugin("clipboard").with_provider(Box::new(get_provider));
+
+	registry.register(definition);
+}
+
+fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
+	let config = Arc::new(config_adapter.get::<Config>(DEFAULT_CONFIG));
+
+	let store = get_store(&config);
+	let entries = match store.as_ref().map(Store::load) {
+		Some(Ok(entries)) => entries,
+		Some(Err(err)) => {
+			log::error!("unable to load clipboard history: {err:#}");
+			vec![]
+		}
+		None => vec![],
+	};
+
+	let history = Arc::new(Mutex::new(History::new(entries, config.max_entries)));
+	recorder::start(config, history.clone(), store);
+
+	Box::new(ClipboardProvider { history })
+}
+
+/// Returns where to save the history, or nothing if it shouldn't be saved.
+fn get_store(config: &Config) -> Option<Store> {
+	if !config.persist {
+		return None;
+	}
+
+	let path = get_gravel_data_dir().join("clipboard-history");
+
+	if !config.encrypt {
+		return Some(Store::new(path, None));
+	}
+
+	match history::get_key(&get_gravel_config_dir().join("clipboard.key")) {
+		Ok(key) => Some(Store::new(path, Some(&key))),
+		Err(err) => {
+			// never fall back to saving the history unencrypted
+			log::error!("unable to get clipboard history key, history will not be saved: {err:#}");
+			None
+		}
+	}
+}
+
+struct ClipboardProvider {
+	history: Arc<Mutex<History>>,
+}
+
+impl Provider for ClipboardProvider {
+	fn query(&self, query: &str) -> ProviderResult {
+		let history = self.history.lock().expect("thread holding the mutex can't panic");
+		let text = query.trim();
+
+		let hits = history
+			.entries()
+			.enumerate()
+			.filter_map(|(i, entry)| {
+				let score = match text.is_empty() {
+					true => MAX_SCORE - i as u32,
+					false => get_text_score(&entry.text, text)?,
+				};
+
+				Some(get_hit(entry, score))
+			})
+			.collect();
+
+		ProviderResult::new(hits)
+	}
+}
+
+fn get_hit(entry: &Entry, score: u32) -> Arc<dyn Hit> {
+	let lines = entry.text.lines().filter(|l| !l.trim().is_empty()).count();
+	let subtitle = match lines {
+		0 | 1 => format!("Copied {} ago", format_duration(entry.age())),
+		lines => format!("Copied {} ago, {lines} lines", format_duration(entry.age())),
+	};
+
+	let text = entry.text.clone();
+	let hit = SimpleHit::new(get_title(&entry.text), subtitle, move |_, s| do_copy(&text, s));
+
+	Arc::new(hit.with_score(score))
+}
+
+/// Returns the first non-empty line, shortened to [`TITLE_LENGTH`].
+fn get_title(text: &str) -> String {
+	let line = text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+
+	match line.char_indices().nth(TITLE_LENGTH) {
+		Some((end, _)) => format!("{}…", &line[..end]),
+		None => line.to_owned(),
+	}
+}
+
+fn format_duration(duration: Duration) -> String {
+	let secs = duration.as_secs();
+
+	match (secs / 86400, secs / 3600, secs / 60) {
+		(0, 0, 0) => format!("{secs}s"),
+		(0, 0, m) => format!("{m}m"),
+		(0, h, _) => format!("{h}h"),
+		(d, _, _) => format!("{d}d"),
+	}
+}
+
+fn do_copy(text: &str, sender: &Sender<FrontendMessage>) {
+	log::debug!("copying history entry to clipboard");
+
+	clipboard::set_text(text);
+
+	sender.send(FrontendMessage::Hide).ok();
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+	pub poll_interval: f32,
+	pub max_entries: usize,
+	pub persist: bool,
+	pub encrypt: bool,
+	pub exclude_types: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("hello", "hello")]
+	#[case("\n  first line  \nsecond line", "first line")]
+	#[case(&"a".repeat(100), &format!("{}…", "a".repeat(80)))]
+	#[case(&"ä".repeat(81), &format!("{}…", "ä".repeat(80)))]
+	fn should_get_title(#[case] text: &str, #[case] expected: &str) {
+		assert_eq!(expected, get_title(text));
+	}
+
+	#[rstest]
+	#[case(45, "45s")]
+	#[case(12 * 60 + 5, "12m")]
+	#[case(3 * 3600 + 5 * 60, "3h")]
+	#[case(2 * 86400 + 4 * 3600, "2d")]
+	fn should_format_duration(#[case] secs: u64, #[case] expected: &str) {
+		assert_eq!(expected, format_duration(Duration::from_secs(secs)));
+	}
+}
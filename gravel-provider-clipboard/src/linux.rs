@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use std::thread;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, CreateWindowAux, WindowClass};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+x11rb::atom_manager! {
+	Atoms: AtomsCookie {
+		CLIPBOARD,
+		TARGETS,
+		GRAVEL_TARGETS,
+	}
+}
+
+/// How long to wait for the clipboard owner to answer.
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Asks the clipboard's owner which types it offers. Wayland compositors
+/// make the clipboard available to X11 clients as well, so this works there too.
+pub(crate) struct ClipboardTypes {
+	conn: RustConnection,
+	window: u32,
+	atoms: Atoms,
+}
+
+impl ClipboardTypes {
+	pub fn new() -> Result<Self> {
+		let (conn, screen) = x11rb::connect(None).context("unable to connect to the X server")?;
+		let root = conn.setup().roots[screen].root;
+		let atoms = Atoms::new(&conn)?.reply()?;
+
+		// the selection is converted into a property of this window
+		let window = conn.generate_id()?;
+		let aux = CreateWindowAux::new();
+		conn.create_window(0, window, root, 0, 0, 1, 1, 0, WindowClass::INPUT_ONLY, 0, &aux)?;
+		conn.flush()?;
+
+		Ok(Self { conn, window, atoms })
+	}
+
+	/// Returns the names of the given types that the clipboard contents are currently offered as.
+	pub fn filter(&self, names: &[String]) -> Result<Vec<String>> {
+		let available = self.get()?;
+
+		Ok(names.iter().filter(|n| available.contains(n)).cloned().collect())
+	}
+
+	fn get(&self) -> Result<Vec<String>> {
+		let (selection, target, property) = (self.atoms.CLIPBOARD, self.atoms.TARGETS, self.atoms.GRAVEL_TARGETS);
+
+		self.conn
+			.convert_selection(self.window, selection, target, property, x11rb::CURRENT_TIME)?;
+		self.conn.flush()?;
+
+		if !self.wait_for_selection()? {
+			return Ok(vec![]);
+		}
+
+		let reply = self
+			.conn
+			.get_property(true, self.window, property, AtomEnum::ATOM, 0, u32::MAX)?
+			.reply()?;
+
+		let Some(atoms) = reply.value32() else {
+			return Ok(vec![]);
+		};
+
+		let cookies = atoms
+			.map(|atom| self.conn.get_atom_name(atom))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let names = cookies
+			.into_iter()
+			.filter_map(|cookie| cookie.reply().ok())
+			.map(|reply| String::from_utf8_lossy(&reply.name).into_owned())
+			.collect();
+
+		Ok(names)
+	}
+
+	/// Waits until the owner answered, returns whether it converted the selection.
+	fn wait_for_selection(&self) -> Result<bool> {
+		let deadline = Instant::now() + TIMEOUT;
+
+		while Instant::now() < deadline {
+			match self.conn.poll_for_event()? {
+				Some(Event::SelectionNotify(event)) if event.requestor == self.window => {
+					return Ok(event.property != x11rb::NONE);
+				}
+				Some(_) => (),
+				None => thread::sleep(Duration::from_millis(5)),
+			}
+		}
+
+		log::debug!("clipboard owner didn't report its types in time");
+		Ok(false)
+	}
+}
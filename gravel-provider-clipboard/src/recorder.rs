@@ -0,0 +1,88 @@
+use crate::history::{History, Store};
+use crate::implementation::ClipboardTypes;
+use crate::Config;
+use arboard::Clipboard;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Checks the clipboard for new text on a new thread and adds it to the history.
+pub(crate) fn start(config: Arc<Config>, history: Arc<Mutex<History>>, store: Option<Store>) {
+	thread::spawn(move || {
+		let mut clipboard = match Clipboard::new() {
+			Ok(clipboard) => clipboard,
+			Err(err) => {
+				log::error!("unable to initialize clipboard, history will not be recorded: {err}");
+				return;
+			}
+		};
+
+		let types = ClipboardTypes::new()
+			.map_err(|err| log::warn!("unable to check clipboard types, secrets may be recorded: {err:#}"))
+			.ok();
+
+		record(&config, &mut clipboard, types.as_ref(), &history, store.as_ref());
+	});
+}
+
+fn record(
+	config: &Config,
+	clipboard: &mut Clipboard,
+	types: Option<&ClipboardTypes>,
+	history: &Mutex<History>,
+	store: Option<&Store>,
+) {
+	let interval = Duration::from_secs_f32(config.poll_interval.max(0.05));
+
+	// don't record the same text again after a restart
+	let mut last = history
+		.lock()
+		.expect("thread holding the mutex can't panic")
+		.latest()
+		.map(str::to_owned);
+
+	loop {
+		thread::sleep(interval);
+
+		// fails if the clipboard is empty or doesn't contain text
+		let Ok(text) = clipboard.get_text() else {
+			continue;
+		};
+
+		if last.as_ref() == Some(&text) {
+			continue;
+		}
+
+		last = Some(text.clone());
+
+		if text.trim().is_empty() || is_excluded(config, types) {
+			continue;
+		}
+
+		let mut history = history.lock().expect("thread holding the mutex can't panic");
+		history.push(text);
+		log::trace!("recorded clipboard contents");
+
+		if let Some(Err(err)) = store.map(|s| s.save(&history)) {
+			log::error!("unable to save clipboard history: {err:#}");
+		}
+	}
+}
+
+fn is_excluded(config: &Config, types: Option<&ClipboardTypes>) -> bool {
+	let Some(types) = types.filter(|_| !config.exclude_types.is_empty()) else {
+		return false;
+	};
+
+	match types.filter(&config.exclude_types) {
+		Ok(excluded) if excluded.is_empty() => false,
+		Ok(excluded) => {
+			log::debug!("not recording clipboard contents marked as {excluded:?}");
+			true
+		}
+		Err(err) => {
+			log::warn!("unable to check clipboard types: {err:#}");
+			false
+		}
+	}
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use std::ffi::OsStr;
+use std::iter::once;
+use std::os::windows::ffi::OsStrExt;
+use winapi::um::winuser::{IsClipboardFormatAvailable, RegisterClipboardFormatW};
+
+/// Checks the clipboard for registered formats, such as those password
+/// managers use to exclude their contents from clipboard history.
+pub(crate) struct ClipboardTypes;
+
+impl ClipboardTypes {
+	pub fn new() -> Result<Self> {
+		Ok(Self)
+	}
+
+	/// Returns the names of the given formats that the clipboard currently contains.
+	pub fn filter(&self, names: &[String]) -> Result<Vec<String>> {
+		let available = names
+			.iter()
+			.filter(|name| {
+				let wide = OsStr::new(name).encode_wide().chain(once(0)).collect::<Vec<_>>();
+				let format = unsafe { RegisterClipboardFormatW(wide.as_ptr()) };
+
+				format != 0 && unsafe { IsClipboardFormatAvailable(format) } != 0
+			})
+			.cloned()
+			.collect();
+
+		Ok(available)
+	}
+}
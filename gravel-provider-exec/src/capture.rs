@@ -0,0 +1,171 @@
+//! Output of commands run in capture mode.
+
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, ExitStatus};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often to check whether a command exited after closing its output.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The last captured command, shown as hits while the query matches it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captured {
+	pub command: String,
+	pub status: String,
+	pub lines: Vec<String>,
+}
+
+impl Captured {
+	pub fn running(command: &str) -> Self {
+		Self {
+			command: command.to_owned(),
+			status: "Running…".to_owned(),
+			lines: vec![],
+		}
+	}
+
+	pub fn failed(command: &str, err: &anyhow::Error) -> Self {
+		Self {
+			command: command.to_owned(),
+			status: format!("Failed to run: {err}"),
+			lines: vec![],
+		}
+	}
+
+	/// Collects the non-empty lines of stdout and stderr as they arrive.
+	/// Kills the child once it prints more than `max_lines` or runs
+	/// longer than `timeout`.
+	pub fn collect(command: &str, mut child: Child, max_lines: usize, timeout: Duration) -> Self {
+		let deadline = Instant::now() + timeout;
+		let receiver = read_lines(&mut child);
+		let mut lines = vec![];
+
+		let stopped = loop {
+			match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+				Ok(line) if line.trim().is_empty() => continue,
+				Ok(_) if lines.len() == max_lines => break Some(format!("Stopped after {max_lines} lines")),
+				Ok(line) => lines.push(line.trim_end().to_owned()),
+				Err(RecvTimeoutError::Timeout) => break Some(format!("Stopped after {timeout:?}")),
+				Err(RecvTimeoutError::Disconnected) => break None,
+			}
+		};
+
+		let status = stopped
+			.or_else(|| wait_until(&mut child, deadline).map(get_status))
+			.unwrap_or_else(|| format!("Stopped after {timeout:?}"));
+
+		// processes started by the shell may keep the pipes open, the readers
+		// stop once they try to send another line after this returns
+		child.kill().ok();
+		child.wait().ok();
+
+		Self {
+			command: command.to_owned(),
+			status,
+			lines,
+		}
+	}
+}
+
+/// Reads the child's stdout and stderr line by line on separate threads,
+/// so neither pipe fills up while the other is read.
+fn read_lines(child: &mut Child) -> Receiver<String> {
+	let (sender, receiver) = mpsc::channel();
+
+	if let Some(stdout) = child.stdout.take() {
+		spawn_reader(stdout, sender.clone());
+	}
+
+	if let Some(stderr) = child.stderr.take() {
+		spawn_reader(stderr, sender);
+	}
+
+	receiver
+}
+
+fn spawn_reader(output: impl Read + Send + 'static, sender: Sender<String>) {
+	thread::spawn(move || {
+		for line in BufReader::new(output).split(b'\n') {
+			let Ok(line) = line else {
+				return;
+			};
+
+			if sender.send(String::from_utf8_lossy(&line).into_owned()).is_err() {
+				return;
+			}
+		}
+	});
+}
+
+/// Waits for the child to exit, giving up at the deadline.
+fn wait_until(child: &mut Child, deadline: Instant) -> Option<ExitStatus> {
+	while Instant::now() < deadline {
+		match child.try_wait() {
+			Ok(Some(status)) => return Some(status),
+			Ok(None) => thread::sleep(POLL_INTERVAL),
+			Err(err) => {
+				log::error!("unable to wait for captured command: {err}");
+				return None;
+			}
+		}
+	}
+
+	None
+}
+
+fn get_status(status: ExitStatus) -> String {
+	match status.code() {
+		Some(code) => format!("Exited with code {code}"),
+		None => "Terminated by a signal".to_owned(),
+	}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+	use std::process::{Command, Stdio};
+
+	fn spawn(cmd: &str) -> Child {
+		Command::new("sh")
+			.arg("-c")
+			.arg(cmd)
+			.stdin(Stdio::null())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.unwrap()
+	}
+
+	#[rstest]
+	#[case("printf 'a\\n\\nb  \\n'", 10, "Exited with code 0", &["a", "b"])]
+	#[case("echo err >&2; exit 1", 10, "Exited with code 1", &["err"])]
+	#[case("seq 3", 3, "Exited with code 0", &["1", "2", "3"])]
+	#[case("seq 4", 3, "Stopped after 3 lines", &["1", "2", "3"])]
+	#[case("yes", 5, "Stopped after 5 lines", &["y"; 5])]
+	#[case("kill -9 $$", 10, "Terminated by a signal", &[])]
+	fn should_collect_output(
+		#[case] cmd: &str,
+		#[case] max_lines: usize,
+		#[case] status: &str,
+		#[case] lines: &[&str],
+	) {
+		let captured = Captured::collect(cmd, spawn(cmd), max_lines, Duration::from_secs(10));
+
+		assert_eq!(status, captured.status);
+		assert_eq!(lines, captured.lines);
+	}
+
+	#[rstest]
+	#[case("echo started; sleep 10")]
+	#[case("exec >&- 2>&-; sleep 10")]
+	fn should_stop_after_timeout(#[case] cmd: &str) {
+		let start = Instant::now();
+		let captured = Captured::collect(cmd, spawn(cmd), 10, Duration::from_millis(200));
+
+		assert_eq!("Stopped after 200ms", captured.status);
+		assert!(start.elapsed() < Duration::from_secs(5));
+	}
+}
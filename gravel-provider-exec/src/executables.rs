@@ -0,0 +1,174 @@
+//! Names of the programs in `$PATH`, for completion.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the directories are checked for changes while querying.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct Executables {
+	dirs: Vec<PathBuf>,
+	/// Modification times of the directories at the last scan.
+	modified: Vec<Option<SystemTime>>,
+	checked: Option<Instant>,
+	/// Sorted and without duplicates.
+	names: Vec<String>,
+}
+
+impl Executables {
+	pub fn new(dirs: Vec<PathBuf>) -> Self {
+		Self {
+			dirs,
+			modified: vec![],
+			checked: None,
+			names: vec![],
+		}
+	}
+
+	pub fn from_env() -> Self {
+		let dirs = env::var_os("PATH").map(|path| env::split_paths(&path).collect());
+
+		Self::new(dirs.unwrap_or_default())
+	}
+
+	/// Like [`Executables::refresh`], but checks the directories at most once
+	/// per interval, so they aren't read on every keystroke.
+	pub fn refresh_if_stale(&mut self) {
+		if self.checked.is_some_and(|c| c.elapsed() < CHECK_INTERVAL) {
+			return;
+		}
+
+		self.refresh();
+	}
+
+	/// Scans the directories again if any of them changed since the last scan.
+	pub fn refresh(&mut self) {
+		self.checked = Some(Instant::now());
+
+		let modified = self
+			.dirs
+			.iter()
+			.map(|d| fs::metadata(d).and_then(|m| m.modified()).ok())
+			.collect::<Vec<_>>();
+
+		if modified == self.modified {
+			return;
+		}
+
+		let mut names = self
+			.dirs
+			.iter()
+			.filter_map(|d| fs::read_dir(d).ok())
+			.flatten()
+			.filter_map(Result::ok)
+			.filter_map(|e| crate::implementation::get_executable_name(&e))
+			.collect::<Vec<_>>();
+
+		names.sort();
+		names.dedup();
+
+		log::debug!("found {} executables in $PATH", names.len());
+		self.names = names;
+		self.modified = modified;
+	}
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.names.binary_search_by(|n| n.as_str().cmp(name)).is_ok()
+	}
+
+	/// Returns the names starting with the prefix, shortest first.
+	pub fn complete(&self, prefix: &str) -> Vec<&str> {
+		let start = self.names.partition_point(|n| n.as_str() < prefix);
+
+		let mut names = self.names[start..]
+			.iter()
+			.take_while(|n| n.starts_with(prefix))
+			.map(String::as_str)
+			.collect::<Vec<_>>();
+
+		names.sort_by_key(|n| n.len());
+		names
+	}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+	use std::os::unix::fs::PermissionsExt;
+	use std::path::Path;
+	use tempfile::TempDir;
+
+	/// Creates the executables in a new temporary directory, which is returned alongside them.
+	fn get_executables() -> (Executables, TempDir) {
+		let dir = tempfile::tempdir().unwrap();
+		let root = dir.path();
+		fs::create_dir_all(root.join("bin")).unwrap();
+
+		for (file, mode) in [
+			("firefox", 0o755),
+			("firewall-cmd", 0o700),
+			("fish", 0o755),
+			("fi.txt", 0o644),
+		] {
+			let path = root.join("bin").join(file);
+			fs::write(&path, "").unwrap();
+			fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+		}
+
+		fs::create_dir_all(root.join("bin/find.d")).unwrap();
+
+		let mut executables = Executables::new(vec![root.join("bin"), root.join("missing")]);
+		executables.refresh();
+
+		(executables, dir)
+	}
+
+	#[rstest]
+	#[case("fi", &["fish", "firefox", "firewall-cmd"])]
+	#[case("fir", &["firefox", "firewall-cmd"])]
+	#[case("fish", &["fish"])]
+	#[case("git", &[])]
+	fn should_complete(#[case] prefix: &str, #[case] expected: &[&str]) {
+		let (executables, _dir) = get_executables();
+
+		assert_eq!(expected, executables.complete(prefix), "{prefix}");
+	}
+
+	fn add_executable(root: &Path, name: &str) {
+		let path = root.join("bin").join(name);
+		fs::write(&path, "").unwrap();
+		fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+		// some filesystems only store modification times in whole seconds
+		let modified = SystemTime::now() + Duration::from_secs(1);
+		fs::File::open(root.join("bin"))
+			.unwrap()
+			.set_modified(modified)
+			.unwrap();
+	}
+
+	#[test]
+	fn should_refresh() {
+		let (mut executables, dir) = get_executables();
+		assert!(!executables.contains("htop"));
+
+		add_executable(dir.path(), "htop");
+		executables.refresh();
+
+		assert!(executables.contains("htop"));
+		assert!(executables.contains("firefox"));
+	}
+
+	#[test]
+	fn should_not_refresh_before_interval() {
+		let (mut executables, dir) = get_executables();
+
+		add_executable(dir.path(), "htop");
+		executables.refresh_if_stale();
+
+		assert!(!executables.contains("htop"));
+	}
+}
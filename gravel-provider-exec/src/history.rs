@@ -0,0 +1,185 @@
+//! Previously run commands, ranked by frecency.
+
+use anyhow::Result;
+use gravel_core::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+	pub command: String,
+	pub count: u32,
+	/// Seconds since the unix epoch, or 0 if unknown.
+	pub last_used: u64,
+}
+
+impl Entry {
+	/// Combines how often and how recently the command was run, so that
+	/// commands used a lot long ago don't bury those used recently.
+	pub fn frecency(&self, now: u64) -> u32 {
+		let weight = match now.saturating_sub(self.last_used) / DAY {
+			0..=3 => 100,
+			4..=13 => 70,
+			14..=30 => 50,
+			31..=90 => 30,
+			_ => 10,
+		};
+
+		self.count.saturating_mul(weight)
+	}
+}
+
+pub struct History {
+	entries: Vec<Entry>,
+	/// Commands from shell history files, never saved.
+	imported: Vec<Entry>,
+	max_entries: usize,
+}
+
+impl History {
+	pub fn new(entries: Vec<Entry>, imported: Vec<Entry>, max_entries: usize) -> Self {
+		Self {
+			entries,
+			imported,
+			max_entries,
+		}
+	}
+
+	/// Counts a run of the command, discarding the least frecent
+	/// entries if there are too many.
+	pub fn push(&mut self, command: &str) {
+		let now = get_now();
+
+		match self.entries.iter_mut().find(|e| e.command == command) {
+			Some(entry) => {
+				entry.count = entry.count.saturating_add(1);
+				entry.last_used = now;
+			}
+			None => self.entries.push(Entry {
+				command: command.to_owned(),
+				count: 1,
+				last_used: now,
+			}),
+		}
+
+		if self.entries.len() > self.max_entries {
+			self.entries.sort_by_key(|e| std::cmp::Reverse(e.frecency(now)));
+			self.entries.truncate(self.max_entries);
+		}
+	}
+
+	pub fn contains(&self, command: &str) -> bool {
+		self.entries.iter().chain(&self.imported).any(|e| e.command == command)
+	}
+
+	/// Returns commands containing the query, ignoring case, most frecent first.
+	pub fn search(&self, query: &str) -> Vec<&str> {
+		let now = get_now();
+		let query = query.to_lowercase();
+
+		let mut matches = self
+			.entries
+			.iter()
+			.chain(&self.imported)
+			.filter(|e| e.command.to_lowercase().contains(&query))
+			.collect::<Vec<_>>();
+
+		matches.sort_by_key(|e| std::cmp::Reverse(e.frecency(now)));
+
+		let mut seen = HashSet::new();
+		matches
+			.into_iter()
+			.map(|e| e.command.as_str())
+			.filter(|command| seen.insert(*command))
+			.collect()
+	}
+}
+
+pub fn load(path: &Path) -> Result<Vec<Entry>> {
+	let data = match fs::read(path) {
+		Ok(data) => data,
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+		Err(err) => return Err(err.into()),
+	};
+
+	Ok(serde_json::from_slice(&data)?)
+}
+
+pub fn save(path: &Path, history: &History) -> Result<()> {
+	storage::write_atomic(path, &serde_json::to_vec(&history.entries)?)?;
+
+	Ok(())
+}
+
+fn get_now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	fn entry(command: &str, count: u32, days_ago: u64) -> Entry {
+		Entry {
+			command: command.to_owned(),
+			count,
+			last_used: get_now() - days_ago * DAY,
+		}
+	}
+
+	fn get_history() -> History {
+		let entries = vec![
+			entry("firefox", 3, 0),
+			entry("git status", 20, 100),
+			entry("git stash", 3, 1),
+		];
+		let imported = ["git status", "gitk"]
+			.map(|command| Entry {
+				command: command.to_owned(),
+				count: 1,
+				last_used: 0,
+			})
+			.to_vec();
+
+		History::new(entries, imported, 3)
+	}
+
+	#[rstest]
+	#[case("git", &["git stash", "git status", "gitk"])]
+	#[case("FIR", &["firefox"])]
+	#[case("sta", &["git stash", "git status"])]
+	#[case("chromium", &[])]
+	fn should_search(#[case] query: &str, #[case] expected: &[&str]) {
+		assert_eq!(expected, get_history().search(query), "{query}");
+	}
+
+	#[test]
+	fn should_push() {
+		let mut history = get_history();
+		history.push("git status");
+		history.push("htop");
+
+		let commands = history.entries.iter().map(|e| e.command.as_str()).collect::<Vec<_>>();
+		assert_eq!(vec!["git status", "firefox", "git stash"], commands);
+		assert_eq!(21, history.entries[0].count);
+	}
+
+	#[test]
+	fn should_round_trip() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("exec-history");
+		let history = get_history();
+		save(&path, &history).unwrap();
+
+		assert_eq!(history.entries, load(&path).unwrap());
+	}
+}
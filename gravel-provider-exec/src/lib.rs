@@ -1,11 +1,39 @@
 //! gravel's command runner
 //!
-//! Always returns a hit with the minimum score that, when selected,
-//! runs the command with the system shell.
+//! Always returns hits with the minimum score that, when selected,
+//! run the command with the system shell, one for each configured mode:
+//! - `detached`: in the background, discarding any output
+//! - `terminal`: in a terminal emulator
+//! - `hold`: in a terminal emulator that stays open after the command exits
+//! - `capture`: in the background, showing the output and exit code as hits
+//!   once it's done. Selecting an output line copies it. The command is
+//!   stopped once it prints too many lines or runs for too long.
+//!
+//! Programs in `$PATH` starting with the query and previously run commands
+//! containing it are offered as completions, so `> fir` suggests `firefox`.
+//! They rank above the query itself, unless it starts with a known program.
+//! Run commands are remembered and ranked by frecency. Commands from bash,
+//! zsh and fish history files can optionally be imported as well.
+//!
+//! On Linux, the shell and terminal emulator are configurable, and
+//! commands can optionally be started in their own systemd user scope.
 
-use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::MIN_SCORE, *};
+use capture::Captured;
+use executables::Executables;
+use gravel_core::paths::get_gravel_data_dir;
+use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::*, *};
+use history::History;
 use serde::Deserialize;
-use std::sync::{mpsc::Sender, Arc};
+use shell_history::Shell;
+use std::path::PathBuf;
+use std::sync::{mpsc::Sender, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+mod capture;
+mod executables;
+mod history;
+mod shell_history;
 
 #[cfg_attr(target_os = "linux", path = "linux.rs")]
 #[cfg_attr(windows, path = "windows.rs")]
@@ -13,6 +41,10 @@ mod implementation;
 
 const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
 
+/// Score of the hits running the query when it starts with a known program.
+/// Completions rank just below them, output of captured commands above.
+const COMMAND_SCORE: u32 = MAX_SCORE / 2;
+
 pub fn register_plugins(registry: &mut PluginRegistry) {
 	let definition = plugin("exec").with_provider(Box::new(get_provider));
 
@@ -22,32 +54,254 @@ pub fn register_plugins(registry: &mut PluginRegistry) {
 fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
 	let config = config_adapter.get::<Config>(DEFAULT_CONFIG);
 
-	let provider = ExecProvider { config };
+	let entries = match config.remember.then(|| history::load(&get_history_path())) {
+		Some(Ok(entries)) => entries,
+		Some(Err(err)) => {
+			log::error!("unable to load exec history: {err:#}");
+			vec![]
+		}
+		None => vec![],
+	};
+
+	let imported = shell_history::import(&config.import_history);
+	let history = History::new(entries, imported, config.max_history);
+
+	let provider = ExecProvider {
+		config: Arc::new(config),
+		captured: Default::default(),
+		history: Arc::new(Mutex::new(history)),
+		executables: Mutex::new(Executables::from_env()),
+	};
 
 	Box::new(provider)
 }
 
 pub struct ExecProvider {
-	config: Config,
+	config: Arc<Config>,
+	captured: Arc<Mutex<Option<Captured>>>,
+	history: Arc<Mutex<History>>,
+	executables: Mutex<Executables>,
 }
 
 impl Provider for ExecProvider {
 	fn query(&self, query: &str) -> ProviderResult {
-		let hit = SimpleHit::new(query, &*self.config.subtitle, run_command).with_score(MIN_SCORE);
+		let output_hits = self.get_output_hits(query);
+		let (completions, known) = self.get_completions(query.trim());
+
+		let score = match known {
+			true => COMMAND_SCORE,
+			false => MIN_SCORE,
+		};
+
+		let mode_hits = self
+			.config
+			.modes
+			.iter()
+			.map(|mode_config| self.get_command_hit(query, mode_config, score));
+
+		// completions are run with the first mode
+		let completion_hits = completions.iter().enumerate().filter_map(|(i, command)| {
+			let mode_config = self.config.modes.first()?;
+			Some(self.get_command_hit(command, mode_config, COMMAND_SCORE - 1 - i as u32))
+		});
+
+		let hits = output_hits.into_iter().chain(mode_hits).chain(completion_hits);
+		ProviderResult::new(hits.collect())
+	}
+}
+
+impl ExecProvider {
+	fn get_command_hit(&self, command: &str, mode_config: &ModeConfig, score: u32) -> Arc<dyn Hit> {
+		let config = self.config.clone();
+		let captured = self.captured.clone();
+		let history = self.history.clone();
+		let mode = mode_config.mode;
+		let hit = SimpleHit::new(command, &*mode_config.subtitle, move |h, s| {
+			run_command(&config, &captured, &history, mode, h.get_title(), s)
+		});
+
+		Arc::new(hit.with_score(score))
+	}
 
-		ProviderResult::single(Arc::new(hit))
+	/// Returns previously run commands and programs completing the query,
+	/// and whether the query already starts with a known program.
+	fn get_completions(&self, query: &str) -> (Vec<String>, bool) {
+		let mut executables = self.executables.lock().expect("thread holding the mutex can't panic");
+		let history = self.history.lock().expect("thread holding the mutex can't panic");
+		executables.refresh_if_stale();
+
+		let program = query.split_whitespace().next().unwrap_or_default();
+		let known = executables.contains(program) || history.contains(query);
+
+		if query.is_empty() {
+			return (vec![], known);
+		}
+
+		// programs can only complete the first word
+		let programs = match query.contains(char::is_whitespace) {
+			true => vec![],
+			false => executables.complete(query),
+		};
+
+		let mut completions = history.search(query);
+
+		for program in programs {
+			if !completions.contains(&program) {
+				completions.push(program);
+			}
+		}
+
+		let completions = completions
+			.into_iter()
+			.filter(|command| *command != query)
+			.take(self.config.max_completions)
+			.map(str::to_owned)
+			.collect();
+
+		(completions, known)
+	}
+
+	/// Returns the output of the last captured command, if it matches the query.
+	fn get_output_hits(&self, query: &str) -> Vec<Arc<dyn Hit>> {
+		let captured = self.captured.lock().expect("thread holding the mutex can't panic");
+
+		let Some(captured) = captured.as_ref().filter(|c| c.command == query) else {
+			return vec![];
+		};
+
+		if captured.lines.is_empty() {
+			let hit = SimpleHit::new(&*captured.status, query, |_, _| ());
+			return vec![Arc::new(hit.with_score(MAX_SCORE)) as Arc<dyn Hit>];
+		}
+
+		captured
+			.lines
+			.iter()
+			.enumerate()
+			.map(|(i, line)| {
+				let text = line.clone();
+				let hit = SimpleHit::new(&**line, &*captured.status, move |_, s| do_copy(&text, s));
+
+				Arc::new(hit.with_score(MAX_SCORE - i as u32)) as Arc<dyn Hit>
+			})
+			.collect()
 	}
 }
 
-fn run_command(hit: &SimpleHit, sender: &Sender<FrontendMessage>) {
-	if let Err(err) = implementation::run_command(hit.get_title()) {
+fn run_command(
+	config: &Arc<Config>,
+	captured: &Arc<Mutex<Option<Captured>>>,
+	history: &Mutex<History>,
+	mode: Mode,
+	cmd: &str,
+	sender: &Sender<FrontendMessage>,
+) {
+	record(config, history, cmd.trim());
+
+	let result = match mode {
+		Mode::Detached => implementation::run_command(cmd, config),
+		Mode::Terminal => implementation::run_in_terminal(cmd, false, config),
+		Mode::Hold => implementation::run_in_terminal(cmd, true, config),
+		Mode::Capture => return capture_command(config.clone(), captured.clone(), cmd, sender.clone()),
+	};
+
+	if let Err(err) = result {
 		log::error!("{err}");
 	}
 
 	sender.send(FrontendMessage::Hide).ok();
 }
 
+/// Runs the command on a background thread, refreshing the hits when it's done.
+fn capture_command(
+	config: Arc<Config>,
+	captured: Arc<Mutex<Option<Captured>>>,
+	cmd: &str,
+	sender: Sender<FrontendMessage>,
+) {
+	set_captured(&captured, Captured::running(cmd));
+	sender.send(FrontendMessage::Refresh).ok();
+
+	let cmd = cmd.to_owned();
+	let timeout = Duration::from_secs(config.capture_timeout);
+
+	thread::spawn(move || {
+		let result = match implementation::capture_command(&cmd, &config) {
+			Ok(child) => Captured::collect(&cmd, child, config.max_output_lines, timeout),
+			Err(err) => {
+				log::error!("{err}");
+				Captured::failed(&cmd, &err)
+			}
+		};
+
+		log::debug!("command '{cmd}' finished: {}", result.status);
+		set_captured(&captured, result);
+		sender.send(FrontendMessage::Refresh).ok();
+	});
+}
+
+/// Counts a run of the command and saves the history.
+fn record(config: &Config, history: &Mutex<History>, cmd: &str) {
+	if !config.remember || cmd.is_empty() {
+		return;
+	}
+
+	let mut history = history.lock().expect("thread holding the mutex can't panic");
+	history.push(cmd);
+
+	if let Err(err) = history::save(&get_history_path(), &history) {
+		log::error!("unable to save exec history: {err:#}");
+	}
+}
+
+fn get_history_path() -> PathBuf {
+	get_gravel_data_dir().join("exec-history")
+}
+
+fn set_captured(captured: &Mutex<Option<Captured>>, value: Captured) {
+	*captured.lock().expect("thread holding the mutex can't panic") = Some(value);
+}
+
+fn do_copy(text: &str, sender: &Sender<FrontendMessage>) {
+	log::debug!("copying command output to clipboard");
+
+	clipboard::set_text(text);
+
+	sender.send(FrontendMessage::Hide).ok();
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Mode {
+	Detached,
+	Terminal,
+	Hold,
+	Capture,
+}
+
 #[derive(Deserialize, Debug)]
-struct Config {
+struct ModeConfig {
+	pub mode: Mode,
 	pub subtitle: String,
 }
+
+#[derive(Deserialize, Debug)]
+struct Config {
+	pub modes: Vec<ModeConfig>,
+	pub max_output_lines: usize,
+	pub capture_timeout: u64,
+	pub max_completions: usize,
+	pub remember: bool,
+	pub max_history: usize,
+	pub import_history: Vec<Shell>,
+	#[cfg(target_os = "linux")]
+	pub linux: LinuxConfig,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Deserialize, Debug)]
+struct LinuxConfig {
+	pub shell: String,
+	pub terminal: Vec<String>,
+	pub systemd_scope: bool,
+}
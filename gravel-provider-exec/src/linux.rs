@@ -1,20 +1,80 @@
-use anyhow::{anyhow, Result};
-use std::process::{Command, Stdio};
+use crate::Config;
+use anyhow::{anyhow, Context, Result};
+use gravel_core::process;
+use std::fs::{self, DirEntry};
+use std::os::unix::fs::PermissionsExt;
+use std::process::{Child, Command, Stdio};
 
-const SHELL: &str = "sh";
+/// Runs the command in the configured shell, then waits for enter, so the
+/// terminal stays open. The shell and command are passed as `$0` and `$1`.
+const HOLD_SCRIPT: &str = r#""$0" -c "$1"; printf '\n[exited with code %s, press enter to close]' "$?"; read -r _"#;
 
-/// Passes the given string to a new bash process.
-pub fn run_command(cmd: &str) -> Result<()> {
-	log::debug!("running command in {SHELL} '{cmd}'");
+/// Passes the given string to a new shell process, detached from gravel.
+pub fn run_command(cmd: &str, config: &Config) -> Result<()> {
+	let shell = &config.linux.shell;
+	log::debug!("running command in {shell} '{cmd}'");
 
-	Command::new(SHELL)
+	launch(Command::new(shell).arg("-c").arg(cmd), cmd, config)
+}
+
+/// Runs the command in the configured terminal emulator. With `hold`, the
+/// terminal stays open after the command exits.
+pub fn run_in_terminal(cmd: &str, hold: bool, config: &Config) -> Result<()> {
+	let shell = &config.linux.shell;
+	let (terminal, terminal_args) = config
+		.linux
+		.terminal
+		.split_first()
+		.context("no terminal emulator configured")?;
+
+	log::debug!("running command in {terminal} with {shell} '{cmd}'");
+
+	let mut command = Command::new(terminal);
+	command.args(terminal_args);
+
+	match hold {
+		true => command.args(["sh", "-c", HOLD_SCRIPT, shell, cmd]),
+		false => command.args([shell, "-c", cmd]),
+	};
+
+	launch(&mut command, cmd, config)
+}
+
+/// Runs the command in the configured shell, with its output piped to gravel.
+pub fn capture_command(cmd: &str, config: &Config) -> Result<Child> {
+	let shell = &config.linux.shell;
+	log::debug!("capturing output of command in {shell} '{cmd}'");
+
+	Command::new(shell)
 		.arg("-c")
 		.arg(cmd)
-		// explicitly prevent stream inheritance
 		.stdin(Stdio::null())
-		.stdout(Stdio::null())
-		.stderr(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
 		.spawn()
-		.map(drop)
-		.map_err(|e| anyhow!("error invoking {SHELL}: {e}"))
+		.map_err(|e| anyhow!("error invoking {shell}: {e}"))
+}
+
+fn launch(command: &mut Command, cmd: &str, config: &Config) -> Result<()> {
+	let program = command.get_program().to_string_lossy().into_owned();
+	let app_id = get_app_id(cmd, &config.linux.shell);
+
+	process::launch(command, app_id, config.linux.systemd_scope).map_err(|e| anyhow!("error invoking {program}: {e}"))
+}
+
+/// Name of the invoked program, used to name the systemd scope.
+fn get_app_id<'a>(cmd: &'a str, shell: &'a str) -> &'a str {
+	cmd.split_whitespace()
+		.next()
+		.and_then(|program| program.rsplit('/').next())
+		.filter(|name| !name.is_empty())
+		.unwrap_or(shell)
+}
+
+/// Returns the file name if the entry is an executable file, following symlinks.
+pub fn get_executable_name(entry: &DirEntry) -> Option<String> {
+	let metadata = fs::metadata(entry.path()).ok()?;
+	let executable = metadata.is_file() && metadata.permissions().mode() & 0o111 != 0;
+
+	executable.then(|| entry.file_name().into_string().ok()).flatten()
 }
@@ -0,0 +1,147 @@
+//! Import of commands from the history files of common shells.
+
+use crate::history::Entry;
+use gravel_core::paths::{get_home, get_xdg_data_home};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Shell {
+	Bash,
+	Zsh,
+	Fish,
+}
+
+impl Shell {
+	fn get_path(self) -> PathBuf {
+		match self {
+			Shell::Bash => get_home().join(".bash_history"),
+			Shell::Zsh => get_home().join(".zsh_history"),
+			Shell::Fish => get_xdg_data_home().join("fish/fish_history"),
+		}
+	}
+
+	fn parse(self, text: &str) -> Vec<String> {
+		match self {
+			Shell::Bash => parse_bash(text),
+			Shell::Zsh => parse_zsh(text),
+			Shell::Fish => parse_fish(text),
+		}
+	}
+}
+
+/// Reads the commands from each shell's history, counting how often each
+/// was run. Multi-line commands are skipped.
+pub fn import(shells: &[Shell]) -> Vec<Entry> {
+	let mut counts = BTreeMap::<String, u32>::new();
+
+	for shell in shells {
+		let path = shell.get_path();
+
+		let text = match fs::read(&path) {
+			Ok(data) => String::from_utf8_lossy(&data).into_owned(),
+			Err(err) => {
+				log::warn!("unable to read shell history {path:?}: {err}");
+				continue;
+			}
+		};
+
+		for command in shell.parse(&text) {
+			*counts.entry(command).or_default() += 1;
+		}
+	}
+
+	log::debug!("imported {} commands from shell history", counts.len());
+
+	counts
+		.into_iter()
+		.map(|(command, count)| Entry {
+			command,
+			count,
+			last_used: 0,
+		})
+		.collect()
+}
+
+/// Lines, skipping the `#1700000000` timestamps of `HISTTIMEFORMAT`.
+fn parse_bash(text: &str) -> Vec<String> {
+	text.lines()
+		.filter(|l| !is_timestamp(l))
+		.filter_map(get_command)
+		.collect()
+}
+
+/// Lines, with the `: 1700000000:0;` prefix of `EXTENDED_HISTORY` removed.
+/// Lines ending in a backslash are continued on the next one.
+fn parse_zsh(text: &str) -> Vec<String> {
+	let mut commands = vec![];
+	let mut multiline = false;
+
+	for line in text.lines() {
+		let continued = multiline;
+		multiline = line.ends_with('\\');
+
+		if continued || multiline {
+			continue;
+		}
+
+		let line = match line.strip_prefix(": ") {
+			Some(extended) => extended.split_once(';').map_or(line, |(_, command)| command),
+			None => line,
+		};
+
+		commands.extend(get_command(line));
+	}
+
+	commands
+}
+
+/// Values of the `- cmd: ` lines, which escape newlines and backslashes.
+fn parse_fish(text: &str) -> Vec<String> {
+	text.lines()
+		.filter_map(|l| l.strip_prefix("- cmd: "))
+		.filter(|command| !command.replace("\\\\", "").contains("\\n"))
+		.map(|command| command.replace("\\\\", "\\"))
+		.filter_map(|command| get_command(&command))
+		.collect()
+}
+
+fn is_timestamp(line: &str) -> bool {
+	line.strip_prefix('#')
+		.is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn get_command(line: &str) -> Option<String> {
+	let command = line.trim();
+	(!command.is_empty()).then(|| command.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_parse_bash() {
+		let text = "ls -la\n#1700000000\ngit status\n\n# a comment\n";
+
+		assert_eq!(vec!["ls -la", "git status", "# a comment"], parse_bash(text));
+	}
+
+	#[test]
+	fn should_parse_zsh() {
+		let text =
+			": 1700000000:0;ls -la\nfirefox\n: 1700000001:3;for f in *; do\\\necho $f\\\ndone\n: 1700000002:0;htop\n";
+
+		assert_eq!(vec!["ls -la", "firefox", "htop"], parse_zsh(text));
+	}
+
+	#[test]
+	fn should_parse_fish() {
+		let text = "- cmd: ls -la\n  when: 1700000000\n- cmd: echo a\\\\nb\n- cmd: echo a\\nb\n  when: 1700000001\n";
+
+		assert_eq!(vec!["ls -la", "echo a\\nb"], parse_fish(text));
+	}
+}
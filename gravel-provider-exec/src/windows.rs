@@ -1,16 +1,69 @@
+use crate::Config;
 use anyhow::{anyhow, Result};
+use std::env;
 use std::ffi::CString;
+use std::fs::DirEntry;
+use std::os::windows::process::CommandExt;
+use std::process::{Child, Command, Stdio};
 use winapi::shared::ntdef::{LPCSTR, NULL};
 use winapi::shared::windef::HWND;
 use winapi::um::shellapi::ShellExecuteA;
+use winapi::um::winbase::CREATE_NEW_CONSOLE;
 
 /// Passes the given string to ShellExecute.
-pub fn run_command(cmd: &str) -> Result<()> {
+pub fn run_command(cmd: &str, _config: &Config) -> Result<()> {
 	log::debug!("running command with shell_execute '{cmd}'");
 
 	shell_execute(cmd).map_err(|_| anyhow!(""))
 }
 
+/// Runs the command with cmd in a new console window. With `hold`, the
+/// window stays open after the command exits.
+pub fn run_in_terminal(cmd: &str, hold: bool, _config: &Config) -> Result<()> {
+	log::debug!("running command in a new console '{cmd}'");
+
+	let flag = if hold { "/K" } else { "/C" };
+
+	Command::new("cmd")
+		.arg(flag)
+		.raw_arg(cmd)
+		.creation_flags(CREATE_NEW_CONSOLE)
+		.spawn()
+		.map(|_| ())
+		.map_err(|e| anyhow!("error invoking cmd: {e}"))
+}
+
+/// Runs the command with cmd, with its output piped to gravel.
+pub fn capture_command(cmd: &str, _config: &Config) -> Result<Child> {
+	log::debug!("capturing output of command '{cmd}'");
+
+	Command::new("cmd")
+		.arg("/C")
+		.raw_arg(cmd)
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.map_err(|e| anyhow!("error invoking cmd: {e}"))
+}
+
+/// Returns the file name without its extension, if the extension is one
+/// of the executable ones listed in `%PATHEXT%`.
+pub fn get_executable_name(entry: &DirEntry) -> Option<String> {
+	let path = entry.path();
+	let extension = path.extension()?.to_str()?;
+	let pathext = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_owned());
+
+	let executable = pathext
+		.split(';')
+		.any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(extension));
+
+	match executable {
+		true => path.file_stem()?.to_str().map(str::to_owned),
+		false => None,
+	}
+}
+
 fn shell_execute(cmd: &str) -> Result<()> {
 	let cmd = CString::new(cmd).unwrap();
 
@@ -0,0 +1,348 @@
+use crate::Config;
+use gravel_core::paths::get_home;
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{Walk, WalkBuilder};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+pub(crate) type SharedIndex = Arc<RwLock<FileIndex>>;
+
+/// In-memory index of all files and directories below the configured roots.
+///
+/// Paths are kept sorted, so everything below a directory can be
+/// updated at once without rescanning the other roots.
+pub(crate) struct FileIndex {
+	walker: Walker,
+	entries: BTreeMap<PathBuf, FileEntry>,
+}
+
+/// Reads files below the configured roots. Kept apart from the index's
+/// entries, so it can walk directories without holding the index's lock.
+#[derive(Clone)]
+pub(crate) struct Walker {
+	config: Arc<Config>,
+	roots: Vec<Root>,
+}
+
+#[derive(Clone)]
+struct Root {
+	path: PathBuf,
+	/// The configured ignore globs, relative to this root.
+	overrides: Override,
+}
+
+pub(crate) struct FileEntry {
+	pub name: String,
+	/// The full path, with the home directory shortened to `~`.
+	pub display: String,
+	pub is_dir: bool,
+}
+
+impl FileIndex {
+	/// Creates a new, empty index. See [`populate`].
+	pub fn new(config: Arc<Config>) -> Self {
+		Self {
+			walker: Walker::new(config),
+			entries: BTreeMap::new(),
+		}
+	}
+
+	pub fn shared(self) -> SharedIndex {
+		Arc::new(RwLock::new(self))
+	}
+
+	pub fn walker(&self) -> Walker {
+		self.walker.clone()
+	}
+
+	pub fn roots(&self) -> impl Iterator<Item = &Path> {
+		self.walker.roots.iter().map(|r| r.path.as_path())
+	}
+
+	pub fn entries(&self) -> impl Iterator<Item = (&PathBuf, &FileEntry)> {
+		self.entries.iter()
+	}
+
+	/// Returns the indexed directories at or below the path.
+	pub fn directories(&self, path: &Path) -> Vec<PathBuf> {
+		self.below(path)
+			.filter(|(_, e)| e.is_dir)
+			.map(|(p, _)| p.clone())
+			.collect()
+	}
+
+	/// Discards the index and rescans all roots.
+	pub fn refresh(&mut self) {
+		self.entries = self.walker.walk_all().collect();
+
+		log::debug!("indexed {} files", self.entries.len());
+	}
+
+	/// Replaces the path and everything below it with entries returned by [`Walker::walk`].
+	pub fn replace(&mut self, path: &Path, entries: Vec<(PathBuf, FileEntry)>) {
+		self.remove(path);
+		self.entries.extend(entries);
+	}
+
+	/// Removes the path and everything below it.
+	pub fn remove(&mut self, path: &Path) {
+		let removed = self.below(path).map(|(p, _)| p.clone()).collect::<Vec<_>>();
+
+		for path in removed {
+			self.entries.remove(&path);
+		}
+	}
+
+	/// Paths sort right before their descendants, so they form a single range.
+	fn below<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = (&'a PathBuf, &'a FileEntry)> {
+		self.entries
+			.range::<Path, _>((Bound::Included(path), Bound::Unbounded))
+			.take_while(move |(p, _)| p.starts_with(path))
+	}
+}
+
+impl Walker {
+	fn new(config: Arc<Config>) -> Self {
+		let roots = config
+			.roots
+			.iter()
+			.filter_map(|r| expand_root(r))
+			.map(|path| Root {
+				overrides: get_overrides(&config, &path),
+				path,
+			})
+			.collect();
+
+		Self { config, roots }
+	}
+
+	fn walk_all(&self) -> impl Iterator<Item = (PathBuf, FileEntry)> + '_ {
+		self.roots
+			.iter()
+			.flat_map(|root| get_entries(get_walk(&self.config, root, &root.path, self.config.max_depth)))
+	}
+
+	/// Reads the path and everything below it, for example because it was
+	/// just created. Ignore files in and above the path are respected.
+	pub fn walk(&self, path: &Path) -> Vec<(PathBuf, FileEntry)> {
+		let Some(root) = self.roots.iter().find(|r| path.starts_with(&r.path)) else {
+			return vec![];
+		};
+
+		let depth = path.strip_prefix(&root.path).map_or(0, |p| p.components().count());
+
+		let walk = match (depth, path.parent()) {
+			(0, _) | (_, None) => get_walk(&self.config, root, path, self.config.max_depth),
+			(depth, _) if depth > self.config.max_depth => return vec![],
+			(depth, Some(parent)) => {
+				// walk from the parent, so the path itself is checked against ignore files and globs
+				let mut builder = get_builder(&self.config, root, parent, self.config.max_depth - depth + 1);
+				let path = path.to_owned();
+				builder.filter_entry(move |e| e.depth() == 0 || e.path().starts_with(&path));
+				builder.build()
+			}
+		};
+
+		let entries = get_entries(walk).collect();
+		log::trace!("rescanned {path:?}");
+
+		entries
+	}
+}
+
+/// Scans all roots and replaces the index's entries with the result. The lock
+/// is only held to swap them, so queries can be answered in the meantime.
+pub(crate) fn populate(index: &SharedIndex) {
+	let mut populated = {
+		let index = index.read().expect("thread holding the lock can't panic");
+		FileIndex::new(index.walker.config.clone())
+	};
+
+	populated.refresh();
+	*index.write().expect("thread holding the lock can't panic") = populated;
+}
+
+fn get_entries(walk: Walk) -> impl Iterator<Item = (PathBuf, FileEntry)> {
+	let home = get_home();
+
+	walk.filter_map(move |result| {
+		let entry = match result {
+			Ok(entry) if entry.depth() > 0 => entry,
+			Ok(_) => return None,
+			Err(err) => {
+				log::debug!("skipping file while indexing: {err}");
+				return None;
+			}
+		};
+
+		let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+		let path = entry.into_path();
+		let file_entry = FileEntry {
+			name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+			display: get_display(&path, &home),
+			is_dir,
+		};
+
+		Some((path, file_entry))
+	})
+}
+
+fn get_walk(config: &Config, root: &Root, start: &Path, max_depth: usize) -> Walk {
+	get_builder(config, root, start, max_depth).build()
+}
+
+fn get_builder(config: &Config, root: &Root, start: &Path, max_depth: usize) -> WalkBuilder {
+	let mut builder = WalkBuilder::new(start);
+
+	builder
+		.max_depth(Some(max_depth))
+		.hidden(!config.hidden)
+		.ignore(config.respect_gitignore)
+		.git_ignore(config.respect_gitignore)
+		.git_global(config.respect_gitignore)
+		.git_exclude(config.respect_gitignore)
+		.require_git(false)
+		.overrides(root.overrides.clone());
+
+	builder
+}
+
+/// Turns the ignore globs into overrides, which ignore matching paths when negated.
+fn get_overrides(config: &Config, root: &Path) -> Override {
+	let mut builder = OverrideBuilder::new(root);
+
+	for glob in &config.ignore {
+		if let Err(err) = builder.add(&format!("!{glob}")) {
+			log::error!("invalid ignore glob '{glob}': {err}");
+		}
+	}
+
+	builder.build().unwrap_or_else(|err| {
+		log::error!("unable to use ignore globs: {err}");
+		Override::empty()
+	})
+}
+
+fn expand_root(root: &str) -> Option<PathBuf> {
+	shellexpand::full(root)
+		.map_err(|err| log::error!("unable to expand root '{root}': {err}"))
+		.ok()
+		.map(|r| PathBuf::from(&*r))
+}
+
+fn get_display(path: &Path, home: &Path) -> String {
+	match path.strip_prefix(home) {
+		Ok(relative) if relative.as_os_str().is_empty() => String::from("~"),
+		Ok(relative) => Path::new("~").join(relative).to_string_lossy().into_owned(),
+		Err(_) => path.to_string_lossy().into_owned(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ActionsConfig;
+	use rstest::rstest;
+	use std::fs;
+	use tempfile::TempDir;
+
+	fn get_config(root: &Path) -> Arc<Config> {
+		Arc::new(Config {
+			roots: vec![root.to_string_lossy().into_owned()],
+			max_depth: 3,
+			ignore: vec![String::from("node_modules")],
+			respect_gitignore: true,
+			hidden: false,
+			max_hits: 20,
+			actions: ActionsConfig {
+				open_folder: String::new(),
+				copy_path: String::new(),
+			},
+		})
+	}
+
+	/// Creates the files, including their parent directories, in a new temporary directory.
+	fn create_tree(files: &[&str]) -> TempDir {
+		let dir = tempfile::tempdir().unwrap();
+
+		for file in files {
+			let path = dir.path().join(file);
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, "").unwrap();
+		}
+
+		dir
+	}
+
+	fn get_paths(index: &FileIndex, root: &Path) -> Vec<String> {
+		index
+			.entries()
+			.filter(|(_, e)| !e.is_dir)
+			.map(|(p, _)| p.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+			.collect()
+	}
+
+	#[test]
+	fn should_skip_ignored() {
+		let files = [
+			"notes.txt",
+			".hidden",
+			".gitignore",
+			"build/out.o",
+			"project/src/main.rs",
+			"project/node_modules/dep/index.js",
+			"deep/a/b/c.txt",
+		];
+
+		let dir = create_tree(&files);
+		let root = dir.path();
+		fs::write(root.join(".gitignore"), "build/\n").unwrap();
+
+		let mut index = FileIndex::new(get_config(root));
+		index.refresh();
+
+		assert_eq!(vec!["notes.txt", "project/src/main.rs"], get_paths(&index, root));
+	}
+
+	#[test]
+	fn should_update() {
+		let dir = create_tree(&["a/one.txt", "b/two.txt"]);
+		let root = dir.path();
+		let mut index = FileIndex::new(get_config(root));
+		index.refresh();
+
+		fs::create_dir_all(root.join("c/node_modules")).unwrap();
+		fs::write(root.join("c/three.txt"), "").unwrap();
+		fs::write(root.join("c/node_modules/four.txt"), "").unwrap();
+		let entries = index.walker().walk(&root.join("c"));
+		index.replace(&root.join("c"), entries);
+		index.remove(&root.join("a"));
+
+		assert_eq!(vec!["b/two.txt", "c/three.txt"], get_paths(&index, root));
+		assert_eq!(vec![root.join("c")], index.directories(&root.join("c")));
+	}
+
+	#[test]
+	fn should_populate() {
+		let dir = create_tree(&["a/one.txt"]);
+		let root = dir.path();
+		let index = FileIndex::new(get_config(root)).shared();
+		let empty = get_paths(&index.read().unwrap(), root);
+
+		populate(&index);
+
+		assert!(empty.is_empty());
+		assert_eq!(vec!["a/one.txt"], get_paths(&index.read().unwrap(), root));
+	}
+
+	#[rstest]
+	#[case("/home/me/notes.txt", "~/notes.txt")]
+	#[case("/home/me", "~")]
+	#[case("/etc/hosts", "/etc/hosts")]
+	#[case("/home/meow/x", "/home/meow/x")]
+	fn should_get_display(#[case] path: &str, #[case] expected: &str) {
+		assert_eq!(expected, get_display(Path::new(path), Path::new("/home/me")));
+	}
+}
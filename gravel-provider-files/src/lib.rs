@@ -0,0 +1,149 @@
+//! Files provider.
+//! Searches for files and directories and opens them with the default application.
+//!
+//! Indexes the configured roots up to a maximum depth, skipping paths that
+//! match the configured ignore globs or are listed in .gitignore files.
+//! The query is fuzzy-matched against the whole path.
+//!
+//! The best matching file additionally gets hits to open the directory
+//! containing it and to copy its path to the clipboard.
+//!
+//! Files are indexed in the background on startup, so no files are found
+//! until that finishes.
+//!
+//! ### Linux
+//! The indexed directories are watched using inotify, so the index stays
+//! up to date without restarting gravel.
+//!
+//! ### Windows
+//! Files are indexed once on startup.
+
+use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::get_text_score, *};
+use index::{FileEntry, FileIndex, SharedIndex};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::path::Path;
+use std::sync::{mpsc::Sender, Arc};
+
+mod index;
+
+#[cfg_attr(target_os = "linux", path = "linux.rs")]
+#[cfg_attr(windows, path = "windows.rs")]
+mod implementation;
+
+const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
+
+pub fn register_plugins(registry: &mut PluginRegistry) {
+	let definition = plugin("files").with_provider(Box::new(get_provider));
+
+	registry.register(definition);
+}
+
+fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
+	let config = Arc::new(config_adapter.get::<Config>(DEFAULT_CONFIG));
+
+	let index = FileIndex::new(config.clone()).shared();
+
+	Box::new(FilesProvider { config, index })
+}
+
+struct FilesProvider {
+	config: Arc<Config>,
+	index: SharedIndex,
+}
+
+impl Provider for FilesProvider {
+	fn query(&self, query: &str) -> ProviderResult {
+		let index = self.index.read().expect("thread holding the lock can't panic");
+		let text = query.trim();
+
+		let matches = index
+			.entries()
+			.filter_map(|(path, entry)| Some((get_text_score(&entry.display, text)?, path, entry)))
+			.sorted_by_key(|(score, _, _)| Reverse(*score))
+			.take(self.config.max_hits)
+			.collect_vec();
+
+		let actions = matches
+			.first()
+			.map(|(score, path, entry)| self.get_action_hits(path, entry, *score))
+			.unwrap_or_default();
+
+		let hits = matches
+			.into_iter()
+			.map(|(score, path, entry)| get_hit(path, entry, score))
+			.chain(actions)
+			.collect();
+
+		ProviderResult::new(hits)
+	}
+
+	fn set_sender(&mut self, sender: Sender<FrontendMessage>) {
+		implementation::watch(self.index.clone(), sender);
+	}
+}
+
+impl FilesProvider {
+	/// Returns hits titled like "notes.txt: Copy Path". They share the file's
+	/// score, so they are shown right below it.
+	fn get_action_hits(&self, path: &Path, entry: &FileEntry, score: u32) -> Vec<Arc<dyn Hit>> {
+		let actions = &self.config.actions;
+
+		let folder = path.parent().map(Path::to_path_buf).unwrap_or_default();
+		let folder_title = format!("{}: {}", entry.name, actions.open_folder);
+		let folder_hit = SimpleHit::new(folder_title, &*entry.display, move |_, s| do_open(&folder, s));
+
+		let path = path.to_owned();
+		let copy_title = format!("{}: {}", entry.name, actions.copy_path);
+		let copy_hit = SimpleHit::new(copy_title, &*entry.display, move |_, s| do_copy(&path, s));
+
+		vec![
+			Arc::new(folder_hit.with_score(score)),
+			Arc::new(copy_hit.with_score(score)),
+		]
+	}
+}
+
+fn get_hit(path: &Path, entry: &FileEntry, score: u32) -> Arc<dyn Hit> {
+	let path = path.to_owned();
+	let hit = SimpleHit::new(&*entry.name, &*entry.display, move |_, s| do_open(&path, s)).with_score(score);
+
+	Arc::new(hit)
+}
+
+fn do_open(path: &Path, sender: &Sender<FrontendMessage>) {
+	log::debug!("opening {path:?}");
+
+	if let Err(err) = open::that(path) {
+		log::error!("unable to open {path:?}: {err}");
+	}
+
+	sender.send(FrontendMessage::Hide).ok();
+}
+
+fn do_copy(path: &Path, sender: &Sender<FrontendMessage>) {
+	let value = path.to_string_lossy();
+	log::debug!("copying path to clipboard: {value}");
+
+	clipboard::set_text(&value);
+
+	sender.send(FrontendMessage::Hide).ok();
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+	pub roots: Vec<String>,
+	pub max_depth: usize,
+	pub ignore: Vec<String>,
+	pub respect_gitignore: bool,
+	pub hidden: bool,
+	pub max_hits: usize,
+	pub actions: ActionsConfig,
+}
+
+#[derive(Deserialize, Debug)]
+struct ActionsConfig {
+	pub open_folder: String,
+	pub copy_path: String,
+}
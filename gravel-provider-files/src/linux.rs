@@ -0,0 +1,140 @@
+use crate::index::{self, SharedIndex, Walker};
+use gravel_core::FrontendMessage;
+use nix::errno::Errno;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// Files that change which paths are skipped while indexing.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+const ADDED: AddWatchFlags = AddWatchFlags::IN_CREATE.union(AddWatchFlags::IN_MOVED_TO);
+const REMOVED: AddWatchFlags = AddWatchFlags::IN_DELETE.union(AddWatchFlags::IN_MOVED_FROM);
+
+#[derive(Default)]
+struct Watches {
+	dirs: HashMap<WatchDescriptor, PathBuf>,
+	/// Whether the limit on inotify watches was reached, which is only logged once.
+	limit_reached: bool,
+}
+
+/// Populates the index on a new thread, then watches the roots and all
+/// indexed directories and updates the index whenever a file is created
+/// or removed. Asks the frontend to refresh once the index is populated.
+///
+/// Roots that don't exist at the time of indexing are not watched.
+pub(crate) fn watch(index: SharedIndex, sender: Sender<FrontendMessage>) {
+	thread::spawn(move || {
+		index::populate(&index);
+		sender.send(FrontendMessage::Refresh).ok();
+
+		watch_index(&index);
+	});
+}
+
+fn watch_index(index: &SharedIndex) {
+	let inotify = match Inotify::init(InitFlags::IN_CLOEXEC) {
+		Ok(inotify) => inotify,
+		Err(err) => {
+			log::error!("unable to initialize inotify, files will not be updated automatically: {err}");
+			return;
+		}
+	};
+
+	let dirs = {
+		let index = index.read().expect("thread holding the lock can't panic");
+		let roots = index.roots().map(|r| r.to_owned()).collect::<Vec<_>>();
+		let subdirs = roots.iter().flat_map(|r| index.directories(r)).collect::<Vec<_>>();
+
+		roots.into_iter().chain(subdirs).collect::<Vec<_>>()
+	};
+
+	let mut watches = Watches::default();
+	add_watches(&inotify, &mut watches, dirs);
+	log::debug!("watching {} directories for changes", watches.dirs.len());
+
+	let walker = index.read().expect("thread holding the lock can't panic").walker();
+	watch_loop(&inotify, watches, &walker, index);
+}
+
+fn add_watches(inotify: &Inotify, watches: &mut Watches, dirs: Vec<PathBuf>) {
+	let flags = ADDED | REMOVED | AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_ONLYDIR;
+
+	for dir in dirs {
+		match inotify.add_watch(&dir, flags) {
+			Ok(descriptor) => {
+				watches.dirs.insert(descriptor, dir);
+			}
+			Err(Errno::ENOSPC) if !watches.limit_reached => {
+				log::warn!(
+					"reached the inotify watch limit after {} directories, changes in {dir:?} and others won't be noticed. \
+					Consider raising fs.inotify.max_user_watches or indexing fewer directories",
+					watches.dirs.len()
+				);
+
+				watches.limit_reached = true;
+			}
+			Err(err) => log::debug!("not watching {dir:?}: {err}"),
+		}
+	}
+}
+
+/// Directories are walked without holding the index's lock, which is
+/// only taken to store the result, so queries aren't blocked meanwhile.
+fn watch_loop(inotify: &Inotify, mut watches: Watches, walker: &Walker, index: &SharedIndex) {
+	loop {
+		let events = match inotify.read_events() {
+			Ok(events) => events,
+			Err(err) => {
+				log::error!("error while watching files, stopping: {err}");
+				return;
+			}
+		};
+
+		for event in events {
+			if event.mask.contains(AddWatchFlags::IN_IGNORED) {
+				watches.dirs.remove(&event.wd);
+				continue;
+			}
+
+			let (Some(dir), Some(name)) = (watches.dirs.get(&event.wd), event.name) else {
+				continue;
+			};
+
+			let dir = dir.clone();
+			let path = dir.join(&name);
+
+			let is_ignore_file = IGNORE_FILES.iter().any(|f| name == *f);
+
+			// an ignore file changed, which might affect anything in its directory
+			let rescanned = match is_ignore_file {
+				true => dir,
+				false if event.mask.intersects(ADDED) => path,
+				false if event.mask.intersects(REMOVED) => {
+					index
+						.write()
+						.expect("thread holding the lock can't panic")
+						.remove(&path);
+					continue;
+				}
+				false => continue,
+			};
+
+			let entries = walker.walk(&rescanned);
+			let dirs = entries
+				.iter()
+				.filter(|(_, e)| e.is_dir)
+				.map(|(p, _)| p.clone())
+				.collect();
+
+			index
+				.write()
+				.expect("thread holding the lock can't panic")
+				.replace(&rescanned, entries);
+
+			add_watches(inotify, &mut watches, dirs);
+		}
+	}
+}
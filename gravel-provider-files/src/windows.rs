@@ -0,0 +1,13 @@
+use crate::index::{self, SharedIndex};
+use gravel_core::FrontendMessage;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// Populates the index on a new thread. Watching for changes isn't
+/// supported, files are only indexed on startup.
+pub(crate) fn watch(index: SharedIndex, sender: Sender<FrontendMessage>) {
+	thread::spawn(move || {
+		index::populate(&index);
+		sender.send(FrontendMessage::Refresh).ok();
+	});
+}
@@ -0,0 +1,258 @@
+//! Parses filters like `user:root` or `mem>500M` out of the query.
+
+use crate::ProcessInfo;
+use std::time::Duration;
+
+/// Filters given in the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filters {
+	pub user: UserFilter,
+	pub memory: Vec<(Comparison, u64)>,
+	pub cpu: Vec<(Comparison, f32)>,
+	/// Whether the query contained any filters at all.
+	pub explicit: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserFilter {
+	/// Only processes of the user running gravel.
+	Current,
+	All,
+	Name(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+	Less,
+	LessOrEqual,
+	Greater,
+	GreaterOrEqual,
+}
+
+impl Filters {
+	pub fn matches(&self, process: &ProcessInfo) -> bool {
+		let user = match &self.user {
+			UserFilter::Current => process.own,
+			UserFilter::All => true,
+			UserFilter::Name(name) => process.user == *name,
+		};
+
+		user && self.memory.iter().all(|(c, v)| c.compare(process.memory, *v))
+			&& self.cpu.iter().all(|(c, v)| c.compare(process.cpu, *v))
+	}
+}
+
+impl Comparison {
+	fn compare<T: PartialOrd>(self, a: T, b: T) -> bool {
+		match self {
+			Comparison::Less => a < b,
+			Comparison::LessOrEqual => a <= b,
+			Comparison::Greater => a > b,
+			Comparison::GreaterOrEqual => a >= b,
+		}
+	}
+}
+
+/// Splits the query into filters and the remaining text.
+///
+/// `current_user_only` determines the user filter if none is given.
+/// `user:*` shows processes of all users.
+pub fn parse(query: &str, current_user_only: bool) -> (Filters, String) {
+	let mut filters = Filters {
+		user: match current_user_only {
+			true => UserFilter::Current,
+			false => UserFilter::All,
+		},
+		memory: vec![],
+		cpu: vec![],
+		explicit: false,
+	};
+
+	let mut text = vec![];
+
+	for word in query.split(' ') {
+		if !parse_filter(word, &mut filters) {
+			text.push(word);
+			continue;
+		}
+
+		filters.explicit = true;
+	}
+
+	(filters, text.join(" ").trim().to_owned())
+}
+
+/// Adds the filter to the set, returns false if the word isn't one.
+fn parse_filter(word: &str, filters: &mut Filters) -> bool {
+	if let Some(user) = word.strip_prefix("user:").filter(|u| !u.is_empty()) {
+		filters.user = match user {
+			"*" => UserFilter::All,
+			name => UserFilter::Name(name.to_owned()),
+		};
+
+		return true;
+	}
+
+	if let Some((comparison, value)) = word.strip_prefix("mem").and_then(parse_comparison) {
+		let Some(value) = parse_size(value) else {
+			return false;
+		};
+
+		filters.memory.push((comparison, value));
+		return true;
+	}
+
+	if let Some((comparison, value)) = word.strip_prefix("cpu").and_then(parse_comparison) {
+		let Ok(value) = value.trim_end_matches('%').parse() else {
+			return false;
+		};
+
+		filters.cpu.push((comparison, value));
+		return true;
+	}
+
+	false
+}
+
+fn parse_comparison(value: &str) -> Option<(Comparison, &str)> {
+	[
+		(">=", Comparison::GreaterOrEqual),
+		("<=", Comparison::LessOrEqual),
+		(">", Comparison::Greater),
+		("<", Comparison::Less),
+	]
+	.into_iter()
+	.find_map(|(prefix, comparison)| Some((comparison, value.strip_prefix(prefix)?)))
+}
+
+/// Parses sizes like `500M` or `1.5G`, using binary units.
+/// Plain numbers are bytes.
+fn parse_size(value: &str) -> Option<u64> {
+	let value = value.to_uppercase();
+	let value = value.trim_end_matches("IB").trim_end_matches('B');
+
+	let (number, exponent) = match value.char_indices().last()? {
+		(i, 'K') => (&value[..i], 1),
+		(i, 'M') => (&value[..i], 2),
+		(i, 'G') => (&value[..i], 3),
+		(i, 'T') => (&value[..i], 4),
+		_ => (value, 0),
+	};
+
+	let number = number.parse::<f64>().ok().filter(|n| *n >= 0.0)?;
+
+	Some((number * 1024_f64.powi(exponent)) as u64)
+}
+
+/// Formats the size like `350.2M`, the same way filters accept it.
+pub fn format_size(bytes: u64) -> String {
+	const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+
+	let mut value = bytes as f64;
+	let mut unit = "B";
+
+	for next in UNITS {
+		if value < 1024.0 {
+			break;
+		}
+
+		value /= 1024.0;
+		unit = next;
+	}
+
+	match unit {
+		"B" => format!("{bytes}B"),
+		_ => format!("{value:.1}{unit}"),
+	}
+}
+
+/// Formats the duration with its two most significant units, like `3h 5m`.
+pub fn format_duration(duration: Duration) -> String {
+	let secs = duration.as_secs();
+	let (days, hours, minutes) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60);
+
+	match (days, hours, minutes) {
+		(0, 0, 0) => format!("{secs}s"),
+		(0, 0, m) => format!("{m}m"),
+		(0, h, m) => format!("{h}h {m}m"),
+		(d, h, _) => format!("{d}d {h}h"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	fn get_process(user: &str, own: bool, memory: u64, cpu: f32) -> ProcessInfo {
+		ProcessInfo {
+			pid: 100,
+			start_time: 0,
+			parent: None,
+			name: String::from("test"),
+			cmdline: String::from("test"),
+			user: user.to_owned(),
+			own,
+			cpu,
+			memory,
+			uptime: Duration::ZERO,
+		}
+	}
+
+	#[rstest]
+	#[case("firefox", "firefox", false)]
+	#[case("user:root firefox", "firefox", true)]
+	#[case("mem>500M", "", true)]
+	#[case("fire mem<1G cpu>=5 fox", "fire fox", true)]
+	#[case("user:", "user:", false)]
+	#[case("mem>lots", "mem>lots", false)]
+	#[case("memory", "memory", false)]
+	fn should_parse(#[case] query: &str, #[case] expected_text: &str, #[case] expected_explicit: bool) {
+		let (filters, text) = parse(query, true);
+
+		assert_eq!(expected_text, text);
+		assert_eq!(expected_explicit, filters.explicit);
+	}
+
+	#[rstest]
+	#[case("", true, get_process("me", true, 0, 0.0))]
+	#[case("", false, get_process("me", false, 0, 0.0))]
+	#[case("user:*", true, get_process("root", false, 0, 0.0))]
+	#[case("user:root", true, get_process("root", false, 0, 0.0))]
+	#[case("mem>500M", true, get_process("me", true, 600 * 1024 * 1024, 0.0))]
+	#[case("mem<=1.5g", true, get_process("me", true, 1024 * 1024 * 1024, 0.0))]
+	#[case("cpu>10%", true, get_process("me", true, 0, 12.5))]
+	#[case("cpu<10 mem>=1K", true, get_process("me", true, 1024, 2.0))]
+	fn should_match(#[case] query: &str, #[case] current_user_only: bool, #[case] process: ProcessInfo) {
+		let (filters, _) = parse(query, current_user_only);
+		assert!(filters.matches(&process), "{query}");
+	}
+
+	#[rstest]
+	#[case("", true, get_process("root", false, 0, 0.0))]
+	#[case("user:root", true, get_process("me", true, 0, 0.0))]
+	#[case("mem>500M", true, get_process("me", true, 400 * 1024 * 1024, 0.0))]
+	#[case("cpu>10", true, get_process("me", true, 0, 5.0))]
+	fn should_not_match(#[case] query: &str, #[case] current_user_only: bool, #[case] process: ProcessInfo) {
+		let (filters, _) = parse(query, current_user_only);
+		assert!(!filters.matches(&process), "{query}");
+	}
+
+	#[rstest]
+	#[case(512, "512B")]
+	#[case(2048, "2.0K")]
+	#[case(350 * 1024 * 1024 + 200 * 1024, "350.2M")]
+	#[case(3 * 1024 * 1024 * 1024, "3.0G")]
+	fn should_format_size(#[case] bytes: u64, #[case] expected: &str) {
+		assert_eq!(expected, format_size(bytes));
+	}
+
+	#[rstest]
+	#[case(45, "45s")]
+	#[case(12 * 60 + 5, "12m")]
+	#[case(3 * 3600 + 5 * 60, "3h 5m")]
+	#[case(2 * 86400 + 4 * 3600 + 60, "2d 4h")]
+	fn should_format_duration(#[case] secs: u64, #[case] expected: &str) {
+		assert_eq!(expected, format_duration(Duration::from_secs(secs)));
+	}
+}
@@ -1,53 +1,447 @@
 //! gravel's process killer
 //! Lists running processes on your system and will allow you to kill them.
+//!
+//! Each hit shows the process' user, CPU usage, memory usage and uptime.
+//! By default, only processes of the current user are listed. Processes
+//! sharing a name are additionally grouped into a single hit that kills
+//! all of them.
+//!
+//! The list of processes is only read when the query starts and then
+//! reused for a few seconds, or until a process was killed. Processes that
+//! exited in the meantime are marked as such.
+//!
+//! The query accepts filters, such as `user:root`, `user:*` for all users,
+//! `mem>500M` or `cpu>=10`.
+//!
+//! On Linux, processes are asked to exit with SIGTERM first and only killed
+//! with SIGKILL if they are still running after a timeout. Other signals,
+//! such as SIGSTOP, are offered as extra hits once the query contains "sig".
+//!
+//! gravel itself and the init process are never killed. Processes that are
+//! important to the session have to be confirmed by selecting them twice,
+//! optionally all of them. When killing a process also kills its children,
+//! the same applies to each of them.
 
-use gravel_core::{config::PluginConfigAdapter, plugin::*, *};
-use implementation::Pid;
+use filter::{format_duration, format_size};
+use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::get_text_score, *};
+use implementation::{Pid, Signal};
+use itertools::Itertools;
+use serde::Deserialize;
+use snapshot::Snapshots;
+use std::collections::{HashMap, HashSet};
+use std::iter::once;
 use std::sync::{mpsc::Sender, Arc};
+use std::time::Duration;
+
+mod filter;
+mod snapshot;
 
 #[cfg_attr(target_os = "linux", path = "linux.rs")]
 #[cfg_attr(windows, path = "windows.rs")]
 mod implementation;
 
+const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
+
 pub fn register_plugins(registry: &mut PluginRegistry) {
 	let definition = plugin("kill").with_provider(Box::new(get_provider));
 
 	registry.register(definition);
 }
 
-fn get_provider(_config: &PluginConfigAdapter) -> Box<dyn Provider> {
-	Box::new(KillProvider {})
+fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
+	let config = config_adapter.get::<Config>(DEFAULT_CONFIG);
+	let signals = implementation::get_signals(&config);
+	let snapshots = Snapshots::new(Duration::from_secs_f32(config.snapshot_lifetime.max(0.0)));
+
+	let context = Context { config, snapshots };
+
+	Box::new(KillProvider {
+		context: Arc::new(context),
+		signals,
+	})
+}
+
+pub struct KillProvider {
+	context: Arc<Context>,
+	signals: Vec<Signal>,
 }
 
-pub struct KillProvider {}
+/// State shared between the provider and its hits.
+struct Context {
+	config: Config,
+	snapshots: Snapshots,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Request {
+	target: Target,
+	/// Signal to send instead of terminating the process.
+	signal: Option<Signal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+	Process(Pid),
+	/// All processes with this name.
+	Group(String),
+}
+
+/// A running process.
+#[derive(Debug, Clone)]
+pub(crate) struct ProcessInfo {
+	pub pid: Pid,
+	/// Tells the process apart from later ones that reuse its PID.
+	pub start_time: u64,
+	pub parent: Option<Pid>,
+	pub name: String,
+	pub cmdline: String,
+	pub user: String,
+	/// Whether the process belongs to the user running gravel.
+	pub own: bool,
+	/// CPU usage in percent of a single core.
+	pub cpu: f32,
+	/// Resident memory in bytes.
+	pub memory: u64,
+	pub uptime: Duration,
+}
+
+/// A process or group of processes to create hits for.
+struct Entry {
+	target: Target,
+	title: String,
+	name: String,
+	subtitle: String,
+	/// PIDs and start times of the processes, as seen in the snapshot.
+	processes: Vec<(Pid, u64)>,
+	protection: Protection,
+	/// Whether all processes have exited since the snapshot was taken.
+	vanished: bool,
+}
+
+/// Ordered from least to most restrictive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Protection {
+	None,
+	Confirm,
+	Refuse,
+}
 
 impl Provider for KillProvider {
-	fn query(&self, _query: &str) -> ProviderResult {
-		let hits = match implementation::query() {
-			Ok(hits) => hits,
-			Err(err) => {
-				log::error!("error while querying running processes: {err}");
-				vec![]
+	fn query(&self, query: &str) -> ProviderResult {
+		let config = &self.context.config;
+		let (filters, text) = filter::parse(query, config.current_user_only);
+
+		let snapshot = self.context.snapshots.get();
+		let processes = snapshot.processes.iter().filter(|p| filters.matches(p)).collect_vec();
+
+		let mut entries = processes
+			.iter()
+			.map(|p| get_process_entry(config, p, &snapshot.vanished))
+			.collect_vec();
+
+		if config.group_by_name {
+			entries.extend(get_group_entries(config, &processes, &snapshot.vanished));
+		}
+
+		if config.kill_tree {
+			for entry in &mut entries {
+				let pids = entry.processes.iter().map(|(pid, _)| *pid).collect_vec();
+				let descendants = get_descendants(config, &snapshot.processes, &pids);
+				let strictest = descendants.into_iter().map(|(_, protection)| protection).max();
+
+				entry.protection = entry.protection.max(strictest.unwrap_or(Protection::None));
 			}
-		};
+		}
+
+		let show_signals = text.to_lowercase().contains("sig");
+		let signals = self.signals.iter().filter(|_| show_signals).copied().map(Some);
+
+		let hits = entries
+			.iter()
+			.cartesian_product(once(None).chain(signals))
+			.map(|(entry, signal)| get_hit(&self.context, entry, signal))
+			.filter_map(|hit| match filters.explicit {
+				// the filters would prevent the title from matching, so score the remaining text instead
+				true => get_score(&hit, &text).map(|score| hit.with_score(score)),
+				false => Some(hit),
+			})
+			.map(|hit| Arc::new(hit) as Arc<dyn Hit>)
+			.collect();
 
 		ProviderResult::new(hits)
 	}
 }
 
-pub(crate) fn get_hit(name: &str, pid: Pid, cmdline: &str) -> Arc<dyn Hit> {
-	let title = format!("{name} - {pid}");
+fn get_score(hit: &SimpleHit, text: &str) -> Option<u32> {
+	match text.is_empty() {
+		true => Some(0),
+		false => get_text_score(hit.get_title(), text),
+	}
+}
+
+fn get_process_entry(config: &Config, process: &ProcessInfo, vanished: &HashSet<Pid>) -> Entry {
+	let subtitle = format!(
+		"{}, {:.1}% CPU, {}, up {} - {}",
+		process.user,
+		process.cpu,
+		format_size(process.memory),
+		format_duration(process.uptime),
+		process.cmdline
+	);
 
-	let hit = SimpleHit::new(title, cmdline, move |_, s| do_kill(s, pid));
-	Arc::new(hit)
+	Entry {
+		target: Target::Process(process.pid),
+		title: format!("{} - {}", process.name, process.pid),
+		name: process.name.clone(),
+		subtitle,
+		processes: vec![(process.pid, process.start_time)],
+		protection: get_protection(config, process),
+		vanished: vanished.contains(&process.pid),
+	}
 }
 
-fn do_kill(sender: &Sender<FrontendMessage>, pid: Pid) {
-	log::debug!("attempting to kill PID {pid}");
+/// Returns an entry for each name shared by multiple running processes.
+fn get_group_entries(config: &Config, processes: &[&ProcessInfo], vanished: &HashSet<Pid>) -> Vec<Entry> {
+	processes
+		.iter()
+		.filter(|p| get_protection(config, p) != Protection::Refuse && !vanished.contains(&p.pid))
+		.into_group_map_by(|p| p.name.as_str())
+		.into_iter()
+		.filter(|(_, group)| group.len() > 1)
+		.map(|(name, group)| {
+			let cpu = group.iter().map(|p| p.cpu).sum::<f32>();
+			let memory = group.iter().map(|p| p.memory).sum();
+			let subtitle = format!("{} processes, {cpu:.1}% CPU, {}", group.len(), format_size(memory));
+
+			let protection = match config.confirm.iter().any(|c| c == name) {
+				true => Protection::Confirm,
+				false => Protection::None,
+			};
+
+			Entry {
+				target: Target::Group(name.to_owned()),
+				title: format!("{name} ({} processes)", group.len()),
+				name: name.to_owned(),
+				subtitle,
+				processes: group.iter().map(|p| (p.pid, p.start_time)).collect(),
+				protection,
+				vanished: false,
+			}
+		})
+		.collect()
+}
 
-	if let Err(err) = implementation::kill_process(pid) {
-		log::error!("killing PID {pid} failed: {err}");
+fn get_hit(context: &Arc<Context>, entry: &Entry, signal: Option<Signal>) -> SimpleHit {
+	let request = Request {
+		target: entry.target.clone(),
+		signal,
+	};
+
+	let title = match signal {
+		None => entry.title.clone(),
+		Some(signal) => format!("{}: {signal}", entry.title),
+	};
+
+	let subtitle = match entry.vanished {
+		true => format!("No longer running - {}", entry.subtitle),
+		false => entry.subtitle.clone(),
+	};
+
+	let confirmation = match entry.protection {
+		Protection::Confirm => Some(format!("Press Enter again to kill {}", entry.name)),
+		_ => None,
+	};
+
+	let protection = entry.protection;
+	let processes = entry.processes.clone();
+	let context = context.clone();
+
+	SimpleHit::new(title, subtitle, move |_, s| {
+		do_kill(&context, &request, &processes, protection, s)
+	})
+	.with_confirmation(confirmation)
+}
+
+fn do_kill(
+	context: &Context,
+	request: &Request,
+	processes: &[(Pid, u64)],
+	protection: Protection,
+	sender: &Sender<FrontendMessage>,
+) {
+	if protection == Protection::Refuse {
+		log::warn!("refusing to kill {:?}", request.target);
+		return;
 	}
 
+	// the snapshot may be outdated, so make sure the PIDs weren't reused by other processes
+	let (running, vanished): (Vec<_>, Vec<_>) = processes
+		.iter()
+		.partition(|(pid, start_time)| implementation::is_running(*pid, *start_time));
+
+	let running = running.into_iter().map(|(pid, _)| pid).collect_vec();
+	let vanished = vanished.into_iter().map(|(pid, _)| pid).collect_vec();
+	context.snapshots.mark_vanished(&vanished);
+
+	if running.is_empty() {
+		log::info!("{:?} is no longer running", request.target);
+		sender.send(FrontendMessage::Refresh).ok();
+		return;
+	}
+
+	let targets = match context.config.kill_tree {
+		true => get_tree(&context.config, &running, protection == Protection::Confirm),
+		false => running,
+	};
+
+	log::debug!("attempting to kill PIDs {targets:?} with {:?}", request.signal);
+
+	if let Err(err) = implementation::kill_processes(&targets, request.signal, &context.config) {
+		log::error!("killing {:?} failed: {err}", request.target);
+	}
+
+	context.snapshots.invalidate();
 	sender.send(FrontendMessage::Refresh).ok();
 }
+
+fn get_protection(config: &Config, process: &ProcessInfo) -> Protection {
+	if process.pid == implementation::get_own_pid() || implementation::PROTECTED_PIDS.contains(&process.pid) {
+		return Protection::Refuse;
+	}
+
+	match config.confirm_all || config.confirm.contains(&process.name) {
+		true => Protection::Confirm,
+		false => Protection::None,
+	}
+}
+
+/// Returns the processes and the descendants that may be killed along with them.
+/// Descendants that require confirmation are only included if it was given,
+/// since they might have started after the hit was shown.
+fn get_tree(config: &Config, pids: &[Pid], confirmed: bool) -> Vec<Pid> {
+	let processes = implementation::query().unwrap_or_default();
+	let descendants = get_descendants(config, &processes, pids);
+
+	let (included, skipped): (Vec<_>, Vec<_>) = descendants
+		.into_iter()
+		.partition(|(_, protection)| *protection == Protection::None || confirmed);
+
+	if !skipped.is_empty() {
+		log::info!("not killing unconfirmed descendants {skipped:?}");
+	}
+
+	pids.iter()
+		.copied()
+		.chain(included.into_iter().map(|(pid, _)| pid))
+		.collect()
+}
+
+/// Returns all descendants of the processes along with their protection.
+/// Processes that must never be killed are left out, as are their own descendants.
+fn get_descendants(config: &Config, processes: &[ProcessInfo], pids: &[Pid]) -> Vec<(Pid, Protection)> {
+	let mut children = HashMap::<Pid, Vec<&ProcessInfo>>::new();
+	for process in processes {
+		if let Some(parent) = process.parent {
+			children.entry(parent).or_default().push(process);
+		}
+	}
+
+	let mut visited = pids.iter().copied().collect::<HashSet<_>>();
+	let mut tree = pids.to_vec();
+	let mut descendants = vec![];
+	let mut i = 0;
+
+	while i < tree.len() {
+		for child in children.get(&tree[i]).into_iter().flatten() {
+			let protection = get_protection(config, child);
+
+			if protection != Protection::Refuse && visited.insert(child.pid) {
+				tree.push(child.pid);
+				descendants.push((child.pid, protection));
+			}
+		}
+
+		i += 1;
+	}
+
+	descendants
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+	pub snapshot_lifetime: f32,
+	pub current_user_only: bool,
+	pub group_by_name: bool,
+	pub kill_tree: bool,
+	pub confirm_all: bool,
+	pub confirm: Vec<String>,
+	#[cfg(target_os = "linux")]
+	pub linux: LinuxConfig,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Deserialize, Debug)]
+struct LinuxConfig {
+	pub term_timeout: f32,
+	pub signals: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn get_config(confirm: &[&str]) -> Config {
+		Config {
+			snapshot_lifetime: 0.0,
+			current_user_only: true,
+			group_by_name: false,
+			kill_tree: true,
+			confirm_all: false,
+			confirm: confirm.iter().map(ToString::to_string).collect(),
+			#[cfg(target_os = "linux")]
+			linux: LinuxConfig {
+				term_timeout: 0.0,
+				signals: vec![],
+			},
+		}
+	}
+
+	fn get_process(pid: Pid, parent: Pid, name: &str) -> ProcessInfo {
+		ProcessInfo {
+			pid,
+			start_time: 0,
+			parent: Some(parent),
+			name: name.to_owned(),
+			cmdline: name.to_owned(),
+			user: String::from("me"),
+			own: true,
+			cpu: 0.0,
+			memory: 0,
+			uptime: Duration::ZERO,
+		}
+	}
+
+	#[test]
+	fn should_protect_descendants() {
+		let own_pid = implementation::get_own_pid();
+		let processes = [
+			get_process(100, 10, "terminal"),
+			get_process(200, 100, "shell"),
+			get_process(own_pid, 200, "gravel"),
+			get_process(300, own_pid, "launched-by-gravel"),
+			get_process(400, 100, "Xwayland"),
+			get_process(500, 400, "x-client"),
+		];
+
+		let mut descendants = get_descendants(&get_config(&["Xwayland"]), &processes, &[100]);
+		descendants.sort();
+
+		let expected = [
+			(200, Protection::None),
+			(400, Protection::Confirm),
+			(500, Protection::None),
+		];
+
+		assert_eq!(expected.to_vec(), descendants);
+	}
+}
@@ -1,38 +1,188 @@
-use std::sync::Arc;
-
+use crate::{Config, ProcessInfo};
 use anyhow::Result;
-use gravel_core::Hit;
-use itertools::Itertools;
-use nix::sys::signal::{kill, Signal};
+use nix::sys::signal::kill;
+use nix::unistd::{getuid, Uid, User};
 use procfs::process::Process;
+use procfs::{Current, Uptime};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub use nix::sys::signal::Signal;
 
 pub type Pid = i32;
 
-pub fn kill_process(pid: Pid) -> Result<()> {
-	let pid = nix::unistd::Pid::from_raw(pid);
+/// Processes that can never be killed, in addition to gravel itself.
+pub const PROTECTED_PIDS: &[Pid] = &[1];
+
+/// How often to check whether terminated processes have exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn get_own_pid() -> Pid {
+	std::process::id() as Pid
+}
+
+/// Parses the configured signals, skipping invalid ones.
+pub fn get_signals(config: &Config) -> Vec<Signal> {
+	config
+		.linux
+		.signals
+		.iter()
+		.filter_map(|name| {
+			Signal::from_str(name)
+				.map_err(|_| log::warn!("unknown signal '{name}'"))
+				.ok()
+		})
+		.collect()
+}
+
+/// Sends the signal to all processes. Without a signal, the processes are
+/// asked to exit with SIGTERM first and killed with SIGKILL after the
+/// configured timeout.
+pub fn kill_processes(pids: &[Pid], signal: Option<Signal>, config: &Config) -> Result<()> {
+	let timeout = Duration::from_secs_f32(config.linux.term_timeout.max(0.0));
+
+	let signal = match signal {
+		Some(signal) => signal,
+		None if timeout.is_zero() => Signal::SIGKILL,
+		None => return terminate(pids, timeout),
+	};
+
+	send_signal(pids, signal)
+}
+
+fn terminate(pids: &[Pid], timeout: Duration) -> Result<()> {
+	// remember the start times, so we don't kill an unrelated process that reused the PID
+	let targets = pids
+		.iter()
+		.filter_map(|pid| Some((*pid, get_start_time(*pid)?)))
+		.collect::<Vec<_>>();
+
+	let result = send_signal(pids, Signal::SIGTERM);
+	thread::spawn(move || escalate(targets, timeout));
+
+	result
+}
+
+/// Waits for the processes to exit and kills those that haven't after the timeout.
+fn escalate(mut targets: Vec<(Pid, u64)>, timeout: Duration) {
+	let deadline = Instant::now() + timeout;
+
+	while Instant::now() < deadline {
+		targets.retain(|(pid, start_time)| is_running(*pid, *start_time));
+
+		if targets.is_empty() {
+			return;
+		}
+
+		thread::sleep(POLL_INTERVAL);
+	}
 
-	kill(pid, Signal::SIGKILL)?;
-	Ok(())
+	for (pid, start_time) in targets {
+		if is_running(pid, start_time) {
+			log::info!("PID {pid} is still running after SIGTERM, sending SIGKILL");
+			send_signal(&[pid], Signal::SIGKILL).ok();
+		}
+	}
 }
 
-pub fn query() -> Result<Vec<Arc<dyn Hit>>> {
-	let hits = procfs::process::all_processes()?
+/// Sends the signal to all processes, even if some fail.
+/// Returns the first error encountered.
+fn send_signal(pids: &[Pid], signal: Signal) -> Result<()> {
+	let mut result = Ok(());
+
+	for pid in pids {
+		if let Err(err) = kill(nix::unistd::Pid::from_raw(*pid), signal) {
+			log::debug!("sending {signal} to PID {pid} failed: {err}");
+			result = result.and(Err(err));
+		}
+	}
+
+	Ok(result?)
+}
+
+fn get_start_time(pid: Pid) -> Option<u64> {
+	Process::new(pid).and_then(|p| p.stat()).map(|s| s.starttime).ok()
+}
+
+/// Whether the process started at the given time still exists and hasn't
+/// exited yet. Zombies are considered to have exited, they only wait for
+/// their parent.
+pub fn is_running(pid: Pid, start_time: u64) -> bool {
+	Process::new(pid)
+		.and_then(|p| p.stat())
+		.is_ok_and(|s| s.starttime == start_time && s.state != 'Z')
+}
+
+pub fn query() -> Result<Vec<ProcessInfo>> {
+	let mut system = SystemInfo {
+		uptime: Uptime::current()?.uptime,
+		ticks_per_second: procfs::ticks_per_second() as f64,
+		page_size: procfs::page_size(),
+		own_uid: getuid().as_raw(),
+		users: HashMap::new(),
+	};
+
+	let processes = procfs::process::all_processes()?
 		.filter_map(Result::ok)
-		.filter_map(|p| get_hit(&p).ok())
-		.collect_vec();
+		.filter_map(|p| get_process_info(&p, &mut system).ok())
+		.collect();
 
-	Ok(hits)
+	Ok(processes)
 }
 
-fn get_hit(process: &Process) -> Result<Arc<dyn Hit>> {
+/// Values needed to interpret the numbers in `/proc`.
+struct SystemInfo {
+	/// System uptime in seconds.
+	uptime: f64,
+	ticks_per_second: f64,
+	page_size: u64,
+	own_uid: u32,
+	/// Cache for user names.
+	users: HashMap<u32, String>,
+}
+
+fn get_process_info(process: &Process, system: &mut SystemInfo) -> Result<ProcessInfo> {
 	let args = process.cmdline()?;
+	let stat = process.stat()?;
+	let uid = process.uid()?;
+
 	let cmdline = args.join(" ").replace('\n', "\\n");
 	let name = get_cmdline_binary(&args)
 		.or_else(|| get_exe_binary(process))
-		.or_else(|| get_command_name(process))
-		.unwrap_or(String::from("unknown process"));
+		.unwrap_or_else(|| format!("[{}]", stat.comm));
+
+	let uptime = (system.uptime - stat.starttime as f64 / system.ticks_per_second).max(0.0);
+	let cpu_time = (stat.utime + stat.stime) as f64 / system.ticks_per_second;
+
+	// average since the process started, there's no earlier sample to compare against
+	let cpu = match uptime > 0.0 {
+		true => (cpu_time / uptime * 100.0) as f32,
+		false => 0.0,
+	};
+
+	let user = system.users.entry(uid).or_insert_with(|| get_user_name(uid)).clone();
 
-	Ok(super::get_hit(&name, process.pid, &cmdline))
+	Ok(ProcessInfo {
+		pid: process.pid,
+		start_time: stat.starttime,
+		parent: Some(stat.ppid).filter(|ppid| *ppid != 0),
+		name,
+		cmdline,
+		user,
+		own: uid == system.own_uid,
+		cpu,
+		memory: stat.rss * system.page_size,
+		uptime: Duration::from_secs_f64(uptime),
+	})
+}
+
+fn get_user_name(uid: u32) -> String {
+	match User::from_uid(Uid::from_raw(uid)) {
+		Ok(Some(user)) => user.name,
+		_ => uid.to_string(),
+	}
 }
 
 fn get_cmdline_binary(args: &[String]) -> Option<String> {
@@ -50,6 +200,16 @@ fn get_exe_binary(process: &Process) -> Option<String> {
 	exe.file_name().map(|s| s.to_string_lossy().into_owned())
 }
 
-fn get_command_name(process: &Process) -> Option<String> {
-	process.stat().ok().map(|s| format!("[{}]", s.comm))
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_tell_reused_pids_apart() {
+		let pid = get_own_pid();
+		let start_time = get_start_time(pid).unwrap();
+
+		assert!(is_running(pid, start_time));
+		assert!(!is_running(pid, start_time + 1));
+	}
 }
@@ -0,0 +1,77 @@
+use crate::implementation::{self, Pid};
+use crate::ProcessInfo;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Caches the list of running processes, so that it isn't read again on
+/// every keystroke.
+///
+/// The list is re-read when it's older than the configured lifetime, or
+/// after it was invalidated, for example because a process was killed.
+pub struct Snapshots {
+	lifetime: Duration,
+	current: Mutex<Option<Snapshot>>,
+}
+
+#[derive(Clone)]
+pub struct Snapshot {
+	pub processes: Arc<Vec<ProcessInfo>>,
+	/// Processes that have exited since the snapshot was taken.
+	pub vanished: HashSet<Pid>,
+	taken: Instant,
+}
+
+impl Snapshots {
+	pub fn new(lifetime: Duration) -> Self {
+		Self {
+			lifetime,
+			current: Mutex::new(None),
+		}
+	}
+
+	/// Returns the current snapshot, taking a new one if necessary.
+	pub fn get(&self) -> Snapshot {
+		let mut current = self.current.lock().expect("thread holding the mutex can't panic");
+
+		match &*current {
+			Some(snapshot) if snapshot.taken.elapsed() < self.lifetime => snapshot.clone(),
+			_ => current.insert(take_snapshot()).clone(),
+		}
+	}
+
+	/// Forces a new snapshot on the next query.
+	pub fn invalidate(&self) {
+		self.current
+			.lock()
+			.expect("thread holding the mutex can't panic")
+			.take();
+	}
+
+	/// Remembers that the processes have exited, without taking a new snapshot.
+	pub fn mark_vanished(&self, pids: &[Pid]) {
+		let mut current = self.current.lock().expect("thread holding the mutex can't panic");
+
+		if let Some(snapshot) = &mut *current {
+			snapshot.vanished.extend(pids);
+		}
+	}
+}
+
+fn take_snapshot() -> Snapshot {
+	let processes = match implementation::query() {
+		Ok(processes) => processes,
+		Err(err) => {
+			log::error!("error while querying running processes: {err}");
+			vec![]
+		}
+	};
+
+	log::trace!("took snapshot of {} processes", processes.len());
+
+	Snapshot {
+		processes: Arc::new(processes),
+		vanished: HashSet::new(),
+		taken: Instant::now(),
+	}
+}
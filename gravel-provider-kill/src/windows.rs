@@ -1,15 +1,29 @@
+use crate::{Config, ProcessInfo};
 use anyhow::Result;
-use gravel_core::Hit;
 use itertools::Itertools;
-use std::sync::Arc;
-use sysinfo::{Process, System};
+use std::fmt;
+use std::time::Duration;
+use sysinfo::{Process, System, Uid, Users};
 use thiserror::Error;
-use winapi::shared::minwindef::DWORD;
-use winapi::um::errhandlingapi;
+use winapi::shared::minwindef::{DWORD, FILETIME};
+use winapi::um::{errhandlingapi, minwinbase};
 use winapi::um::{handleapi, processthreadsapi, winnt, winnt::HANDLE};
 
 pub type Pid = u32;
 
+/// Processes that can never be killed, in addition to gravel itself.
+pub const PROTECTED_PIDS: &[Pid] = &[0, 4];
+
+/// Windows has no signals, processes can only be terminated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {}
+
+impl fmt::Display for Signal {
+	fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {}
+	}
+}
+
 struct HandleWrapper {
 	pub handle: HANDLE,
 }
@@ -32,25 +46,86 @@ pub enum KillError {
 	NativeError(u32),
 }
 
-pub fn query() -> Result<Vec<Arc<dyn Hit>>> {
+pub fn get_own_pid() -> Pid {
+	std::process::id()
+}
+
+pub fn get_signals(_config: &Config) -> Vec<Signal> {
+	vec![]
+}
+
+pub fn query() -> Result<Vec<ProcessInfo>> {
 	// TODO: sysinfo crate loads a lot of unnecessary data into memory,
 	// replace with native calls (or a crate that does streaming)
 	let mut sys = System::new();
 	sys.refresh_processes();
 
-	let hits = sys
+	let users = Users::new_with_refreshed_list();
+	let own_user = sysinfo::get_current_pid()
+		.ok()
+		.and_then(|pid| sys.process(pid))
+		.and_then(|p| p.user_id().cloned());
+
+	let processes = sys
 		.processes()
 		.iter()
-		.map(|(pid, process)| get_hit(pid, process))
+		.map(|(pid, process)| get_process_info(pid, process, &users, own_user.as_ref()))
 		.collect_vec();
 
-	Ok(hits)
+	Ok(processes)
+}
+
+fn get_process_info(pid: &sysinfo::Pid, process: &Process, users: &Users, own_user: Option<&Uid>) -> ProcessInfo {
+	let user_id = process.user_id();
+	let user = user_id
+		.and_then(|id| users.get_user_by_id(id))
+		.map_or(String::new(), |u| u.name().to_owned());
+
+	ProcessInfo {
+		pid: pid.as_u32(),
+		start_time: process.start_time(),
+		parent: process.parent().map(|p| p.as_u32()),
+		name: process.name().to_owned(),
+		cmdline: process.cmd().join(" "),
+		user,
+		own: user_id.is_some() && user_id == own_user,
+		cpu: process.cpu_usage(),
+		memory: process.memory(),
+		uptime: Duration::from_secs(process.run_time()),
+	}
+}
+
+/// Whether the process started at the given time still exists and hasn't exited yet.
+pub fn is_running(pid: Pid, start_time: u64) -> bool {
+	let Ok(handle) = open_process(winnt::PROCESS_QUERY_LIMITED_INFORMATION, pid) else {
+		return false;
+	};
+
+	let mut exit_code = 0;
+	let result = unsafe { processthreadsapi::GetExitCodeProcess(handle.handle, &mut exit_code) };
+
+	result != 0 && exit_code == minwinbase::STILL_ACTIVE && get_start_time(&handle) == Some(start_time)
 }
 
-fn get_hit(pid: &sysinfo::Pid, process: &Process) -> Arc<dyn Hit> {
-	let cmdline = process.cmd().join(" ");
+/// Seconds since the Unix epoch, computed the same way as sysinfo's `start_time`.
+fn get_start_time(handle: &HandleWrapper) -> Option<u64> {
+	let zero = FILETIME {
+		dwLowDateTime: 0,
+		dwHighDateTime: 0,
+	};
+
+	let [mut creation, mut exit, mut kernel, mut user] = [zero; 4];
+	let result =
+		unsafe { processthreadsapi::GetProcessTimes(handle.handle, &mut creation, &mut exit, &mut kernel, &mut user) };
+
+	if result == 0 {
+		return None;
+	}
+
+	let creation = (creation.dwHighDateTime as u64) << 32 | creation.dwLowDateTime as u64;
 
-	super::get_hit(process.name(), pid.as_u32(), &cmdline)
+	// 11644473600 seconds between the Windows epoch (1601) and the Unix epoch
+	Some((creation / 10_000_000).saturating_sub(11_644_473_600))
 }
 
 fn open_process(desired_access: DWORD, pid: Pid) -> Result<HandleWrapper, KillError> {
@@ -69,10 +144,26 @@ fn get_last_error() -> KillError {
 	KillError::NativeError(errno)
 }
 
-pub fn kill_process(pid: Pid) -> Result<(), KillError> {
+/// Terminates all processes, even if some fail.
+/// Returns the first error encountered.
+pub fn kill_processes(pids: &[Pid], signal: Option<Signal>, _config: &Config) -> Result<(), KillError> {
+	if let Some(signal) = signal {
+		match signal {}
+	}
+
+	let mut result = Ok(());
+
+	for pid in pids {
+		result = result.and(kill_process(*pid));
+	}
+
+	result
+}
+
+fn kill_process(pid: Pid) -> Result<(), KillError> {
 	let handle = open_process(winnt::PROCESS_TERMINATE, pid)?;
 
-	if unsafe { processthreadsapi::TerminateProcess(handle.handle, 1) } != 0 {
+	if unsafe { processthreadsapi::TerminateProcess(handle.handle, 1) } == 0 {
 		return Err(get_last_error());
 	}
 
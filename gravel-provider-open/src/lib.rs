@@ -0,0 +1,110 @@
+//! Open provider.
+//! Detects URLs and paths in the query and opens them with the default
+//! application, so `github.com/thorio/gravel` opens the browser and
+//! `~/Documents/report.pdf` the document.
+//!
+//! URLs without a scheme get `https://`, or `http://` for local addresses.
+//! Paths have to be absolute, after expanding `~` and environment variables.
+//!
+//! While typing a path, matching directories are offered as completions.
+//! Selecting one replaces the query with it.
+
+use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::MAX_SCORE, *};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::{mpsc::Sender, Arc};
+
+mod path;
+mod url;
+
+const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
+
+pub fn register_plugins(registry: &mut PluginRegistry) {
+	let definition = plugin("open").with_provider(Box::new(get_provider));
+
+	registry.register(definition);
+}
+
+fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
+	let config = config_adapter.get::<Config>(DEFAULT_CONFIG);
+
+	Box::new(OpenProvider { config })
+}
+
+struct OpenProvider {
+	config: Config,
+}
+
+impl Provider for OpenProvider {
+	fn query(&self, query: &str) -> ProviderResult {
+		let query = query.trim();
+
+		if let Some(path) = path::expand(query) {
+			return ProviderResult::new(self.get_path_hits(query, &path));
+		}
+
+		match url::get_url(query) {
+			Some(url) => {
+				let title = url.clone();
+				let hit = SimpleHit::new(title, &*self.config.subtitles.url, move |_, s| do_open(&url, s));
+				ProviderResult::single(Arc::new(hit.with_score(MAX_SCORE)))
+			}
+			None => ProviderResult::empty(),
+		}
+	}
+}
+
+impl OpenProvider {
+	/// Returns a hit opening the path if it exists, followed by completions.
+	fn get_path_hits(&self, query: &str, path: &Path) -> Vec<Arc<dyn Hit>> {
+		let subtitles = &self.config.subtitles;
+
+		let open_hit = path.exists().then(|| {
+			let path = path.to_owned();
+			let hit = SimpleHit::new(query, &*subtitles.path, move |_, s| do_open(&path, s));
+
+			Arc::new(hit.with_score(MAX_SCORE)) as Arc<dyn Hit>
+		});
+
+		let completions = path::complete(query)
+			.into_iter()
+			.take(self.config.max_completions)
+			.enumerate()
+			.map(|(i, completion)| {
+				let title = completion.clone();
+				let hit = SimpleHit::new(title, &*subtitles.complete, move |_, s| do_complete(&completion, s));
+
+				Arc::new(hit.with_score(MAX_SCORE - 1 - i as u32)) as Arc<dyn Hit>
+			});
+
+		open_hit.into_iter().chain(completions).collect()
+	}
+}
+
+fn do_open(target: impl AsRef<std::ffi::OsStr>, sender: &Sender<FrontendMessage>) {
+	let target = target.as_ref();
+	log::debug!("opening {target:?}");
+
+	if let Err(err) = open::that(target) {
+		log::error!("unable to open {target:?}: {err}");
+	}
+
+	sender.send(FrontendMessage::Hide).ok();
+}
+
+fn do_complete(completion: &str, sender: &Sender<FrontendMessage>) {
+	sender.send(FrontendMessage::ShowWithQuery(completion.to_owned())).ok();
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+	pub max_completions: usize,
+	pub subtitles: SubtitlesConfig,
+}
+
+#[derive(Deserialize, Debug)]
+struct SubtitlesConfig {
+	pub url: String,
+	pub path: String,
+	pub complete: String,
+}
@@ -0,0 +1,95 @@
+//! Detection and completion of paths, like `/etc/hosts` or `~/Documents`.
+
+use std::fs;
+use std::path::{is_separator, PathBuf};
+
+/// Expands `~` and environment variables, returning the path if it's absolute.
+pub fn expand(query: &str) -> Option<PathBuf> {
+	let expanded = shellexpand::full(query).ok()?;
+	let path = PathBuf::from(&*expanded);
+
+	path.is_absolute().then_some(path)
+}
+
+/// Returns queries completing the last part of the path to directories
+/// in its parent, like `~/Documents/` for `~/doc`. The queries keep the
+/// form the user typed, so `~` stays `~`.
+///
+/// Hidden directories are only offered if the last part starts with a dot.
+pub fn complete(query: &str) -> Vec<String> {
+	// `~/Documents/` lists the directory, `~/Doc` the directories next to it
+	let prefix = query.rsplit(is_separator).next().unwrap_or_default();
+	let head = &query[..query.len() - prefix.len()];
+
+	let Some(entries) = expand(head).and_then(|d| fs::read_dir(d).ok()) else {
+		return vec![];
+	};
+
+	let separator = head.chars().last().filter(|c| is_separator(*c)).unwrap_or('/');
+	let prefix = prefix.to_lowercase();
+
+	let mut names = entries
+		.filter_map(Result::ok)
+		.filter(|e| e.path().is_dir())
+		.filter_map(|e| e.file_name().into_string().ok())
+		.filter(|name| name.to_lowercase().starts_with(&prefix))
+		.filter(|name| !name.starts_with('.') || prefix.starts_with('.'))
+		.collect::<Vec<_>>();
+
+	names.sort_by_key(|name| name.to_lowercase());
+
+	names
+		.into_iter()
+		.map(|name| format!("{head}{name}{separator}"))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+	use tempfile::TempDir;
+
+	/// Creates a directory tree in a new temporary directory.
+	fn create_tree() -> TempDir {
+		let dir = tempfile::tempdir().unwrap();
+
+		for directory in ["Documents", "Downloads", "docs", ".config", "Music"] {
+			fs::create_dir_all(dir.path().join(directory)).unwrap();
+		}
+
+		fs::write(dir.path().join("document.txt"), "").unwrap();
+		dir
+	}
+
+	#[rstest]
+	#[case("do", &["docs/", "Documents/", "Downloads/"])]
+	#[case("Mu", &["Music/"])]
+	#[case("", &["docs/", "Documents/", "Downloads/", "Music/"])]
+	#[case(".", &[".config/"])]
+	#[case("docs", &["docs/"])]
+	#[case("document", &["Documents/"])]
+	#[case("x", &[])]
+	fn should_complete(#[case] name: &str, #[case] expected: &[&str]) {
+		let dir = create_tree();
+		let root = dir.path();
+		let query = format!("{}/{name}", root.display());
+
+		let actual = complete(&query);
+
+		let expected = expected
+			.iter()
+			.map(|e| format!("{}/{e}", root.display()))
+			.collect::<Vec<_>>();
+		assert_eq!(expected, actual, "{name}");
+	}
+
+	#[rstest]
+	#[case("gravel")]
+	#[case("Documents/report.pdf")]
+	#[case("$GRAVEL_UNSET_VARIABLE/file")]
+	#[case("")]
+	fn should_not_expand(#[case] query: &str) {
+		assert_eq!(None, expand(query), "{query}");
+	}
+}
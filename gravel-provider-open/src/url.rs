@@ -0,0 +1,117 @@
+//! Detection of URLs, like `https://example.com` or `github.com/thorio/gravel`.
+
+/// Top level domains of queries that are URLs even without a scheme, path
+/// or port. Others are too easily confused with file names, like `notes.md`.
+const COMMON_TLDS: &[&str] = &[
+	"app", "com", "de", "dev", "edu", "eu", "fr", "gov", "info", "io", "me", "net", "nl", "org", "uk", "us",
+];
+
+/// Returns the URL to open for the query, adding a scheme if necessary.
+pub fn get_url(query: &str) -> Option<String> {
+	if query.contains(char::is_whitespace) || query.is_empty() {
+		return None;
+	}
+
+	if has_scheme(query) {
+		return Some(query.to_owned());
+	}
+
+	let end = query.find(['/', '?', '#']).unwrap_or(query.len());
+	let (authority, rest) = query.split_at(end);
+
+	let (host, port) = match authority.rsplit_once(':') {
+		Some((host, port)) if is_port(port) => (host, Some(port)),
+		Some(_) => return None,
+		None => (authority, None),
+	};
+
+	// without a port or path, an address could just as well be a version number
+	let explicit = port.is_some() || !rest.is_empty();
+
+	if host == "localhost" || (is_ipv4(host) && explicit) {
+		return Some(format!("http://{query}"));
+	}
+
+	let tld = get_tld(host)?;
+	let explicit = explicit || host.starts_with("www.");
+
+	(explicit || COMMON_TLDS.contains(&tld.to_lowercase().as_str())).then(|| format!("https://{query}"))
+}
+
+/// Checks for schemes like `https://`, as well as `mailto:`.
+fn has_scheme(query: &str) -> bool {
+	if query.starts_with("mailto:") {
+		return true;
+	}
+
+	let Some((scheme, _)) = query.split_once("://") else {
+		return false;
+	};
+
+	scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+		&& scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+}
+
+/// Returns the top level domain, if the host looks like a domain name.
+fn get_tld(host: &str) -> Option<&str> {
+	let labels = host.split('.').collect::<Vec<_>>();
+	let tld = *labels.last()?;
+
+	let valid = labels.len() >= 2 && labels.iter().all(|l| is_label(l));
+	(valid && tld.len() >= 2 && tld.chars().all(char::is_alphabetic)).then_some(tld)
+}
+
+fn is_label(label: &str) -> bool {
+	!label.is_empty() && !label.starts_with('-') && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+}
+
+fn is_port(port: &str) -> bool {
+	port.parse::<u16>().is_ok()
+}
+
+fn is_ipv4(host: &str) -> bool {
+	host.parse::<std::net::Ipv4Addr>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("https://example.com", "https://example.com")]
+	#[case("ftp://example.com/file", "ftp://example.com/file")]
+	#[case("mailto:someone@example.com", "mailto:someone@example.com")]
+	#[case("github.com/thorio/gravel", "https://github.com/thorio/gravel")]
+	#[case("example.com", "https://example.com")]
+	#[case("www.example.ch", "https://www.example.ch")]
+	#[case("example.ch/", "https://example.ch/")]
+	#[case("example.ch:8080", "https://example.ch:8080")]
+	#[case("docs.rs?search=gravel", "https://docs.rs?search=gravel")]
+	#[case("localhost", "http://localhost")]
+	#[case("localhost:3000/api", "http://localhost:3000/api")]
+	#[case("192.168.0.1:8080", "http://192.168.0.1:8080")]
+	#[case("münchen.de", "https://münchen.de")]
+	fn should_get_url(#[case] query: &str, #[case] expected: &str) {
+		assert_eq!(Some(expected), get_url(query).as_deref(), "{query}");
+	}
+
+	#[rstest]
+	#[case("")]
+	#[case("gravel")]
+	#[case("notes.md")]
+	#[case("report.pdf")]
+	#[case("1.5")]
+	#[case("3.14159")]
+	#[case("192.168.0.1")]
+	#[case("example .com")]
+	#[case("example..com")]
+	#[case("-example.com")]
+	#[case("example.com:http")]
+	#[case("/usr/share")]
+	#[case("~/Documents")]
+	#[case("1 + 1")]
+	fn should_not_get_url(#[case] query: &str) {
+		assert_eq!(None, get_url(query), "{query}");
+	}
+}
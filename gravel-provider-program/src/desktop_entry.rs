@@ -0,0 +1,361 @@
+//! Parses desktop entries according to the
+//! [Desktop Entry Specification](https://specifications.freedesktop.org/desktop-entry-spec/latest/).
+
+use freedesktop_entry_parser::{AttrSelector, Entry};
+use std::env;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+const MAIN_SECTION: &str = "Desktop Entry";
+
+/// The parts of a desktop entry relevant to gravel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopEntry {
+	pub kind: EntryKind,
+	pub name: String,
+	pub icon: Option<String>,
+	/// Working directory to run the program in.
+	pub path: Option<PathBuf>,
+	pub terminal: bool,
+	/// Whether the application should be started via D-Bus instead of `Exec`.
+	pub dbus_activatable: bool,
+	/// Whether the entry should be shown to the user.
+	pub visible: bool,
+	/// Additional ways to launch the application, from `Actions`.
+	pub actions: Vec<DesktopAction>,
+}
+
+/// A `[Desktop Action ...]` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopAction {
+	pub id: String,
+	pub name: String,
+	pub exec: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryKind {
+	Application { exec: Option<String> },
+	Link { url: String },
+}
+
+/// The parts of the environment that influence how entries are interpreted.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+	/// From `$XDG_CURRENT_DESKTOP`.
+	pub desktops: Vec<String>,
+	/// Locale keys to look for, most specific first.
+	pub locales: Vec<String>,
+	/// From `$PATH`.
+	pub path: Vec<PathBuf>,
+}
+
+impl Environment {
+	pub fn from_env() -> Self {
+		let desktops = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+		let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+			.into_iter()
+			.filter_map(|v| env::var(v).ok())
+			.find(|v| !v.is_empty())
+			.unwrap_or_default();
+
+		Self {
+			desktops: split_list(&desktops, ':'),
+			locales: get_locale_keys(&locale),
+			path: env::var_os("PATH").map_or(vec![], |p| env::split_paths(&p).collect()),
+		}
+	}
+}
+
+/// Parses the desktop entry at the given path.
+///
+/// Returns [`None`] if the file can't be read or does not contain an
+/// entry gravel can do anything with, such as `Type=Directory`.
+pub fn parse(path: &Path, environment: &Environment) -> Option<DesktopEntry> {
+	let entry = Entry::parse_file(path)
+		.map_err(|err| log::debug!("unable to parse desktop entry {path:?}: {err}"))
+		.ok()?;
+
+	parse_entry(&entry, environment)
+}
+
+fn parse_entry(entry: &Entry, environment: &Environment) -> Option<DesktopEntry> {
+	let section = entry.section(MAIN_SECTION);
+
+	let kind = match section.attr("Type") {
+		Some("Application") => EntryKind::Application {
+			exec: section.attr("Exec").map(ToOwned::to_owned),
+		},
+		Some("Link") => EntryKind::Link {
+			url: section.attr("URL")?.to_owned(),
+		},
+		_ => return None,
+	};
+
+	let name = get_localized(&section, "Name", &environment.locales)?.to_owned();
+
+	let visible = !get_bool(&section, "Hidden")
+		&& !get_bool(&section, "NoDisplay")
+		&& is_shown_in(&section, &environment.desktops)
+		&& section
+			.attr("TryExec")
+			.into_iter()
+			.all(|e| is_executable(e, &environment.path));
+
+	let actions = match kind {
+		EntryKind::Application { .. } => parse_actions(entry, environment),
+		EntryKind::Link { .. } => vec![],
+	};
+
+	Some(DesktopEntry {
+		kind,
+		name,
+		icon: section.attr("Icon").map(ToOwned::to_owned),
+		path: section.attr("Path").filter(|p| !p.is_empty()).map(PathBuf::from),
+		terminal: get_bool(&section, "Terminal"),
+		dbus_activatable: get_bool(&section, "DBusActivatable"),
+		visible,
+		actions,
+	})
+}
+
+/// Parses the sections of all actions listed in `Actions`.
+/// Actions without a name or `Exec` are skipped.
+fn parse_actions(entry: &Entry, environment: &Environment) -> Vec<DesktopAction> {
+	let actions = entry.section(MAIN_SECTION).attr("Actions").unwrap_or_default();
+
+	split_list(actions, ';')
+		.into_iter()
+		.filter_map(|id| {
+			let section = entry.section(format!("Desktop Action {id}"));
+
+			Some(DesktopAction {
+				name: get_localized(&section, "Name", &environment.locales)?.to_owned(),
+				exec: section.attr("Exec")?.to_owned(),
+				id,
+			})
+		})
+		.collect()
+}
+
+/// Builds the desktop file ID from the entry's path relative to
+/// the `applications` directory it was found in.
+///
+/// `applications/vendor/foo.desktop` becomes `vendor-foo.desktop`
+pub fn get_desktop_file_id(applications_dir: &Path, path: &Path) -> Option<String> {
+	let relative = path.strip_prefix(applications_dir).ok()?;
+
+	let parts = relative
+		.components()
+		.map(|c| c.as_os_str().to_str())
+		.collect::<Option<Vec<_>>>()?;
+
+	Some(parts.join("-"))
+}
+
+/// Returns the value of the key localized according to the locale keys,
+/// falling back to the unlocalized value.
+fn get_localized<'a, T: AsRef<str>>(section: &'a AttrSelector<T>, key: &str, locales: &[String]) -> Option<&'a str> {
+	locales
+		.iter()
+		.find_map(|locale| section.attr_with_param(key, locale))
+		.or_else(|| section.attr(key))
+}
+
+fn get_bool<T: AsRef<str>>(section: &AttrSelector<'_, T>, key: &str) -> bool {
+	matches!(section.attr(key), Some("true"))
+}
+
+fn is_shown_in<T: AsRef<str>>(section: &AttrSelector<'_, T>, desktops: &[String]) -> bool {
+	let is_current = |d: &str| desktops.iter().any(|current| current == d);
+
+	if let Some(only) = section.attr("OnlyShowIn") {
+		if !split_list(only, ';').iter().any(|d| is_current(d)) {
+			return false;
+		}
+	}
+
+	if let Some(not) = section.attr("NotShowIn") {
+		if split_list(not, ';').iter().any(|d| is_current(d)) {
+			return false;
+		}
+	}
+
+	true
+}
+
+/// Checks whether the given program exists and is executable.
+/// Programs without a slash are searched for in the given path.
+fn is_executable(program: &str, path: &[PathBuf]) -> bool {
+	let check = |p: &Path| {
+		p.metadata()
+			.is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+	};
+
+	match program.contains('/') {
+		true => check(Path::new(program)),
+		false => path.iter().any(|dir| check(&dir.join(program))),
+	}
+}
+
+/// Turns a POSIX locale like `de_DE.UTF-8@euro` into the keys the spec
+/// prescribes, in order: `de_DE@euro`, `de_DE`, `de@euro`, `de`.
+fn get_locale_keys(locale: &str) -> Vec<String> {
+	let (locale, modifier) = match locale.split_once('@') {
+		Some((locale, modifier)) => (locale, Some(modifier)),
+		None => (locale, None),
+	};
+
+	let locale = locale.split('.').next().unwrap_or_default();
+
+	if matches!(locale, "" | "C" | "POSIX") {
+		return vec![];
+	}
+
+	let (lang, country) = match locale.split_once('_') {
+		Some((lang, country)) => (lang, Some(country)),
+		None => (locale, None),
+	};
+
+	let mut keys = vec![];
+
+	if let Some(country) = country {
+		if let Some(modifier) = modifier {
+			keys.push(format!("{lang}_{country}@{modifier}"));
+		}
+
+		keys.push(format!("{lang}_{country}"));
+	}
+
+	if let Some(modifier) = modifier {
+		keys.push(format!("{lang}@{modifier}"));
+	}
+
+	keys.push(lang.to_owned());
+	keys
+}
+
+fn split_list(list: &str, separator: char) -> Vec<String> {
+	list.split(separator)
+		.filter(|s| !s.is_empty())
+		.map(ToOwned::to_owned)
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/applications");
+
+	fn environment(desktops: &[&str], locale: &str) -> Environment {
+		Environment {
+			desktops: desktops.iter().map(|d| d.to_string()).collect(),
+			locales: get_locale_keys(locale),
+			path: vec![PathBuf::from("/bin"), PathBuf::from("/usr/bin")],
+		}
+	}
+
+	fn parse_fixture(name: &str, environment: &Environment) -> Option<DesktopEntry> {
+		parse(&Path::new(FIXTURES).join(name), environment)
+	}
+
+	#[rstest]
+	#[case("basic.desktop", true)]
+	#[case("hidden.desktop", false)]
+	#[case("nodisplay.desktop", false)]
+	#[case("onlyshowin.desktop", false)]
+	#[case("notshowin.desktop", true)]
+	#[case("tryexec-missing.desktop", false)]
+	#[case("tryexec-present.desktop", true)]
+	fn should_determine_visibility(#[case] fixture: &str, #[case] expected: bool) {
+		let entry = parse_fixture(fixture, &environment(&["XFCE"], "C")).unwrap();
+		assert_eq!(expected, entry.visible, "{fixture}");
+	}
+
+	#[rstest]
+	#[case(&["GNOME"], true)]
+	#[case(&["ubuntu", "GNOME"], true)]
+	#[case(&["KDE"], false)]
+	#[case(&[], false)]
+	fn should_respect_only_show_in(#[case] desktops: &[&str], #[case] expected: bool) {
+		let entry = parse_fixture("onlyshowin.desktop", &environment(desktops, "C")).unwrap();
+		assert_eq!(expected, entry.visible, "{desktops:?}");
+	}
+
+	#[rstest]
+	#[case("C", "Localized")]
+	#[case("de_DE.UTF-8", "Lokalisiert (DE)")]
+	#[case("de_AT.UTF-8", "Lokalisiert")]
+	#[case("de_DE@euro", "Lokalisiert (Euro)")]
+	#[case("fr_FR.UTF-8", "Localized")]
+	fn should_localize_name(#[case] locale: &str, #[case] expected: &str) {
+		let entry = parse_fixture("localized.desktop", &environment(&[], locale)).unwrap();
+		assert_eq!(expected, entry.name, "{locale}");
+	}
+
+	#[rstest]
+	#[case("de_DE.UTF-8@euro", &["de_DE@euro", "de_DE", "de@euro", "de"])]
+	#[case("en_US.UTF-8", &["en_US", "en"])]
+	#[case("sr@latin", &["sr@latin", "sr"])]
+	#[case("C.UTF-8", &[])]
+	#[case("", &[])]
+	fn should_get_locale_keys(#[case] locale: &str, #[case] expected: &[&str]) {
+		assert_eq!(expected, get_locale_keys(locale));
+	}
+
+	#[test]
+	fn should_parse_link() {
+		let entry = parse_fixture("link.desktop", &environment(&[], "C")).unwrap();
+
+		let url = String::from("https://github.com/thorio/gravel");
+		assert_eq!(EntryKind::Link { url }, entry.kind);
+	}
+
+	#[test]
+	fn should_parse_launch_options() {
+		let entry = parse_fixture("terminal.desktop", &environment(&[], "C")).unwrap();
+
+		assert!(entry.terminal);
+		assert!(entry.dbus_activatable);
+		assert_eq!(Some(PathBuf::from("/tmp")), entry.path);
+		assert_eq!(Some("utilities-terminal"), entry.icon.as_deref());
+	}
+
+	#[test]
+	fn should_parse_actions() {
+		let entry = parse_fixture("actions.desktop", &environment(&[], "de_DE.UTF-8")).unwrap();
+
+		let expected = vec![
+			DesktopAction {
+				id: String::from("new-window"),
+				name: String::from("Neues Fenster"),
+				exec: String::from("browser --new-window %u"),
+			},
+			DesktopAction {
+				id: String::from("new-private-window"),
+				name: String::from("New Private Window"),
+				exec: String::from("browser --private-window %u"),
+			},
+		];
+
+		assert_eq!(expected, entry.actions);
+	}
+
+	#[rstest]
+	#[case("directory.desktop")]
+	#[case("invalid.desktop")]
+	fn should_skip(#[case] fixture: &str) {
+		assert_eq!(None, parse_fixture(fixture, &environment(&[], "C")));
+	}
+
+	#[rstest]
+	#[case("basic.desktop", "basic.desktop")]
+	#[case("vendor/foo.desktop", "vendor-foo.desktop")]
+	fn should_get_desktop_file_id(#[case] path: &str, #[case] expected: &str) {
+		let root = Path::new(FIXTURES);
+		let actual = get_desktop_file_id(root, &root.join(path));
+		assert_eq!(Some(expected), actual.as_deref());
+	}
+}
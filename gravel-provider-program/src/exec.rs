@@ -0,0 +1,192 @@
+//! Interprets the `Exec` key of desktop entries, see
+//! [the specification](https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html).
+
+/// Values of the desktop entry that field codes can refer to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldValues<'a> {
+	/// `Icon`, for `%i`.
+	pub icon: Option<&'a str>,
+	/// The translated `Name`, for `%c`.
+	pub name: &'a str,
+	/// Path of the desktop file, for `%k`.
+	pub location: &'a str,
+}
+
+/// Splits the `Exec` value into program and arguments, following the
+/// quoting rules of the spec, and expands its field codes.
+///
+/// Returns [`None`] if the value is malformed or empty.
+pub fn parse_exec(exec: &str, values: &FieldValues) -> Option<Vec<String>> {
+	let args = split_args(&unescape_string(exec))?
+		.into_iter()
+		.flat_map(|arg| expand_field_codes(&arg, values))
+		.collect::<Vec<_>>();
+
+	match args.is_empty() {
+		true => None,
+		false => Some(args),
+	}
+}
+
+/// Resolves the escape sequences allowed in all string values.
+fn unescape_string(value: &str) -> String {
+	let mut result = String::with_capacity(value.len());
+	let mut chars = value.chars();
+
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			result.push(c);
+			continue;
+		}
+
+		match chars.next() {
+			Some('s') => result.push(' '),
+			Some('n') => result.push('\n'),
+			Some('t') => result.push('\t'),
+			Some('r') => result.push('\r'),
+			Some('\\') => result.push('\\'),
+			Some(other) => {
+				result.push('\\');
+				result.push(other);
+			}
+			None => result.push('\\'),
+		}
+	}
+
+	result
+}
+
+/// Splits the command line at unquoted spaces. Within double quotes,
+/// a backslash escapes the next character.
+///
+/// Returns [`None`] if a quote is not closed.
+fn split_args(value: &str) -> Option<Vec<String>> {
+	let mut args = vec![];
+	let mut current = String::new();
+	let mut in_arg = false;
+	let mut chars = value.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			' ' => {
+				if in_arg {
+					args.push(std::mem::take(&mut current));
+					in_arg = false;
+				}
+			}
+			'"' => {
+				in_arg = true;
+				loop {
+					match chars.next()? {
+						'"' => break,
+						'\\' => current.push(chars.next()?),
+						other => current.push(other),
+					}
+				}
+			}
+			other => {
+				in_arg = true;
+				current.push(other);
+			}
+		}
+	}
+
+	if in_arg {
+		args.push(current);
+	}
+
+	Some(args)
+}
+
+/// Expands the field codes in the argument. File and URL codes are removed,
+/// since gravel never passes any. Arguments that consisted only of such
+/// a code are removed entirely.
+///
+/// `%i` expands to two arguments, `--icon` and the icon, but only if
+/// it stands alone.
+fn expand_field_codes(arg: &str, values: &FieldValues) -> Vec<String> {
+	if arg == "%i" {
+		return match values.icon {
+			Some(icon) if !icon.is_empty() => vec![String::from("--icon"), icon.to_owned()],
+			_ => vec![],
+		};
+	}
+
+	if !arg.contains('%') {
+		return vec![arg.to_owned()];
+	}
+
+	let mut result = String::with_capacity(arg.len());
+	let mut chars = arg.chars();
+
+	while let Some(c) = chars.next() {
+		if c != '%' {
+			result.push(c);
+			continue;
+		}
+
+		match chars.next() {
+			Some('%') => result.push('%'),
+			Some('c') => result.push_str(values.name),
+			Some('k') => result.push_str(values.location),
+			_ => (),
+		}
+	}
+
+	match result.is_empty() {
+		true => vec![],
+		false => vec![result],
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("firefox", &["firefox"])]
+	#[case("firefox %u", &["firefox"])]
+	#[case("firefox --private-window %U", &["firefox", "--private-window"])]
+	#[case("env FOO=bar  app", &["env", "FOO=bar", "app"])]
+	#[case(r#""/opt/My App/app" --flag"#, &["/opt/My App/app", "--flag"])]
+	#[case(r#"sh -c "echo \"hi\" \\$HOME""#, &["sh", "-c", r#"echo "hi" $HOME"#])]
+	#[case(r#"sh -c "echo \\\\""#, &["sh", "-c", r"echo \"])]
+	#[case("printf 100%%", &["printf", "100%"])]
+	#[case("app x%%y", &["app", "x%y"])]
+	#[case("app --file=%f", &["app", "--file="])]
+	#[case("app x%u", &["app", "x"])]
+	#[case(r#""/opt/my\sapp" --arg"#, &["/opt/my app", "--arg"])]
+	#[case("app %i", &["app", "--icon", "app-icon"])]
+	#[case("app --class=%c", &["app", "--class=App Name"])]
+	#[case("app --name=%c", &["app", "--name=App Name"])]
+	#[case("app %c", &["app", "App Name"])]
+	#[case("app %k", &["app", "/usr/share/applications/app.desktop"])]
+	#[case("app -f%k", &["app", "-f/usr/share/applications/app.desktop"])]
+	#[case("app --desktop-file=%k", &["app", "--desktop-file=/usr/share/applications/app.desktop"])]
+	#[case("app %d %D %n %N %v %m", &["app"])]
+	fn should_parse(#[case] exec: &str, #[case] expected: &[&str]) {
+		let values = FieldValues {
+			icon: Some("app-icon"),
+			name: "App Name",
+			location: "/usr/share/applications/app.desktop",
+		};
+
+		let expected = expected.iter().map(ToString::to_string).collect();
+		assert_eq!(Some(expected), parse_exec(exec, &values), "{exec}");
+	}
+
+	#[test]
+	fn should_omit_missing_icon() {
+		let values = FieldValues::default();
+		assert_eq!(Some(vec![String::from("app")]), parse_exec("app %i", &values));
+	}
+
+	#[rstest]
+	#[case("")]
+	#[case("%F")]
+	#[case(r#"app "unclosed"#)]
+	fn should_fail(#[case] exec: &str) {
+		assert_eq!(None, parse_exec(exec, &FieldValues::default()));
+	}
+}
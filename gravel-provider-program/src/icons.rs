@@ -0,0 +1,267 @@
+//! Resolves icon names to files according to the
+//! [Icon Theme Specification](https://specifications.freedesktop.org/icon-theme-spec/latest/).
+
+use freedesktop_entry_parser::{AttrSelector, Entry};
+use std::path::{Path, PathBuf};
+
+const FALLBACK_THEME: &str = "hicolor";
+const EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
+
+/// Looks up icons in a theme, its parents and finally the fallback theme.
+#[derive(Debug)]
+pub struct IconLookup {
+	themes: Vec<Theme>,
+	fallback_dirs: Vec<PathBuf>,
+	size: u32,
+}
+
+#[derive(Debug)]
+struct Theme {
+	dirs: Vec<ThemeDir>,
+}
+
+/// A subdirectory of a theme, such as `48x48/apps`,
+/// which may exist in several base directories.
+#[derive(Debug)]
+struct ThemeDir {
+	paths: Vec<PathBuf>,
+	size: u32,
+	min_size: u32,
+	max_size: u32,
+	threshold: u32,
+	kind: DirKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirKind {
+	Fixed,
+	Scalable,
+	Threshold,
+}
+
+impl IconLookup {
+	/// Loads the theme and all of its parents from the base directories,
+	/// for example `~/.local/share/icons`.
+	///
+	/// Icons that aren't part of any theme are looked up directly
+	/// in `fallback_dirs`, for example `/usr/share/pixmaps`.
+	pub fn new(base_dirs: &[PathBuf], fallback_dirs: Vec<PathBuf>, theme: &str, size: u32) -> Self {
+		let mut names = vec![];
+		collect_theme_names(base_dirs, theme, &mut names);
+		names.push(FALLBACK_THEME.to_owned());
+
+		let themes = names.iter().filter_map(|name| load_theme(base_dirs, name)).collect();
+
+		Self {
+			themes,
+			fallback_dirs,
+			size,
+		}
+	}
+
+	/// Returns the file best matching the icon name and configured size.
+	/// Absolute paths are returned as-is, if they exist.
+	pub fn lookup(&self, icon: &str) -> Option<PathBuf> {
+		if icon.is_empty() {
+			return None;
+		}
+
+		if Path::new(icon).is_absolute() {
+			return Some(PathBuf::from(icon)).filter(|p| p.is_file());
+		}
+
+		self.themes
+			.iter()
+			.find_map(|theme| theme.lookup(icon, self.size))
+			.or_else(|| find_file(self.fallback_dirs.iter(), icon))
+	}
+}
+
+impl Theme {
+	/// Prefers directories matching the size exactly, then the closest one.
+	fn lookup(&self, icon: &str, size: u32) -> Option<PathBuf> {
+		let exact = self
+			.dirs
+			.iter()
+			.filter(|d| d.matches_size(size))
+			.find_map(|d| find_file(d.paths.iter(), icon));
+
+		if exact.is_some() {
+			return exact;
+		}
+
+		self.dirs
+			.iter()
+			.filter_map(|d| Some((d.size_distance(size), find_file(d.paths.iter(), icon)?)))
+			.min_by_key(|(distance, _)| *distance)
+			.map(|(_, path)| path)
+	}
+}
+
+impl ThemeDir {
+	fn matches_size(&self, size: u32) -> bool {
+		match self.kind {
+			DirKind::Fixed => self.size == size,
+			DirKind::Scalable => (self.min_size..=self.max_size).contains(&size),
+			DirKind::Threshold => {
+				(self.size.saturating_sub(self.threshold)..=self.size + self.threshold).contains(&size)
+			}
+		}
+	}
+
+	fn size_distance(&self, size: u32) -> u32 {
+		let (min, max) = match self.kind {
+			DirKind::Fixed => (self.size, self.size),
+			DirKind::Scalable => (self.min_size, self.max_size),
+			DirKind::Threshold => (self.size.saturating_sub(self.threshold), self.size + self.threshold),
+		};
+
+		min.saturating_sub(size) + size.saturating_sub(max)
+	}
+}
+
+/// Collects the theme and its parents, depth first, skipping the
+/// fallback theme, which is always searched last.
+fn collect_theme_names(base_dirs: &[PathBuf], name: &str, names: &mut Vec<String>) {
+	if name == FALLBACK_THEME || names.iter().any(|n| n == name) {
+		return;
+	}
+
+	names.push(name.to_owned());
+
+	let Some(index) = read_index(base_dirs, name) else {
+		return;
+	};
+
+	let inherits = index
+		.section("Icon Theme")
+		.attr("Inherits")
+		.map(split_list)
+		.unwrap_or_default();
+
+	for parent in inherits {
+		collect_theme_names(base_dirs, &parent, names);
+	}
+}
+
+fn load_theme(base_dirs: &[PathBuf], name: &str) -> Option<Theme> {
+	let index = read_index(base_dirs, name)?;
+	let directories = index.section("Icon Theme").attr("Directories").map(split_list)?;
+
+	let dirs = directories
+		.iter()
+		.filter_map(|dir| parse_dir(&index.section(dir), base_dirs, name, dir))
+		.collect();
+
+	Some(Theme { dirs })
+}
+
+/// Reads the `index.theme` of the first base directory that contains the theme.
+fn read_index(base_dirs: &[PathBuf], name: &str) -> Option<Entry> {
+	base_dirs
+		.iter()
+		.map(|base| base.join(name).join("index.theme"))
+		.find(|path| path.is_file())
+		.and_then(|path| {
+			Entry::parse_file(&path)
+				.map_err(|err| log::debug!("unable to parse icon theme {path:?}: {err}"))
+				.ok()
+		})
+}
+
+/// Parses the directory's section, only keeping the paths that exist.
+/// Directories for scaled icons are ignored.
+fn parse_dir<T: AsRef<str>>(
+	section: &AttrSelector<T>,
+	base_dirs: &[PathBuf],
+	theme: &str,
+	dir: &str,
+) -> Option<ThemeDir> {
+	let get_number = |key: &str| section.attr(key).and_then(|v| v.trim().parse::<u32>().ok());
+
+	if get_number("Scale").unwrap_or(1) != 1 {
+		return None;
+	}
+
+	let size = get_number("Size")?;
+
+	let kind = match section.attr("Type").map(str::trim) {
+		Some("Fixed") => DirKind::Fixed,
+		Some("Scalable") => DirKind::Scalable,
+		_ => DirKind::Threshold,
+	};
+
+	let paths = base_dirs
+		.iter()
+		.map(|base| base.join(theme).join(dir))
+		.filter(|path| path.is_dir())
+		.collect::<Vec<_>>();
+
+	if paths.is_empty() {
+		return None;
+	}
+
+	Some(ThemeDir {
+		paths,
+		size,
+		min_size: get_number("MinSize").unwrap_or(size),
+		max_size: get_number("MaxSize").unwrap_or(size),
+		threshold: get_number("Threshold").unwrap_or(2),
+		kind,
+	})
+}
+
+fn find_file<'a>(dirs: impl Iterator<Item = &'a PathBuf>, icon: &str) -> Option<PathBuf> {
+	dirs.flat_map(|dir| EXTENSIONS.iter().map(move |ext| dir.join(format!("{icon}.{ext}"))))
+		.find(|path| path.is_file())
+}
+
+fn split_list(value: &str) -> Vec<String> {
+	value
+		.split(',')
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.map(str::to_owned)
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	fn fixtures() -> PathBuf {
+		PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))
+	}
+
+	fn get_lookup(theme: &str, size: u32) -> IconLookup {
+		let base_dirs = vec![fixtures().join("icons")];
+		IconLookup::new(&base_dirs, vec![fixtures().join("pixmaps")], theme, size)
+	}
+
+	#[rstest]
+	#[case("test", 64, "themed", Some("icons/test/64x64/apps/themed.png"))]
+	#[case("test", 32, "themed", Some("icons/test/32x32/apps/themed.png"))]
+	#[case("test", 48, "themed", Some("icons/test/64x64/apps/themed.png"))]
+	#[case("test", 40, "themed", Some("icons/test/32x32/apps/themed.png"))]
+	#[case("test", 48, "basic", Some("icons/hicolor/48x48/apps/basic.png"))]
+	#[case("test", 48, "vector", Some("icons/hicolor/scalable/apps/vector.svg"))]
+	#[case("test", 48, "legacy", Some("pixmaps/legacy.xpm"))]
+	#[case("missing-theme", 48, "basic", Some("icons/hicolor/48x48/apps/basic.png"))]
+	#[case("missing-theme", 48, "themed", None)]
+	#[case("test", 48, "missing", None)]
+	#[case("test", 48, "", None)]
+	fn should_lookup(#[case] theme: &str, #[case] size: u32, #[case] icon: &str, #[case] expected: Option<&str>) {
+		let expected = expected.map(|p| fixtures().join(p));
+		assert_eq!(expected, get_lookup(theme, size).lookup(icon), "{icon}");
+	}
+
+	#[test]
+	fn should_lookup_absolute_path() {
+		let path = fixtures().join("pixmaps/legacy.xpm");
+		let lookup = get_lookup("test", 48);
+
+		assert_eq!(Some(path.clone()), lookup.lookup(&path.to_string_lossy()));
+		assert_eq!(None, lookup.lookup("/does/not/exist.png"));
+	}
+}
@@ -0,0 +1,188 @@
+use crate::{expand_glob, get_glob_root, implementation, Config, Program};
+use glob::Pattern;
+use gravel_core::Hit;
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+pub(crate) type SharedIndex = Arc<RwLock<ProgramIndex>>;
+
+/// In-memory index of all programs found in the configured paths.
+///
+/// Each path glob has its own set of entries, so that single files can
+/// be updated without rescanning everything. When two globs yield a
+/// program with the same ID, the earlier glob wins.
+pub(crate) struct ProgramIndex {
+	config: Arc<Config>,
+	paths: Vec<String>,
+	roots: Vec<PathBuf>,
+	patterns: Vec<Option<Pattern>>,
+	entries: Vec<BTreeMap<String, Program>>,
+	hits: Vec<Arc<dyn Hit>>,
+}
+
+impl ProgramIndex {
+	/// Creates a new index and immediately populates it.
+	pub fn new(paths: Vec<String>, config: Arc<Config>) -> Self {
+		let patterns = paths.iter().map(|p| Pattern::new(p).ok()).collect();
+		let roots = paths.iter().map(|p| get_glob_root(p)).collect();
+
+		let mut index = Self {
+			entries: vec![BTreeMap::new(); paths.len()],
+			hits: vec![],
+			config,
+			roots,
+			patterns,
+			paths,
+		};
+
+		index.refresh();
+		index
+	}
+
+	pub fn shared(self) -> SharedIndex {
+		Arc::new(RwLock::new(self))
+	}
+
+	/// The directories containing the configured path globs,
+	/// in order of precedence.
+	pub fn roots(&self) -> &[PathBuf] {
+		&self.roots
+	}
+
+	pub fn hits(&self) -> Vec<Arc<dyn Hit>> {
+		self.hits.clone()
+	}
+
+	/// Discards the index and rescans all paths.
+	pub fn refresh(&mut self) {
+		for ((entries, path), root) in self.entries.iter_mut().zip(&self.paths).zip(&self.roots) {
+			*entries = expand_glob(path)
+				.into_iter()
+				.flatten()
+				.filter_map(Result::ok)
+				.filter_map(|p| read_program(&self.config, root, &p))
+				.collect();
+		}
+
+		self.rebuild_hits();
+		log::debug!("indexed {} programs", self.hits.len());
+	}
+
+	/// Re-reads a single file, adding, replacing or removing its entry.
+	pub fn update(&mut self, path: &Path) {
+		let iter = self.entries.iter_mut().zip(&self.patterns).zip(&self.roots);
+
+		for ((entries, pattern), root) in iter {
+			if !matches!(pattern, Some(p) if p.matches_path(path)) {
+				continue;
+			}
+
+			let Some(id) = implementation::get_program_id(root, path) else {
+				continue;
+			};
+
+			match implementation::get_program(&self.config, &id, path) {
+				Some(program) => entries.insert(id, program),
+				None => entries.remove(&id),
+			};
+		}
+
+		self.rebuild_hits();
+		log::trace!("updated program index entry for {path:?}");
+	}
+
+	fn rebuild_hits(&mut self) {
+		self.hits = self
+			.entries
+			.iter()
+			.flatten()
+			.unique_by(|(id, _)| *id)
+			.flat_map(|(_, program)| match program {
+				Program::Visible(hits) => hits.clone(),
+				Program::Hidden => vec![],
+			})
+			.collect();
+	}
+}
+
+fn read_program(config: &Config, root: &Path, path: &Path) -> Option<(String, Program)> {
+	let id = implementation::get_program_id(root, path)?;
+	let program = implementation::get_program(config, &id, path)?;
+
+	Some((id, program))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+pub(crate) mod tests {
+	use super::*;
+	use crate::{LinuxConfig, RefreshConfig};
+	use std::fs;
+	use tempfile::TempDir;
+
+	pub const ENTRY: &str = "[Desktop Entry]\nType=Application\nName=Test\nExec=true\n";
+
+	/// Creates an index of `share/applications` in a new, otherwise empty
+	/// temporary directory, which is returned alongside it.
+	pub fn get_index() -> (ProgramIndex, TempDir) {
+		let dir = tempfile::tempdir().unwrap();
+
+		let path = dir.path().join("share/applications/**/*.desktop");
+		let paths = vec![path.to_string_lossy().into_owned()];
+		let config = Config {
+			refresh: RefreshConfig {
+				title: String::new(),
+				subtitle: String::new(),
+			},
+			linux: LinuxConfig {
+				terminal: vec![],
+				systemd_scope: false,
+				icon_theme: None,
+				icon_size: 48,
+			},
+		};
+
+		(ProgramIndex::new(paths, Arc::new(config)), dir)
+	}
+
+	pub fn get_titles(index: &ProgramIndex) -> Vec<String> {
+		index.hits().iter().map(|h| h.get_title().to_owned()).collect()
+	}
+
+	#[test]
+	fn should_update_entries() {
+		let (mut index, root) = get_index();
+		let dir = root.path().join("share/applications/sub");
+		let path = dir.join("test.desktop");
+		fs::create_dir_all(&dir).unwrap();
+
+		fs::write(&path, ENTRY).unwrap();
+		index.update(&path);
+		let added = get_titles(&index);
+
+		fs::write(&path, ENTRY.replace("Name=Test", "Name=Renamed")).unwrap();
+		index.update(&path);
+		let replaced = get_titles(&index);
+
+		fs::remove_file(&path).unwrap();
+		index.update(&path);
+		let removed = get_titles(&index);
+
+		assert_eq!(vec!["Test"], added);
+		assert_eq!(vec!["Renamed"], replaced);
+		assert!(removed.is_empty());
+	}
+
+	#[test]
+	fn should_ignore_unmatched_paths() {
+		let (mut index, root) = get_index();
+		let path = root.path().join("share/test.desktop");
+		fs::create_dir_all(root.path().join("share")).unwrap();
+		fs::write(&path, ENTRY).unwrap();
+
+		index.update(&path);
+
+		assert!(get_titles(&index).is_empty());
+	}
+}
@@ -0,0 +1,135 @@
+//! Launches desktop entries natively, without relying on gtk-launch.
+
+use crate::desktop_entry::{DesktopAction, DesktopEntry, EntryKind};
+use crate::exec::{parse_exec, FieldValues};
+use anyhow::{anyhow, Context, Result};
+use gravel_core::process;
+use std::collections::HashMap;
+use std::process::Command;
+use zbus::blocking::Connection;
+use zbus::zvariant::Value;
+
+const APPLICATION_INTERFACE: &str = "org.freedesktop.Application";
+
+/// A desktop entry along with everything needed to launch it.
+#[derive(Debug)]
+pub struct Launchable {
+	pub entry: DesktopEntry,
+	pub id: String,
+	pub location: String,
+	/// Command prefix used for `Terminal=true` entries.
+	pub terminal: Vec<String>,
+	/// Whether to start the program in its own systemd scope.
+	pub systemd_scope: bool,
+}
+
+impl Launchable {
+	/// Launches the entry, using D-Bus activation if the entry supports it.
+	pub fn launch(&self) -> Result<()> {
+		if self.entry.dbus_activatable {
+			match activate(&self.id, None) {
+				Ok(()) => return Ok(()),
+				Err(err) => log::debug!("D-Bus activation of {} failed, falling back to Exec: {err}", self.id),
+			}
+		}
+
+		match &self.entry.kind {
+			EntryKind::Application { exec: Some(exec) } => self.run_exec(exec),
+			EntryKind::Application { exec: None } => Err(anyhow!("{} has no Exec key", self.id)),
+			EntryKind::Link { url } => self.spawn(Command::new("xdg-open").arg(url)),
+		}
+	}
+
+	/// Launches one of the entry's actions.
+	pub fn launch_action(&self, action: &DesktopAction) -> Result<()> {
+		if self.entry.dbus_activatable {
+			match activate(&self.id, Some(&action.id)) {
+				Ok(()) => return Ok(()),
+				Err(err) => log::debug!("D-Bus activation of {} failed, falling back to Exec: {err}", self.id),
+			}
+		}
+
+		self.run_exec(&action.exec)
+	}
+
+	fn run_exec(&self, exec: &str) -> Result<()> {
+		let values = FieldValues {
+			icon: self.entry.icon.as_deref(),
+			name: &self.entry.name,
+			location: &self.location,
+		};
+
+		let mut args = parse_exec(exec, &values).ok_or_else(|| anyhow!("invalid Exec '{exec}'"))?;
+
+		if self.entry.terminal {
+			args.splice(0..0, self.terminal.iter().cloned());
+		}
+
+		let (program, args) = args.split_first().ok_or_else(|| anyhow!("no terminal configured"))?;
+
+		let mut command = Command::new(program);
+		command.args(args);
+
+		if let Some(path) = &self.entry.path {
+			command.current_dir(path);
+		}
+
+		log::debug!("running {command:?}");
+		self.spawn(&mut command)
+			.with_context(|| format!("unable to start '{program}'"))
+	}
+
+	fn spawn(&self, command: &mut Command) -> Result<()> {
+		let app_id = self.id.strip_suffix(".desktop").unwrap_or(&self.id);
+		process::launch(command, app_id, self.systemd_scope)?;
+
+		Ok(())
+	}
+}
+
+/// Activates the application, or one of its actions, through the
+/// `org.freedesktop.Application` interface on the session bus.
+fn activate(id: &str, action: Option<&str>) -> Result<()> {
+	let bus_name = id.strip_suffix(".desktop").unwrap_or(id);
+	let object_path = get_object_path(bus_name);
+	let platform_data = HashMap::<&str, Value>::new();
+
+	let connection = Connection::session()?;
+
+	match action {
+		None => connection.call_method(
+			Some(bus_name),
+			object_path,
+			Some(APPLICATION_INTERFACE),
+			"Activate",
+			&(platform_data,),
+		)?,
+		Some(action) => connection.call_method(
+			Some(bus_name),
+			object_path,
+			Some(APPLICATION_INTERFACE),
+			"ActivateAction",
+			&(action, Vec::<Value>::new(), platform_data),
+		)?,
+	};
+
+	Ok(())
+}
+
+/// `org.example.App-Name` becomes `/org/example/App_Name`
+fn get_object_path(bus_name: &str) -> String {
+	format!("/{}", bus_name.replace('.', "/").replace('-', "_"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("org.gnome.Nautilus", "/org/gnome/Nautilus")]
+	#[case("org.example.App-Name", "/org/example/App_Name")]
+	fn should_get_object_path(#[case] bus_name: &str, #[case] expected: &str) {
+		assert_eq!(expected, get_object_path(bus_name));
+	}
+}
@@ -3,8 +3,22 @@
 //!
 //! ### Linux
 //! Searches for .desktop files in `$XDG_DATA_DIRS` and `$XDG_DATA_HOME`
+//! and interprets them according to the Desktop Entry Specification,
+//! honouring keys such as `Hidden`, `OnlyShowIn` and `TryExec`.
 //!
-//! Launches applications using gtk-launch.
+//! The application directories are watched using inotify, so newly installed
+//! or removed programs show up without restarting gravel.
+//!
+//! Desktop actions, such as a browser's "New Private Window", are shown
+//! as separate hits.
+//!
+//! Icons are resolved according to the Icon Theme Specification, using
+//! the configured theme or, by default, the one GTK is configured to use.
+//!
+//! Launches applications by expanding their `Exec` key or, if they are
+//! `DBusActivatable`, over D-Bus. `Terminal=true` applications are run in
+//! the configured terminal emulator. Links are opened using xdg-open.
+//! Optionally, each application is started in its own systemd user scope.
 //!
 //! ### Windows
 //! Searches for .lnk files in
@@ -12,17 +26,30 @@
 //! - `%APPDATA%\Microsoft\Windows\Start Menu\Programs`
 //!
 //! Launches applications using explorer.
-
-use std::{path::PathBuf, sync::Arc};
+//!
+//! Programs are indexed once on startup. The index can be rebuilt manually
+//! by selecting the refresh hit.
 
 use glob::{glob, Paths};
 use gravel_core::{config::*, plugin::*, *};
-use itertools::Itertools;
+use index::{ProgramIndex, SharedIndex};
 use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[cfg_attr(target_os = "linux", path = "linux.rs")]
 #[cfg_attr(windows, path = "windows.rs")]
 mod implementation;
+mod index;
+
+#[cfg(target_os = "linux")]
+mod desktop_entry;
+#[cfg(target_os = "linux")]
+mod exec;
+#[cfg(target_os = "linux")]
+mod icons;
+#[cfg(target_os = "linux")]
+mod launch;
 
 const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
 
@@ -38,31 +65,39 @@ fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
 	let program_paths = implementation::get_program_paths(&config);
 	log::debug!("determined program paths: {program_paths:?}");
 
-	Box::new(ProgramProvider { program_paths })
+	let refresh_config = config.refresh.clone();
+
+	let index = ProgramIndex::new(program_paths, Arc::new(config)).shared();
+	implementation::watch(index.clone());
+
+	let refresh_hit = get_refresh_hit(refresh_config, index.clone());
+
+	Box::new(ProgramProvider { index, refresh_hit })
 }
 
 struct ProgramProvider {
-	program_paths: Vec<String>,
+	index: SharedIndex,
+	refresh_hit: Arc<dyn Hit>,
 }
 
 impl Provider for ProgramProvider {
 	fn query(&self, _query: &str) -> ProviderResult {
-		let hits = get_programs(&self.program_paths);
+		let mut hits = self.index.read().expect("thread holding the lock can't panic").hits();
+		hits.push(self.refresh_hit.clone());
 
 		ProviderResult::new(hits)
 	}
 }
 
-/// Expands the path globs and returns hit representations of all programs it finds
-pub(crate) fn get_programs(paths: &[String]) -> Vec<Arc<dyn Hit>> {
-	paths
-		.iter()
-		.filter_map(expand_glob)
-		.flatten()
-		.filter_map(Result::ok)
-		.unique_by(|p| p.file_name().map(ToOwned::to_owned))
-		.filter_map(get_hit)
-		.collect()
+fn get_refresh_hit(config: RefreshConfig, index: SharedIndex) -> Arc<dyn Hit> {
+	let hit = SimpleHit::new(config.title, config.subtitle, move |_, sender| {
+		log::debug!("refreshing program index");
+		index.write().expect("thread holding the lock can't panic").refresh();
+
+		sender.send(FrontendMessage::Refresh).ok();
+	});
+
+	Arc::new(hit)
 }
 
 pub fn expand_glob(pattern: &String) -> Option<Paths> {
@@ -71,16 +106,47 @@ pub fn expand_glob(pattern: &String) -> Option<Paths> {
 		.ok()
 }
 
-fn get_hit(path: PathBuf) -> Option<Arc<dyn Hit>> {
-	Some(Arc::new(implementation::get_program(&path)?))
+/// Returns the leading part of the glob that doesn't contain any wildcards.
+pub(crate) fn get_glob_root(pattern: &str) -> PathBuf {
+	Path::new(pattern)
+		.components()
+		.take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+		.collect()
+}
+
+/// A program read from a single file.
+#[derive(Clone)]
+pub(crate) enum Program {
+	/// The program's hits, usually just one.
+	Visible(Vec<Arc<dyn Hit>>),
+	/// Not shown, but still hides programs with the same ID in later paths.
+	Hidden,
 }
 
 #[derive(Deserialize, Debug)]
 struct Config {
+	pub refresh: RefreshConfig,
+	#[cfg(target_os = "linux")]
+	pub linux: LinuxConfig,
 	#[cfg(windows)]
 	pub windows: WindowsConfig,
 }
 
+#[derive(Clone, Deserialize, Debug)]
+struct RefreshConfig {
+	pub title: String,
+	pub subtitle: String,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Deserialize, Debug)]
+struct LinuxConfig {
+	pub terminal: Vec<String>,
+	pub systemd_scope: bool,
+	pub icon_theme: Option<String>,
+	pub icon_size: u32,
+}
+
 #[cfg(windows)]
 #[derive(Deserialize, Debug)]
 struct WindowsConfig {
@@ -1,52 +1,320 @@
-use crate::Config;
-use gravel_core::paths::{get_xdg_data_dirs, get_xdg_data_home};
+use crate::desktop_entry::{self, DesktopAction, Environment};
+use crate::icons::IconLookup;
+use crate::launch::Launchable;
+use crate::{index::SharedIndex, Config, Program};
+use freedesktop_entry_parser::Entry;
+use gravel_core::paths::{get_home, get_xdg_config_home, get_xdg_data_dirs, get_xdg_data_home};
 use gravel_core::*;
+use lazy_static::lazy_static;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
+use std::collections::HashMap;
+use std::fs;
 use std::iter::once;
-use std::path::Path;
-use std::process::{Command, Stdio};
-use std::sync::mpsc::Sender;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+
+lazy_static! {
+	static ref ENVIRONMENT: Environment = Environment::from_env();
+}
+
+static ICONS: OnceLock<IconLookup> = OnceLock::new();
 
 pub(crate) fn get_program_paths(_config: &Config) -> Vec<String> {
 	once(get_xdg_data_home())
 		.chain(get_xdg_data_dirs())
 		.map(|mut p| {
-			p.push("applications/*.desktop");
+			p.push("applications/**/*.desktop");
 			p.to_string_lossy().into_owned()
 		})
 		.collect()
 }
 
-/// Parses a desktop entry and returns a [`SimpleHit`] that represents it.
-pub fn get_program(path: &Path) -> Option<SimpleHit> {
-	let filename = path.file_name()?.to_str()?;
+/// Watches the program directories and their subdirectories on a new thread
+/// and updates the index whenever a desktop entry changes.
+///
+/// For directories that don't exist yet, the nearest existing parent is
+/// watched instead, until the directory is created.
+pub(crate) fn watch(index: SharedIndex) {
+	let inotify = match Inotify::init(InitFlags::IN_CLOEXEC) {
+		Ok(inotify) => inotify,
+		Err(err) => {
+			log::error!("unable to initialize inotify, programs will not be updated automatically: {err}");
+			return;
+		}
+	};
 
-	let entry = freedesktop_entry_parser::parse_entry(path).ok()?;
-	let section = entry.section("Desktop Entry");
+	let roots = index
+		.read()
+		.expect("thread holding the lock can't panic")
+		.roots()
+		.to_vec();
+	let mut watches = HashMap::new();
 
-	if let Some("true") = section.attr("NoDisplay") {
-		return None;
+	for root in &roots {
+		watch_root(&inotify, &mut watches, root);
 	}
 
-	let name = section.attr("Name").unwrap_or(filename);
+	thread::spawn(move || watch_loop(&inotify, watches, &roots, &index));
+}
+
+/// Watches the root recursively if it exists, otherwise only its nearest
+/// existing parent. Returns whether the root itself is watched.
+fn watch_root(inotify: &Inotify, watches: &mut HashMap<WatchDescriptor, PathBuf>, root: &Path) -> bool {
+	if root.is_dir() {
+		add_watches(inotify, watches, root.to_owned());
+		return true;
+	}
+
+	let Some(parent) = root.ancestors().skip(1).find(|p| p.is_dir()) else {
+		return false;
+	};
+
+	match inotify.add_watch(parent, get_watch_flags()) {
+		Ok(descriptor) => {
+			log::trace!("watching {parent:?} until {root:?} is created");
+			watches.insert(descriptor, parent.to_owned());
+		}
+		Err(err) => log::debug!("not watching {parent:?}: {err}"),
+	}
 
-	let filename = filename.to_owned();
-	let hit = SimpleHit::new(name, path.to_string_lossy(), move |h, s| run_program(&filename, h, s));
+	false
+}
+
+/// Watches the directory and all of its subdirectories.
+fn add_watches(inotify: &Inotify, watches: &mut HashMap<WatchDescriptor, PathBuf>, dir: PathBuf) {
+	let descriptor = match inotify.add_watch(&dir, get_watch_flags()) {
+		Ok(descriptor) => descriptor,
+		Err(err) => {
+			log::debug!("not watching {dir:?}: {err}");
+			return;
+		}
+	};
+
+	log::trace!("watching {dir:?} for changes");
+
+	let subdirs = fs::read_dir(&dir)
+		.into_iter()
+		.flatten()
+		.filter_map(Result::ok)
+		.filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+		.map(|e| e.path());
+
+	for subdir in subdirs {
+		add_watches(inotify, watches, subdir);
+	}
+
+	watches.insert(descriptor, dir);
+}
 
-	Some(hit)
+fn get_watch_flags() -> AddWatchFlags {
+	AddWatchFlags::IN_CLOSE_WRITE
+		| AddWatchFlags::IN_CREATE
+		| AddWatchFlags::IN_DELETE
+		| AddWatchFlags::IN_MOVED_FROM
+		| AddWatchFlags::IN_MOVED_TO
 }
 
-/// Runs the given entry using gtk-launch.
-fn run_program(desktop_file: &str, _: &SimpleHit, sender: &Sender<FrontendMessage>) {
-	log::debug!("starting application '{desktop_file}'");
+fn watch_loop(
+	inotify: &Inotify,
+	mut watches: HashMap<WatchDescriptor, PathBuf>,
+	roots: &[PathBuf],
+	index: &SharedIndex,
+) {
+	loop {
+		let events = match inotify.read_events() {
+			Ok(events) => events,
+			Err(err) => {
+				log::error!("error while watching program paths, stopping: {err}");
+				return;
+			}
+		};
 
-	Command::new("gtk-launch")
-		.arg(desktop_file)
-		// explicitly prevent stream inheritance
-		.stdin(Stdio::null())
-		.stdout(Stdio::null())
-		.stderr(Stdio::null())
-		.spawn()
-		.expect("gtk-launch should be present");
+		let mut changed = vec![];
+		let mut rescan = false;
+
+		for event in events {
+			let (Some(dir), Some(name)) = (watches.get(&event.wd), event.name) else {
+				continue;
+			};
+
+			let path = dir.join(name);
+
+			if !event.mask.contains(AddWatchFlags::IN_ISDIR) {
+				changed.push(path);
+				continue;
+			}
+
+			let created = event
+				.mask
+				.intersects(AddWatchFlags::IN_CREATE | AddWatchFlags::IN_MOVED_TO);
+
+			if roots.iter().any(|r| path.starts_with(r)) {
+				if created {
+					add_watches(inotify, &mut watches, path);
+				}
+
+				// a moved directory might already contain entries, so rescan everything
+				rescan = true;
+			} else if created {
+				// a parent of a missing root, which may already contain the root
+				for root in roots.iter().filter(|r| r.starts_with(&path)) {
+					rescan |= watch_root(inotify, &mut watches, root);
+				}
+			}
+		}
+
+		let mut index = index.write().expect("thread holding the lock can't panic");
+
+		if rescan {
+			index.refresh();
+			continue;
+		}
+
+		for path in changed.iter().filter(|p| p.extension().is_some_and(|e| e == "desktop")) {
+			index.update(path);
+		}
+	}
+}
+
+/// Returns the desktop file ID of the entry, see [`desktop_entry::get_desktop_file_id`].
+pub(crate) fn get_program_id(root: &Path, path: &Path) -> Option<String> {
+	desktop_entry::get_desktop_file_id(root, path)
+}
+
+/// Parses a desktop entry and returns a [`Program`] that represents it.
+pub(crate) fn get_program(config: &Config, id: &str, path: &Path) -> Option<Program> {
+	let entry = desktop_entry::parse(path, &ENVIRONMENT)?;
+
+	if !entry.visible {
+		return Some(Program::Hidden);
+	}
 
-	sender.send(FrontendMessage::Hide).ok();
+	let subtitle = path.to_string_lossy().into_owned();
+	let icon = entry.icon.as_deref().and_then(|i| get_icons(config).lookup(i));
+
+	let launchable = Arc::new(Launchable {
+		entry,
+		id: id.to_owned(),
+		location: subtitle.clone(),
+		terminal: config.linux.terminal.clone(),
+		systemd_scope: config.linux.systemd_scope,
+	});
+
+	let actions = launchable
+		.entry
+		.actions
+		.iter()
+		.map(|action| get_action_hit(launchable.clone(), action.clone(), &subtitle, icon.clone()))
+		.collect::<Vec<_>>();
+
+	let launchable_clone = launchable.clone();
+	let hit = SimpleHit::new(&*launchable.entry.name, subtitle, move |_, sender| {
+		log::debug!("starting application '{}'", launchable_clone.id);
+
+		if let Err(err) = launchable_clone.launch() {
+			log::error!("unable to start application '{}': {err:#}", launchable_clone.id);
+		}
+
+		sender.send(FrontendMessage::Hide).ok();
+	})
+	.with_icon(icon);
+
+	Some(Program::Visible(
+		once(Arc::new(hit) as Arc<dyn Hit>).chain(actions).collect(),
+	))
+}
+
+/// Returns a hit titled like "Firefox: New Private Window".
+fn get_action_hit(
+	launchable: Arc<Launchable>,
+	action: DesktopAction,
+	subtitle: &str,
+	icon: Option<PathBuf>,
+) -> Arc<dyn Hit> {
+	let title = format!("{}: {}", launchable.entry.name, action.name);
+
+	let hit = SimpleHit::new(title, subtitle, move |_, sender| {
+		log::debug!("starting action '{}' of '{}'", action.id, launchable.id);
+
+		if let Err(err) = launchable.launch_action(&action) {
+			log::error!("unable to start action '{}' of '{}': {err:#}", action.id, launchable.id);
+		}
+
+		sender.send(FrontendMessage::Hide).ok();
+	})
+	.with_icon(icon);
+
+	Arc::new(hit)
+}
+
+/// Loads the configured icon theme on first use.
+fn get_icons(config: &Config) -> &'static IconLookup {
+	ICONS.get_or_init(|| {
+		let theme = config.linux.icon_theme.clone().or_else(get_gtk_icon_theme);
+		let theme = theme.as_deref().unwrap_or("hicolor");
+		log::debug!("using icon theme '{theme}'");
+
+		let data_dirs = once(get_xdg_data_home()).chain(get_xdg_data_dirs());
+		let base_dirs = once(get_home().join(".icons"))
+			.chain(data_dirs.clone().map(|p| p.join("icons")))
+			.collect::<Vec<_>>();
+
+		let fallback_dirs = data_dirs.map(|p| p.join("pixmaps")).collect();
+
+		IconLookup::new(&base_dirs, fallback_dirs, theme, config.linux.icon_size)
+	})
+}
+
+/// Reads the icon theme configured for GTK applications, which most
+/// desktop environments keep in sync with their own setting.
+fn get_gtk_icon_theme() -> Option<String> {
+	let path = get_xdg_config_home().join("gtk-3.0/settings.ini");
+	let settings = Entry::parse_file(path).ok()?;
+
+	settings
+		.section("Settings")
+		.attr("gtk-icon-theme-name")
+		.map(|t| t.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::index::tests::{get_index, get_titles, ENTRY};
+	use std::time::{Duration, Instant};
+
+	/// Waits for the watcher thread to bring the index to the expected state.
+	fn wait_for(index: &SharedIndex, expected: &[&str]) -> Vec<String> {
+		let start = Instant::now();
+
+		loop {
+			let titles = get_titles(&index.read().unwrap());
+
+			if titles == expected || start.elapsed() > Duration::from_secs(5) {
+				return titles;
+			}
+
+			thread::sleep(Duration::from_millis(10));
+		}
+	}
+
+	#[test]
+	fn should_watch_missing_root() {
+		let (index, root) = get_index();
+		let index = index.shared();
+		watch(index.clone());
+
+		// neither share nor share/applications exist yet
+		let dir = root.path().join("share/applications");
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(dir.join("test.desktop"), ENTRY).unwrap();
+		let added = wait_for(&index, &["Test"]);
+
+		fs::remove_file(dir.join("test.desktop")).unwrap();
+		let removed = wait_for(&index, &[]);
+
+		assert_eq!(vec!["Test"], added);
+		assert!(removed.is_empty());
+	}
 }
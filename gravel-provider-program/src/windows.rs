@@ -1,8 +1,8 @@
-use crate::Config;
+use crate::{index::SharedIndex, Config, Program};
 use gravel_core::*;
 use std::path::Path;
 use std::process::Command;
-use std::sync::mpsc::Sender;
+use std::sync::{mpsc::Sender, Arc};
 
 pub(crate) fn get_program_paths(config: &Config) -> Vec<String> {
 	config.windows.shortcut_paths.iter().filter_map(expand_path).collect()
@@ -15,13 +15,23 @@ fn expand_path(path: &String) -> Option<String> {
 		.ok()
 }
 
+/// Watching the start menu isn't supported, the index
+/// has to be refreshed manually.
+pub(crate) fn watch(_index: SharedIndex) {}
+
+/// Links are identified by their filename.
+pub(crate) fn get_program_id(_root: &Path, path: &Path) -> Option<String> {
+	Some(path.file_name()?.to_str()?.to_owned())
+}
+
 /// Extracts an application's name from the filename of the link and
-/// returns a [`SimpleHit`] that represents it.
-pub fn get_program(path: &Path) -> Option<SimpleHit> {
+/// returns a [`Program`] that represents it.
+pub(crate) fn get_program(_config: &Config, _id: &str, path: &Path) -> Option<Program> {
 	let name = path.file_stem()?.to_string_lossy();
 	let path = path.to_str()?.to_owned();
 
-	Some(SimpleHit::new(name, path.clone(), move |h, s| run_program(&path, h, s)))
+	let hit = SimpleHit::new(name, path.clone(), move |h, s| run_program(&path, h, s));
+	Some(Program::Visible(vec![Arc::new(hit)]))
 }
 
 /// Passes the link's path to explorer, which then launches the application.
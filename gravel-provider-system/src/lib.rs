@@ -1,15 +1,32 @@
 //! System provider.
-//! Provides system commands such as shutdown, log out or exiting gravel.
+//! Provides system commands such as shutdown, log out or exiting gravel,
+//! as well as custom commands defined in the config.
+//!
+//! Commands can require confirmation, so they only run if they are
+//! selected twice. By default, this applies to log out, restart and shutdown.
+//!
+//! On Linux, lock, log out, restart, shutdown, sleep and hibernate go through
+//! systemd-logind's D-Bus API, which also hides those that are unavailable.
+//! Their configured commands are used for operations logind is disabled
+//! for, or if it fails. Availability is checked in the background, so
+//! startup doesn't wait for D-Bus.
+//! On Windows, the native API is used instead.
 
-use anyhow::Result;
 use gravel_core::{config::PluginConfigAdapter, plugin::*, *};
+use implementation::Backend;
 use serde::Deserialize;
-use std::{env, sync::Arc};
+use std::{
+	env,
+	sync::{mpsc::Sender, Arc},
+};
 
 #[cfg_attr(target_os = "linux", path = "linux.rs")]
 #[cfg_attr(windows, path = "windows.rs")]
 mod implementation;
 
+#[cfg(target_os = "linux")]
+mod logind;
+
 const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
 
 pub fn register_plugins(registry: &mut PluginRegistry) {
@@ -21,34 +38,85 @@ pub fn register_plugins(registry: &mut PluginRegistry) {
 fn get_provider(config: &PluginConfigAdapter) -> Box<dyn Provider> {
 	let plugin_config = config.get::<Config>(DEFAULT_CONFIG);
 
-	let provider = WebsearchProvider::new(plugin_config);
+	let provider = SystemProvider::new(plugin_config);
 
 	Box::new(provider)
 }
 
-pub struct WebsearchProvider {
-	hits: Box<[Arc<dyn Hit>]>,
+/// Operations that are run natively, or with their configured Linux command.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Operation {
+	Lock,
+	Logout,
+	Restart,
+	Shutdown,
+	Sleep,
+	Hibernate,
+}
+
+/// A hit, along with the operation it runs, if any.
+type SystemHit = (Option<Operation>, Arc<dyn Hit>);
+
+pub struct SystemProvider {
+	backend: Arc<Backend>,
+	/// Hits running an operation are only shown while it's available.
+	hits: Box<[SystemHit]>,
 }
 
-impl WebsearchProvider {
+impl SystemProvider {
 	fn new(config: Config) -> Self {
-		let hits = vec![
-			get_message_hit(config.exit, FrontendMessage::Exit),
-			get_message_hit(config.reload, FrontendMessage::Restart),
-			get_shell_hit(config.lock, implementation::lock),
-			get_shell_hit(config.logout, implementation::logout),
-			get_shell_hit(config.restart, implementation::restart),
-			get_shell_hit(config.shutdown, implementation::shutdown),
-			get_shell_hit(config.sleep, implementation::sleep),
+		let backend = Arc::new(Backend::new(&config));
+		let enabled = |name: &str| !config.disabled.iter().any(|d| d == name);
+
+		let messages = [
+			("exit", config.exit, FrontendMessage::Exit),
+			("reload", config.reload, FrontendMessage::Restart),
+		];
+
+		let operations = [
+			("lock", config.lock, Operation::Lock),
+			("logout", config.logout, Operation::Logout),
+			("restart", config.restart, Operation::Restart),
+			("shutdown", config.shutdown, Operation::Shutdown),
+			("sleep", config.sleep, Operation::Sleep),
+			("hibernate", config.hibernate, Operation::Hibernate),
 		];
 
-		Self { hits: hits.into() }
+		let message_hits = messages
+			.into_iter()
+			.filter(|(name, _, _)| enabled(name))
+			.map(|(_, config, message)| (None, get_message_hit(config, message)));
+
+		let operation_hits = operations
+			.into_iter()
+			.filter(|(name, _, _)| enabled(name))
+			.map(|(_, config, operation)| (Some(operation), get_operation_hit(config, &backend, operation)));
+
+		let custom = config.custom.into_iter().map(|c| (None, get_custom_hit(c)));
+		let hits = message_hits.chain(operation_hits).chain(custom).collect();
+
+		Self { backend, hits }
 	}
 }
 
-impl Provider for WebsearchProvider {
+impl Provider for SystemProvider {
 	fn query(&self, _query: &str) -> ProviderResult {
-		ProviderResult::new(self.hits.to_vec())
+		let hits = self
+			.hits
+			.iter()
+			.filter(|(operation, _)| match operation {
+				Some(operation) => self.backend.is_available(*operation),
+				None => true,
+			})
+			.map(|(_, hit)| hit.clone())
+			.collect();
+
+		ProviderResult::new(hits)
+	}
+
+	fn set_sender(&mut self, sender: Sender<FrontendMessage>) {
+		self.backend.start(sender);
 	}
 }
 
@@ -57,22 +125,32 @@ fn get_message_hit(config: CommandConfig, message: FrontendMessage) -> Arc<dyn H
 		sender.send(message.clone()).ok();
 	});
 
-	Arc::new(hit)
+	Arc::new(hit.with_confirmation(config.confirm))
 }
 
-fn get_shell_hit(
-	config: ShellCommandConfig,
-	action: impl Fn(&str) -> Result<()> + Send + Sync + 'static,
-) -> Arc<SimpleHit> {
+fn get_operation_hit(config: ShellCommandConfig, backend: &Arc<Backend>, operation: Operation) -> Arc<dyn Hit> {
+	let backend = backend.clone();
 	let hit = SimpleHit::new(config.title, config.subtitle, move |hit, sender| {
-		if let Err(err) = action(&config.command_linux) {
+		if let Err(err) = backend.run(operation, &config.command_linux) {
 			log::error!("error during system operation {}: {err}", hit.get_title());
 		}
 
 		sender.send(FrontendMessage::Hide).ok();
 	});
 
-	Arc::new(hit)
+	Arc::new(hit.with_confirmation(config.confirm))
+}
+
+fn get_custom_hit(config: CustomCommandConfig) -> Arc<dyn Hit> {
+	let hit = SimpleHit::new(config.title, config.subtitle, move |hit, sender| {
+		if let Err(err) = implementation::run_command(&config.command) {
+			log::error!("error during custom command {}: {err}", hit.get_title());
+		}
+
+		sender.send(FrontendMessage::Hide).ok();
+	});
+
+	Arc::new(hit.with_confirmation(config.confirm))
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -84,12 +162,25 @@ struct Config {
 	pub restart: ShellCommandConfig,
 	pub shutdown: ShellCommandConfig,
 	pub sleep: ShellCommandConfig,
+	pub hibernate: ShellCommandConfig,
+	pub disabled: Vec<String>,
+	pub custom: Vec<CustomCommandConfig>,
+	#[cfg(target_os = "linux")]
+	pub linux: LinuxConfig,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Clone, Deserialize, Debug)]
+struct LinuxConfig {
+	pub use_logind: Vec<Operation>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
 struct CommandConfig {
 	pub title: String,
 	pub subtitle: String,
+	/// Prompt shown before running the command, if it requires confirmation.
+	pub confirm: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -97,4 +188,15 @@ struct ShellCommandConfig {
 	pub title: String,
 	pub subtitle: String,
 	pub command_linux: String,
+	/// Prompt shown before running the command, if it requires confirmation.
+	pub confirm: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+struct CustomCommandConfig {
+	pub title: String,
+	pub subtitle: String,
+	pub command: String,
+	/// Prompt shown before running the command, if it requires confirmation.
+	pub confirm: Option<String>,
 }
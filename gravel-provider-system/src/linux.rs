@@ -1,28 +1,127 @@
+use crate::{logind, Config, Operation};
 use anyhow::Result;
+use gravel_core::FrontendMessage;
 use std::process::Command;
+use std::sync::{mpsc::Sender, Arc, Mutex, OnceLock};
+use std::thread;
+use zbus::blocking::Connection;
 
-pub(crate) fn lock(command_linux: &str) -> Result<()> {
-	shell_run(command_linux)
+/// Runs system operations through logind, or with the configured commands.
+pub(crate) struct Backend {
+	/// Operations that should go through logind.
+	logind_operations: Vec<Operation>,
+	/// System bus connection, connected on first use as that may block.
+	connection: OnceLock<Option<Connection>>,
+	/// Operations logind doesn't allow, known once [`Backend::start`] checked them.
+	unavailable: Mutex<Vec<Operation>>,
 }
 
-pub(crate) fn logout(command_linux: &str) -> Result<()> {
-	shell_run(command_linux)
-}
+impl Backend {
+	pub fn new(config: &Config) -> Self {
+		Self {
+			logind_operations: config.linux.use_logind.clone(),
+			connection: OnceLock::new(),
+			unavailable: Mutex::new(vec![]),
+		}
+	}
 
-pub(crate) fn restart(command_linux: &str) -> Result<()> {
-	shell_run(command_linux)
-}
+	/// Asks logind which operations are available on a new thread, so
+	/// startup isn't blocked. Asks the frontend to refresh if some aren't.
+	pub fn start(self: &Arc<Self>, sender: Sender<FrontendMessage>) {
+		if self.logind_operations.is_empty() {
+			return;
+		}
+
+		let backend = self.clone();
+		thread::spawn(move || {
+			let unavailable = backend
+				.logind_operations
+				.iter()
+				.copied()
+				.filter(|operation| !backend.can(*operation))
+				.collect::<Vec<_>>();
+
+			if unavailable.is_empty() {
+				return;
+			}
+
+			log::debug!("logind doesn't allow {unavailable:?}, hiding them");
+
+			let mut state = backend
+				.unavailable
+				.lock()
+				.expect("thread holding the mutex can't panic");
+			*state = unavailable;
+			drop(state);
+
+			sender.send(FrontendMessage::Refresh).ok();
+		});
+	}
+
+	/// Returns whether logind allows the operation. Until that is known,
+	/// or if logind isn't used for it, the operation is assumed to be available.
+	pub fn is_available(&self, operation: Operation) -> bool {
+		let unavailable = self.unavailable.lock().expect("thread holding the mutex can't panic");
 
-pub(crate) fn shutdown(command_linux: &str) -> Result<()> {
-	shell_run(command_linux)
+		!unavailable.contains(&operation)
+	}
+
+	/// Runs the operation through logind if configured, falling back to the command.
+	pub fn run(&self, operation: Operation, command_linux: &str) -> Result<()> {
+		if let Some(connection) = self.get_connection(operation) {
+			match logind::run(connection, operation) {
+				Ok(()) => return Ok(()),
+				Err(err) => log::warn!("logind failed to run {operation:?}, using command instead: {err}"),
+			}
+		}
+
+		shell_run(command_linux)
+	}
+
+	fn can(&self, operation: Operation) -> bool {
+		let Some(connection) = self.get_connection(operation) else {
+			return true;
+		};
+
+		match logind::can(connection, operation) {
+			Ok(available) => available,
+			Err(err) => {
+				log::warn!("unable to check whether {operation:?} is available: {err}");
+				true
+			}
+		}
+	}
+
+	/// Returns the system bus connection, if logind should be used for the operation.
+	fn get_connection(&self, operation: Operation) -> Option<&Connection> {
+		if !self.logind_operations.contains(&operation) {
+			return None;
+		}
+
+		let connection = self.connection.get_or_init(|| {
+			Connection::system()
+				.map_err(|err| log::warn!("unable to connect to the system bus, using commands instead: {err}"))
+				.ok()
+		});
+
+		connection.as_ref()
+	}
 }
 
-pub(crate) fn sleep(command_linux: &str) -> Result<()> {
-	shell_run(command_linux)
+pub(crate) fn run_command(command: &str) -> Result<()> {
+	shell_run(command)
 }
 
+/// Runs the command with bash, logging if it fails in the background.
 fn shell_run(cmd: &str) -> Result<()> {
-	Command::new("/usr/bin/env").arg("bash").arg("-c").arg(cmd).spawn()?;
+	let mut child = Command::new("/usr/bin/env").arg("bash").arg("-c").arg(cmd).spawn()?;
+	let cmd = cmd.to_owned();
+
+	thread::spawn(move || match child.wait() {
+		Ok(status) if !status.success() => log::error!("command '{cmd}' failed with {status}"),
+		Ok(_) => (),
+		Err(err) => log::error!("unable to wait for command '{cmd}': {err}"),
+	});
 
 	Ok(())
 }
@@ -0,0 +1,175 @@
+//! System operations through systemd-logind's D-Bus API.
+
+use crate::Operation;
+use zbus::blocking::Connection;
+
+const LOGIND_DESTINATION: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER: &str = "org.freedesktop.login1.Manager";
+const LOGIND_SESSION: &str = "org.freedesktop.login1.Session";
+
+/// Resolves to the caller's session, or the user's graphical session if
+/// gravel runs outside of one, such as in a systemd user service.
+const SESSION_PATH: &str = "/org/freedesktop/login1/session/auto";
+
+/// Returns whether logind allows the operation. Operations that require
+/// authentication count as allowed, polkit asks for it when they're run.
+pub fn can(connection: &Connection, operation: Operation) -> zbus::Result<bool> {
+	let method = match operation {
+		Operation::Lock | Operation::Logout => return Ok(true),
+		Operation::Restart => "CanReboot",
+		Operation::Shutdown => "CanPowerOff",
+		Operation::Sleep => "CanSuspend",
+		Operation::Hibernate => "CanHibernate",
+	};
+
+	let answer = connection
+		.call_method(Some(LOGIND_DESTINATION), LOGIND_PATH, Some(LOGIND_MANAGER), method, &())?
+		.body::<String>()?;
+
+	Ok(matches!(answer.as_str(), "yes" | "challenge"))
+}
+
+pub fn run(connection: &Connection, operation: Operation) -> zbus::Result<()> {
+	match operation {
+		Operation::Lock => call_session(connection, "Lock"),
+		Operation::Logout => call_session(connection, "Terminate"),
+		Operation::Restart => call_manager(connection, "Reboot"),
+		Operation::Shutdown => call_manager(connection, "PowerOff"),
+		Operation::Sleep => call_manager(connection, "Suspend"),
+		Operation::Hibernate => call_manager(connection, "Hibernate"),
+	}
+}
+
+fn call_manager(connection: &Connection, method: &str) -> zbus::Result<()> {
+	// interactive, so polkit can ask for authentication if necessary
+	let interactive = true;
+
+	connection.call_method(
+		Some(LOGIND_DESTINATION),
+		LOGIND_PATH,
+		Some(LOGIND_MANAGER),
+		method,
+		&(interactive,),
+	)?;
+
+	Ok(())
+}
+
+fn call_session(connection: &Connection, method: &str) -> zbus::Result<()> {
+	connection.call_method(
+		Some(LOGIND_DESTINATION),
+		SESSION_PATH,
+		Some(LOGIND_SESSION),
+		method,
+		&(),
+	)?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use gravel_core::test_util::mock_bus;
+	use rstest::rstest;
+	use std::sync::{Arc, Mutex};
+	use zbus::dbus_interface;
+
+	type Calls = Arc<Mutex<Vec<String>>>;
+
+	/// Stands in for logind's manager, answering capability checks like a
+	/// system that can't hibernate and recording all other calls.
+	struct MockManager {
+		calls: Calls,
+	}
+
+	#[dbus_interface(name = "org.freedesktop.login1.Manager")]
+	impl MockManager {
+		fn can_power_off(&self) -> String {
+			"yes".into()
+		}
+
+		fn can_reboot(&self) -> String {
+			"challenge".into()
+		}
+
+		fn can_suspend(&self) -> String {
+			"yes".into()
+		}
+
+		fn can_hibernate(&self) -> String {
+			"na".into()
+		}
+
+		fn power_off(&self, interactive: bool) {
+			self.calls.lock().unwrap().push(format!("PowerOff({interactive})"));
+		}
+
+		fn reboot(&self, interactive: bool) {
+			self.calls.lock().unwrap().push(format!("Reboot({interactive})"));
+		}
+
+		fn suspend(&self, interactive: bool) {
+			self.calls.lock().unwrap().push(format!("Suspend({interactive})"));
+		}
+
+		fn hibernate(&self, interactive: bool) {
+			self.calls.lock().unwrap().push(format!("Hibernate({interactive})"));
+		}
+	}
+
+	struct MockSession {
+		calls: Calls,
+	}
+
+	#[dbus_interface(name = "org.freedesktop.login1.Session")]
+	impl MockSession {
+		fn lock(&self) {
+			self.calls.lock().unwrap().push("Lock".into());
+		}
+
+		fn terminate(&self) {
+			self.calls.lock().unwrap().push("Terminate".into());
+		}
+	}
+
+	/// Connects to a mock logind over a private peer-to-peer connection.
+	fn mock_logind() -> (Connection, Connection, Calls) {
+		let calls = Calls::default();
+		let manager = MockManager { calls: calls.clone() };
+		let session = MockSession { calls: calls.clone() };
+		let (client, server) =
+			mock_bus(move |builder| builder.serve_at(LOGIND_PATH, manager)?.serve_at(SESSION_PATH, session));
+
+		(client, server, calls)
+	}
+
+	#[rstest]
+	#[case(Operation::Lock, true)]
+	#[case(Operation::Logout, true)]
+	#[case(Operation::Restart, true)]
+	#[case(Operation::Shutdown, true)]
+	#[case(Operation::Sleep, true)]
+	#[case(Operation::Hibernate, false)]
+	fn should_check_capability(#[case] operation: Operation, #[case] expected: bool) {
+		let (client, _server, _) = mock_logind();
+
+		assert_eq!(expected, can(&client, operation).unwrap(), "{operation:?}");
+	}
+
+	#[rstest]
+	#[case(Operation::Lock, "Lock")]
+	#[case(Operation::Logout, "Terminate")]
+	#[case(Operation::Restart, "Reboot(true)")]
+	#[case(Operation::Shutdown, "PowerOff(true)")]
+	#[case(Operation::Sleep, "Suspend(true)")]
+	#[case(Operation::Hibernate, "Hibernate(true)")]
+	fn should_run(#[case] operation: Operation, #[case] expected: &str) {
+		let (client, _server, calls) = mock_logind();
+
+		run(&client, operation).unwrap();
+
+		assert_eq!(vec![expected.to_owned()], *calls.lock().unwrap());
+	}
+}
@@ -1,33 +1,46 @@
+use crate::{Config, Operation};
 use anyhow::Result;
+use gravel_core::FrontendMessage;
+use std::process::Command;
+use std::sync::{mpsc::Sender, Arc};
 use winapi::um::{powrprof, winuser};
 
-pub(crate) fn lock(_command_linux: &str) -> Result<()> {
-	unsafe {
-		winuser::LockWorkStation();
+/// Runs system operations through the native API.
+pub(crate) struct Backend;
+
+impl Backend {
+	pub fn new(_config: &Config) -> Self {
+		Self
 	}
 
-	Ok(())
-}
+	pub fn start(self: &Arc<Self>, _sender: Sender<FrontendMessage>) {}
 
-pub(crate) fn logout(_command_linux: &str) -> Result<()> {
-	system_shutdown::logout()?;
-	Ok(())
-}
+	pub fn is_available(&self, _operation: Operation) -> bool {
+		true
+	}
 
-pub(crate) fn restart(_command_linux: &str) -> Result<()> {
-	system_shutdown::reboot()?;
-	Ok(())
-}
+	pub fn run(&self, operation: Operation, _command_linux: &str) -> Result<()> {
+		match operation {
+			Operation::Lock => unsafe {
+				winuser::LockWorkStation();
+			},
+			Operation::Logout => system_shutdown::logout()?,
+			Operation::Restart => system_shutdown::reboot()?,
+			Operation::Shutdown => system_shutdown::shutdown()?,
+			Operation::Sleep => unsafe {
+				powrprof::SetSuspendState(0, 0, 0);
+			},
+			Operation::Hibernate => unsafe {
+				powrprof::SetSuspendState(1, 0, 0);
+			},
+		}
 
-pub(crate) fn shutdown(_command_linux: &str) -> Result<()> {
-	system_shutdown::shutdown()?;
-	Ok(())
+		Ok(())
+	}
 }
 
-pub(crate) fn sleep(_command_linux: &str) -> Result<()> {
-	unsafe {
-		powrprof::SetSuspendState(0, 0, 0);
-	}
+pub(crate) fn run_command(command: &str) -> Result<()> {
+	Command::new("cmd").arg("/C").arg(command).spawn()?;
 
 	Ok(())
 }
@@ -0,0 +1,314 @@
+//! Date and time provider.
+//!
+//! Calculates with dates and times, for queries like `now + 3 days`,
+//! `2026-12-24 - today`, `next friday` or `unix 1700000000`, and converts
+//! between time zones, like `14:00 PST in Berlin`.
+//!
+//! Time zones can be given by IANA name, city, common abbreviation or offset.
+//! The zone data is bundled, so results don't depend on the system.
+//!
+//! Selecting the hit copies the result to the system's clipboard.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::MAX_SCORE, *};
+use parse::{Answer, Context, Point, Precision, Unit};
+use serde::Deserialize;
+use std::{
+	fmt::Write,
+	sync::{mpsc::Sender, Arc},
+};
+
+mod parse;
+mod zones;
+
+const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
+
+pub fn register_plugins(registry: &mut PluginRegistry) {
+	let definition = plugin("time").with_provider(Box::new(get_provider));
+
+	registry.register(definition);
+}
+
+fn get_provider(config: &PluginConfigAdapter) -> Box<dyn Provider> {
+	let plugin_config = config.get::<Config>(DEFAULT_CONFIG);
+
+	Box::new(TimeProvider {
+		local: get_local_zone(plugin_config.timezone.as_deref()),
+		config: plugin_config,
+	})
+}
+
+/// Uses the configured zone, or the system's if there is none.
+fn get_local_zone(configured: Option<&str>) -> Tz {
+	if let Some(name) = configured {
+		match zones::find_zone(name) {
+			Some(zone) => return zone,
+			None => log::error!("unknown timezone '{name}', using the system's"),
+		}
+	}
+
+	let name = match iana_time_zone::get_timezone() {
+		Ok(name) => name,
+		Err(err) => {
+			log::warn!("unable to determine the system's timezone, using UTC: {err}");
+			return Tz::UTC;
+		}
+	};
+
+	name.parse().unwrap_or_else(|_| {
+		log::warn!("unknown system timezone '{name}', using UTC");
+		Tz::UTC
+	})
+}
+
+struct TimeProvider {
+	config: Config,
+	local: Tz,
+}
+
+impl TimeProvider {
+	/// Adds the zone's name to the subtitle for results that are points in time.
+	fn get_subtitle(&self, answer: &Answer) -> String {
+		match answer {
+			Answer::Point(point) | Answer::Converted { point, .. } => {
+				format!("{} - {}", self.config.subtitle, point.time.timezone().name())
+			}
+			_ => self.config.subtitle.clone(),
+		}
+	}
+}
+
+impl Provider for TimeProvider {
+	fn query(&self, query: &str) -> ProviderResult {
+		let context = Context {
+			now: Utc::now().with_timezone(&self.local),
+			local: self.local,
+		};
+
+		let Some(answer) = parse::evaluate(query.trim(), &context) else {
+			return ProviderResult::empty();
+		};
+
+		let (title, value) = format_answer(&answer, &self.config);
+		let hit = SimpleHit::new(title, self.get_subtitle(&answer), move |_, s| do_copy(&value, s));
+
+		ProviderResult::single(Arc::new(hit.with_score(MAX_SCORE)))
+	}
+}
+
+/// Returns the hit's title and the value to copy.
+fn format_answer(answer: &Answer, config: &Config) -> (String, String) {
+	let title = match *answer {
+		Answer::Point(point) => format_point(point, config),
+		Answer::Converted { point, original } => {
+			let title = format_point(point, config);
+			let days = (point.time.date_naive() - original.time.date_naive()).num_days();
+
+			// times alone don't show the date, so point out when it changed
+			match (point.precision, days) {
+				(Precision::Time, days) if days != 0 => format!("{title} ({days:+} {})", Unit::Day.name(days as f64)),
+				_ => title,
+			}
+		}
+		Answer::Difference { days: Some(days), .. } => format!("{days} {}", Unit::Day.name(days as f64)),
+		Answer::Difference { delta, days: None } => format_delta(delta),
+		Answer::Timestamp(timestamp) => timestamp.to_string(),
+		Answer::Amount { value, unit } => {
+			let value = format_amount(value);
+			let title = format!("{value} {}", unit.name(value.parse().unwrap_or_default()));
+
+			return (title, value);
+		}
+	};
+
+	(title.clone(), title)
+}
+
+fn format_point(point: Point, config: &Config) -> String {
+	let format = match point.precision {
+		Precision::Date => &config.date_format,
+		Precision::Time => &config.time_format,
+		Precision::DateTime => &config.datetime_format,
+	};
+
+	format_time(point.time, format)
+}
+
+/// Formats the time, falling back to RFC 3339 if the format is invalid.
+fn format_time(time: DateTime<Tz>, format: &str) -> String {
+	let mut formatted = String::new();
+
+	match write!(formatted, "{}", time.format(format)) {
+		Ok(()) => formatted,
+		Err(_) => {
+			log::error!("invalid time format '{format}'");
+			time.to_rfc3339()
+		}
+	}
+}
+
+/// Writes the difference like `2 days, 3 hours, 5 seconds`.
+fn format_delta(delta: TimeDelta) -> String {
+	let seconds = delta.num_seconds();
+	let sign = if seconds < 0 { "-" } else { "" };
+	let seconds = seconds.unsigned_abs();
+
+	let parts = [
+		(seconds / 86400, Unit::Day),
+		(seconds / 3600 % 24, Unit::Hour),
+		(seconds / 60 % 60, Unit::Minute),
+		(seconds % 60, Unit::Second),
+	];
+
+	let parts = parts
+		.iter()
+		.filter(|(amount, _)| *amount != 0)
+		.map(|(amount, unit)| format!("{amount} {}", unit.name(*amount as f64)))
+		.collect::<Vec<_>>();
+
+	match parts.is_empty() {
+		true => format!("0 {}", Unit::Second.name(0.0)),
+		false => format!("{sign}{}", parts.join(", ")),
+	}
+}
+
+/// Rounds to two decimals, without trailing zeros.
+fn format_amount(value: f64) -> String {
+	let formatted = format!("{value:.2}");
+	let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+
+	match formatted {
+		"-0" => "0".to_owned(),
+		formatted => formatted.to_owned(),
+	}
+}
+
+fn do_copy(value: &str, sender: &Sender<FrontendMessage>) {
+	log::debug!("copying value to clipboard: {value}");
+
+	clipboard::set_text(value);
+
+	sender.send(FrontendMessage::Hide).ok();
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+	pub subtitle: String,
+	pub timezone: Option<String>,
+	pub datetime_format: String,
+	pub date_format: String,
+	pub time_format: String,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+	use rstest::rstest;
+
+	fn evaluate(query: &str) -> Option<String> {
+		let local = chrono_tz::Europe::Berlin;
+
+		let context = Context {
+			now: local.with_ymd_and_hms(2026, 10, 19, 12, 34, 56).unwrap(),
+			local,
+		};
+
+		let config = Config {
+			subtitle: String::new(),
+			timezone: None,
+			datetime_format: "%a %Y-%m-%d %H:%M:%S %Z".to_owned(),
+			date_format: "%a %Y-%m-%d".to_owned(),
+			time_format: "%H:%M %Z".to_owned(),
+		};
+
+		parse::evaluate(query, &context).map(|a| format_answer(&a, &config).0)
+	}
+
+	#[rstest]
+	#[case("now", "Mon 2026-10-19 12:34:56 CEST")]
+	#[case("today", "Mon 2026-10-19")]
+	#[case("tomorrow", "Tue 2026-10-20")]
+	#[case("now + 3 days", "Thu 2026-10-22 12:34:56 CEST")]
+	#[case("now + 1 week", "Mon 2026-10-26 12:34:56 CET")]
+	#[case("now - 2h30m", "Mon 2026-10-19 10:04:56 CEST")]
+	#[case("now +90m", "Mon 2026-10-19 14:04:56 CEST")]
+	#[case("today + 1 month", "Thu 2026-11-19")]
+	#[case("2026-01-31 + 1 month", "Sat 2026-02-28")]
+	#[case("today + 2 weeks 3 days", "Thu 2026-11-05")]
+	#[case("today + 12 hours", "Mon 2026-10-19 12:00:00 CEST")]
+	#[case("in 3 days", "Thu 2026-10-22 12:34:56 CEST")]
+	#[case("2 hours ago", "Mon 2026-10-19 10:34:56 CEST")]
+	#[case("a day from now", "Tue 2026-10-20 12:34:56 CEST")]
+	#[case("2026-12-24 - today", "66 days")]
+	#[case("2026-12-24 - today in weeks", "9.43 weeks")]
+	#[case("today - 2026-10-18", "1 day")]
+	#[case("2026-12-24 18:00 - now", "66 days, 6 hours, 25 minutes, 4 seconds")]
+	#[case("now - 14:00", "-1 hour, 25 minutes, 4 seconds")]
+	#[case("now - 12:00 in minutes", "34.93 minutes")]
+	#[case("unix 1700000000", "Tue 2023-11-14 23:13:20 CET")]
+	#[case("unix 1700000000000", "Tue 2023-11-14 23:13:20 CET")]
+	#[case("@1700000000 in utc", "Tue 2023-11-14 22:13:20 UTC")]
+	#[case("2026-10-19 in unix", "1792360800")]
+	#[case("14:00 PST in Berlin", "00:00 CEST (+1 day)")]
+	#[case("9am tokyo in new york", "20:00 EDT (-1 day)")]
+	#[case("noon utc in local", "14:00 CEST")]
+	#[case("15:30 to utc+2", "15:30 +02")]
+	#[case("today in tokyo", "Mon 2026-10-19 07:00:00 JST")]
+	#[case("next friday", "Fri 2026-10-23")]
+	#[case("next monday", "Mon 2026-10-26")]
+	#[case("last monday", "Mon 2026-10-12")]
+	#[case("monday", "Mon 2026-10-19")]
+	#[case("24 dec", "Thu 2026-12-24")]
+	#[case("24th december", "Thu 2026-12-24")]
+	#[case("dec 24 2027 18:00", "Fri 2027-12-24 18:00:00 CET")]
+	#[case("24.12.2026 + 1 day", "Fri 2026-12-25")]
+	#[case("24.12.", "Thu 2026-12-24")]
+	#[case("2026/12/24", "Thu 2026-12-24")]
+	#[case("tomorrow 9:30pm", "Tue 2026-10-20 21:30:00 CEST")]
+	#[case("9 am tomorrow", "Tue 2026-10-20 09:00:00 CEST")]
+	#[case("midnight", "00:00 CEST")]
+	#[case("12am", "00:00 CEST")]
+	#[case("12pm", "12:00 CEST")]
+	#[case("14:00 + 1 hour", "15:00 CEST")]
+	#[case("14:00 + 1 day", "Tue 2026-10-20 14:00:00 CEST")]
+	#[case("2026-03-29 02:30", "Sun 2026-03-29 03:30:00 CEST")]
+	#[case("2026-03-28 02:30 + 1 day", "Sun 2026-03-29 03:30:00 CEST")]
+	#[case("2026-10-24 12:00 + 24 hours", "Sun 2026-10-25 11:00:00 CET")]
+	fn should_evaluate(#[case] query: &str, #[case] expected: &str) {
+		assert_eq!(Some(expected), evaluate(query).as_deref(), "{query}");
+	}
+
+	#[rstest]
+	#[case("")]
+	#[case("hello")]
+	#[case("now +")]
+	#[case("now + hello")]
+	#[case("2026-02-30")]
+	#[case("3 days")]
+	#[case("14:00 in atlantis")]
+	#[case("1 + 1")]
+	#[case("24")]
+	#[case("25:00")]
+	#[case("13pm")]
+	#[case("may")]
+	#[case("now - tomorrow + 1 day")]
+	#[case("now + tomorrow")]
+	#[case("today + 1.5 months")]
+	#[case("today in months")]
+	#[case("2026-12-24 - today in months")]
+	#[case("now + 99999999 years")]
+	fn should_fail(#[case] query: &str) {
+		assert_eq!(None, evaluate(query), "{query}");
+	}
+
+	#[rstest]
+	#[case(0, "0 seconds")]
+	#[case(1, "1 second")]
+	#[case(90061, "1 day, 1 hour, 1 minute, 1 second")]
+	#[case(-7200, "-2 hours")]
+	fn should_format_delta(#[case] seconds: i64, #[case] expected: &str) {
+		assert_eq!(expected, format_delta(TimeDelta::try_seconds(seconds).unwrap()));
+	}
+}
@@ -0,0 +1,776 @@
+//! Parsing and evaluation of queries like `now + 3 days`, `2026-12-24 - today`,
+//! `unix 1700000000` or `14:00 PST in Berlin`.
+//!
+//! A query is a point in time, optionally followed by durations to add or
+//! subtract, or by another point to subtract. It may end with a target like
+//! `in Berlin`, `in unix` or `in weeks`.
+
+use crate::zones::find_zone;
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Weekday};
+use chrono_tz::Tz;
+
+/// Words separating the expression from the target.
+const SEPARATORS: &[&str] = &["in", "to", "as"];
+
+/// Timestamps larger than this are read as milliseconds.
+const MAX_TIMESTAMP_SECONDS: i64 = 99_999_999_999;
+
+/// Larger amounts couldn't be added to a date anyway.
+const MAX_AMOUNT: f64 = 1e7;
+
+const MONTHS: &[&str] = &[
+	"january",
+	"february",
+	"march",
+	"april",
+	"may",
+	"june",
+	"july",
+	"august",
+	"september",
+	"october",
+	"november",
+	"december",
+];
+
+const WEEKDAYS: &[(&str, Weekday)] = &[
+	("monday", Weekday::Mon),
+	("tuesday", Weekday::Tue),
+	("wednesday", Weekday::Wed),
+	("thursday", Weekday::Thu),
+	("friday", Weekday::Fri),
+	("saturday", Weekday::Sat),
+	("sunday", Weekday::Sun),
+];
+
+pub struct Context {
+	pub now: DateTime<Tz>,
+	/// Zone of times given without one.
+	pub local: Tz,
+}
+
+/// What the user specified, which decides how a point is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+	Date,
+	Time,
+	DateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+	pub time: DateTime<Tz>,
+	pub precision: Precision,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+	Second,
+	Minute,
+	Hour,
+	Day,
+	Week,
+	Month,
+	Year,
+}
+
+impl Unit {
+	fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"s" | "sec" | "secs" | "second" | "seconds" => Some(Self::Second),
+			"m" | "min" | "mins" | "minute" | "minutes" => Some(Self::Minute),
+			"h" | "hr" | "hrs" | "hour" | "hours" => Some(Self::Hour),
+			"d" | "day" | "days" => Some(Self::Day),
+			"w" | "wk" | "wks" | "week" | "weeks" => Some(Self::Week),
+			"mo" | "month" | "months" => Some(Self::Month),
+			"y" | "yr" | "yrs" | "year" | "years" => Some(Self::Year),
+			_ => None,
+		}
+	}
+
+	/// Length of the unit, if it's always the same.
+	fn seconds(self) -> Option<i64> {
+		match self {
+			Self::Second => Some(1),
+			Self::Minute => Some(60),
+			Self::Hour => Some(3600),
+			Self::Day => Some(86400),
+			Self::Week => Some(604800),
+			Self::Month | Self::Year => None,
+		}
+	}
+
+	pub fn name(self, amount: f64) -> &'static str {
+		let (singular, plural) = match self {
+			Self::Second => ("second", "seconds"),
+			Self::Minute => ("minute", "minutes"),
+			Self::Hour => ("hour", "hours"),
+			Self::Day => ("day", "days"),
+			Self::Week => ("week", "weeks"),
+			Self::Month => ("month", "months"),
+			Self::Year => ("year", "years"),
+		};
+
+		match amount.abs() == 1.0 {
+			true => singular,
+			false => plural,
+		}
+	}
+}
+
+/// Amount of time to add to a point. Months and days are calendar units,
+/// so adding a day keeps the time of day, even across daylight saving changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Span {
+	months: i64,
+	days: i64,
+	seconds: i64,
+}
+
+impl Span {
+	fn new(amount: f64, unit: Unit) -> Option<Self> {
+		if !amount.is_finite() || amount.abs() > MAX_AMOUNT {
+			return None;
+		}
+
+		let whole = amount.fract() == 0.0;
+
+		let span = match unit {
+			Unit::Month | Unit::Year if !whole => return None,
+			Unit::Month => Self::months(amount as i64),
+			Unit::Year => Self::months(amount as i64 * 12),
+			Unit::Day if whole => Self::days(amount as i64),
+			Unit::Week if whole => Self::days(amount as i64 * 7),
+			_ => Self::seconds((amount * unit.seconds()? as f64).round() as i64),
+		};
+
+		Some(span)
+	}
+
+	fn months(months: i64) -> Self {
+		Self {
+			months,
+			..Self::default()
+		}
+	}
+
+	fn days(days: i64) -> Self {
+		Self {
+			days,
+			..Self::default()
+		}
+	}
+
+	fn seconds(seconds: i64) -> Self {
+		Self {
+			seconds,
+			..Self::default()
+		}
+	}
+
+	fn plus(self, other: Self) -> Option<Self> {
+		Some(Self {
+			months: self.months.checked_add(other.months)?,
+			days: self.days.checked_add(other.days)?,
+			seconds: self.seconds.checked_add(other.seconds)?,
+		})
+	}
+}
+
+impl Point {
+	/// Adds the span, or subtracts it if `sign` is negative.
+	fn add(self, span: Span, sign: i64) -> Option<Self> {
+		let zone = self.time.timezone();
+		let (months, days) = (span.months * sign, span.days * sign);
+		let naive = self.time.naive_local();
+
+		let naive = match u32::try_from(months.unsigned_abs()).ok().map(Months::new)? {
+			months_abs if months >= 0 => naive.checked_add_months(months_abs)?,
+			months_abs => naive.checked_sub_months(months_abs)?,
+		};
+
+		let naive = match Days::new(days.unsigned_abs()) {
+			days_abs if days >= 0 => naive.checked_add_days(days_abs)?,
+			days_abs => naive.checked_sub_days(days_abs)?,
+		};
+
+		let seconds = TimeDelta::try_seconds(span.seconds * sign)?;
+		let time = localize(zone, naive)?.checked_add_signed(seconds)?;
+
+		// adding hours to a date results in a date and time, adding days to a time too
+		let precision = match self.precision {
+			Precision::Date if span.seconds != 0 => Precision::DateTime,
+			Precision::Time if span.days != 0 || span.months != 0 => Precision::DateTime,
+			precision => precision,
+		};
+
+		Some(Self { time, precision })
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Answer {
+	Point(Point),
+	/// A point converted to another zone, along with the original.
+	Converted {
+		point: Point,
+		original: Point,
+	},
+	/// Difference between two points, in calendar days if both were dates.
+	Difference {
+		delta: TimeDelta,
+		days: Option<i64>,
+	},
+	Timestamp(i64),
+	/// A difference in the requested unit.
+	Amount {
+		value: f64,
+		unit: Unit,
+	},
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+	Zone(Tz),
+	Unix,
+	Unit(Unit),
+}
+
+/// A date that depends on what day it is in the point's zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateSpec {
+	Absolute {
+		year: Option<i32>,
+		month: u32,
+		day: u32,
+	},
+	/// Days from today.
+	Relative(i64),
+	/// The next occurrence of the weekday, including today.
+	Upcoming(Weekday),
+	/// The next occurrence of the weekday, excluding today.
+	Next(Weekday),
+	/// The last occurrence of the weekday, excluding today.
+	Last(Weekday),
+}
+
+impl DateSpec {
+	fn resolve(self, today: NaiveDate) -> Option<NaiveDate> {
+		let days_until = |weekday: Weekday| {
+			(weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64).rem_euclid(7)
+		};
+
+		let offset = match self {
+			Self::Absolute { year, month, day } => {
+				return NaiveDate::from_ymd_opt(year.unwrap_or(today.year()), month, day)
+			}
+			Self::Relative(days) => days,
+			Self::Upcoming(weekday) => days_until(weekday),
+			Self::Next(weekday) => match days_until(weekday) {
+				0 => 7,
+				days => days,
+			},
+			Self::Last(weekday) => days_until(weekday) - 7,
+		};
+
+		match offset >= 0 {
+			true => today.checked_add_days(Days::new(offset as u64)),
+			false => today.checked_sub_days(Days::new(offset.unsigned_abs())),
+		}
+	}
+}
+
+pub fn evaluate(query: &str, context: &Context) -> Option<Answer> {
+	let words = tokenize(query);
+
+	// the target follows the last separator, unless it's part of the expression, like in `14:00 in 3 days`
+	let targeted = (1..words.len())
+		.rev()
+		.filter(|i| SEPARATORS.contains(&words[*i].as_str()))
+		.find_map(|i| {
+			let target = parse_target(&words[i + 1..], context)?;
+			apply_target(parse_expression(&words[..i], context)?, target)
+		});
+
+	targeted.or_else(|| parse_expression(&words, context))
+}
+
+fn tokenize(query: &str) -> Vec<String> {
+	let mut words = vec![];
+
+	for word in query.to_lowercase().split_whitespace() {
+		let word = word.trim_end_matches(',');
+
+		// split signs from amounts, like `+3d`
+		match word.strip_prefix(['+', '-']) {
+			Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => {
+				words.push(word[..1].to_owned());
+				words.push(rest.to_owned());
+			}
+			_ if !word.is_empty() => words.push(word.to_owned()),
+			_ => {}
+		}
+	}
+
+	words
+}
+
+fn parse_target(words: &[String], context: &Context) -> Option<Target> {
+	match words.join(" ").as_str() {
+		"unix" | "timestamp" => Some(Target::Unix),
+		"local" => Some(Target::Zone(context.local)),
+		name => Unit::from_name(name)
+			.map(Target::Unit)
+			.or_else(|| find_zone(name).map(Target::Zone)),
+	}
+}
+
+fn apply_target(answer: Answer, target: Target) -> Option<Answer> {
+	match (answer, target) {
+		(Answer::Point(original), Target::Zone(zone)) => {
+			// a date becomes a date and time, as midnight in one zone isn't in another
+			let precision = match original.precision {
+				Precision::Date => Precision::DateTime,
+				precision => precision,
+			};
+
+			let point = Point {
+				time: original.time.with_timezone(&zone),
+				precision,
+			};
+
+			Some(Answer::Converted { point, original })
+		}
+		(Answer::Point(point), Target::Unix) => Some(Answer::Timestamp(point.time.timestamp())),
+		(Answer::Difference { delta, .. }, Target::Unit(unit)) => {
+			let value = delta.num_milliseconds() as f64 / 1000.0 / unit.seconds()? as f64;
+			Some(Answer::Amount { value, unit })
+		}
+		_ => None,
+	}
+}
+
+fn parse_expression(words: &[String], context: &Context) -> Option<Answer> {
+	let mut parser = Parser {
+		words,
+		position: 0,
+		context,
+	};
+
+	let mut point = parser.parse_start()?;
+
+	while let Some(operator) = parser.next() {
+		let sign = match operator {
+			"+" => 1,
+			"-" => -1,
+			_ => return None,
+		};
+
+		if let Some(span) = parser.attempt(Parser::parse_span) {
+			point = point.add(span, sign)?;
+			continue;
+		}
+
+		// subtracting another point results in the difference, nothing may follow that
+		let other = parser.parse_point().filter(|_| sign < 0 && parser.is_done())?;
+		return Some(difference(point, other));
+	}
+
+	Some(Answer::Point(point))
+}
+
+fn difference(point: Point, other: Point) -> Answer {
+	let days = match (point.precision, other.precision) {
+		(Precision::Date, Precision::Date) => Some((point.time.date_naive() - other.time.date_naive()).num_days()),
+		_ => None,
+	};
+
+	Answer::Difference {
+		delta: point.time - other.time,
+		days,
+	}
+}
+
+/// Resolves the local time in the zone. Times skipped by daylight saving are moved forward by an hour.
+fn localize(zone: Tz, naive: NaiveDateTime) -> Option<DateTime<Tz>> {
+	zone.from_local_datetime(&naive).earliest().or_else(|| {
+		let shifted = naive.checked_add_signed(TimeDelta::try_hours(1)?)?;
+		zone.from_local_datetime(&shifted).earliest()
+	})
+}
+
+struct Parser<'a> {
+	words: &'a [String],
+	position: usize,
+	context: &'a Context,
+}
+
+impl<'a> Parser<'a> {
+	fn next(&mut self) -> Option<&'a str> {
+		let word = self.words.get(self.position)?;
+		self.position += 1;
+
+		Some(word.as_str())
+	}
+
+	/// Consumes the next word if it's the given one.
+	fn next_if(&mut self, expected: &str) -> bool {
+		self.attempt(|p| p.next().filter(|w| *w == expected)).is_some()
+	}
+
+	fn is_done(&self) -> bool {
+		self.position == self.words.len()
+	}
+
+	/// Runs the parse function, rewinding if it fails.
+	fn attempt<T>(&mut self, parse: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+		let start = self.position;
+		let result = parse(self);
+
+		if result.is_none() {
+			self.position = start;
+		}
+
+		result
+	}
+
+	fn now(&self) -> Point {
+		Point {
+			time: self.context.now,
+			precision: Precision::DateTime,
+		}
+	}
+
+	fn parse_start(&mut self) -> Option<Point> {
+		self.attempt(Self::parse_relative).or_else(|| self.parse_point())
+	}
+
+	/// Parses `in 3 days`, `3 days ago` and `3 days from now`.
+	fn parse_relative(&mut self) -> Option<Point> {
+		if self.next_if("in") {
+			return self.now().add(self.parse_span()?, 1);
+		}
+
+		let span = self.parse_span()?;
+
+		match self.next()? {
+			"ago" => self.now().add(span, -1),
+			"from" if self.next_if("now") => self.now().add(span, 1),
+			_ => None,
+		}
+	}
+
+	fn parse_point(&mut self) -> Option<Point> {
+		if self.next_if("now") {
+			return Some(self.now());
+		}
+
+		if let Some(point) = self.attempt(Self::parse_timestamp) {
+			return Some(point);
+		}
+
+		// the date may come before or after the time, like `9am tomorrow`
+		let mut date = self.attempt(Self::parse_date);
+		let time = self.attempt(Self::parse_time);
+
+		if date.is_none() && time.is_some() {
+			date = self.attempt(Self::parse_date);
+		}
+
+		let precision = match (date, time) {
+			(Some(_), Some(_)) => Precision::DateTime,
+			(Some(_), None) => Precision::Date,
+			(None, Some(_)) => Precision::Time,
+			(None, None) => return None,
+		};
+
+		let zone = self.attempt(Self::parse_zone).unwrap_or(self.context.local);
+		let today = self.context.now.with_timezone(&zone).date_naive();
+
+		let date = match date {
+			Some(date) => date.resolve(today)?,
+			None => today,
+		};
+
+		let time = localize(zone, date.and_time(time.unwrap_or(NaiveTime::MIN)))?;
+		Some(Point { time, precision })
+	}
+
+	/// Parses `unix 1700000000` or `@1700000000`, in seconds or milliseconds.
+	fn parse_timestamp(&mut self) -> Option<Point> {
+		let word = self.next()?;
+
+		let number = match word.strip_prefix('@') {
+			Some(number) => number,
+			None if word == "unix" => self.next()?,
+			None => return None,
+		};
+
+		let timestamp = number.parse::<i64>().ok()?;
+
+		let time = match timestamp.abs() > MAX_TIMESTAMP_SECONDS {
+			true => DateTime::from_timestamp(
+				timestamp.div_euclid(1000),
+				timestamp.rem_euclid(1000) as u32 * 1_000_000,
+			)?,
+			false => DateTime::from_timestamp(timestamp, 0)?,
+		};
+
+		Some(Point {
+			time: time.with_timezone(&self.context.local),
+			precision: Precision::DateTime,
+		})
+	}
+
+	fn parse_date(&mut self) -> Option<DateSpec> {
+		let word = self.next()?;
+
+		let spec = match word {
+			"today" => DateSpec::Relative(0),
+			"tomorrow" => DateSpec::Relative(1),
+			"yesterday" => DateSpec::Relative(-1),
+			"next" => DateSpec::Next(parse_weekday(self.next()?)?),
+			"last" => DateSpec::Last(parse_weekday(self.next()?)?),
+			word => match parse_weekday(word).map(DateSpec::Upcoming) {
+				Some(spec) => spec,
+				None => parse_numeric_date(word).or_else(|| self.parse_written_date(word))?,
+			},
+		};
+
+		Some(spec)
+	}
+
+	/// Parses `24 dec 2026` or `dec 24th, 2026`, the year is optional.
+	fn parse_written_date(&mut self, word: &str) -> Option<DateSpec> {
+		let (day, month) = match (parse_day(word), parse_month(word)) {
+			(Some(day), _) => (day, parse_month(self.next()?)?),
+			(_, Some(month)) => (parse_day(self.next()?)?, month),
+			_ => return None,
+		};
+
+		let year = self.attempt(|p| p.next()?.parse::<i32>().ok().filter(|y| (1000..=9999).contains(y)));
+		Some(DateSpec::Absolute { year, month, day })
+	}
+
+	/// Parses `14:00`, `14:00:30`, `2pm`, `2:30 pm`, `noon` or `midnight`.
+	fn parse_time(&mut self) -> Option<NaiveTime> {
+		let word = self.next()?;
+
+		match word {
+			"noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+			"midnight" => return Some(NaiveTime::MIN),
+			_ => {}
+		}
+
+		// the meridiem may be attached or a separate word
+		let (clock, meridiem) = match word.strip_suffix("am").or_else(|| word.strip_suffix("pm")) {
+			Some(clock) => (clock, Some(&word[clock.len()..])),
+			None => (word, self.attempt(|p| p.next().filter(|w| matches!(*w, "am" | "pm")))),
+		};
+
+		let parts = clock
+			.split(':')
+			.map(|p| p.parse::<u32>().ok())
+			.collect::<Option<Vec<_>>>()?;
+
+		// a number alone is no time, `24` could just as well be a day
+		let (hour, minute, second) = match (parts.as_slice(), meridiem) {
+			(&[hour], Some(_)) => (hour, 0, 0),
+			(&[hour, minute], _) => (hour, minute, 0),
+			(&[hour, minute, second], _) => (hour, minute, second),
+			_ => return None,
+		};
+
+		let hour = match meridiem {
+			None => hour,
+			Some(_) if !(1..=12).contains(&hour) => return None,
+			Some("am") => hour % 12,
+			Some(_) => hour % 12 + 12,
+		};
+
+		NaiveTime::from_hms_opt(hour, minute, second)
+	}
+
+	/// Parses a zone, which may have multiple words, like `new york`.
+	fn parse_zone(&mut self) -> Option<Tz> {
+		let words = self.words;
+		let remaining = &words[self.position..];
+
+		let (count, zone) = (1..=remaining.len().min(3)).rev().find_map(|count| {
+			let zone = match remaining[..count].join(" ").as_str() {
+				"local" => self.context.local,
+				name => find_zone(name)?,
+			};
+
+			Some((count, zone))
+		})?;
+
+		self.position += count;
+		Some(zone)
+	}
+
+	/// Parses amounts of time, like `3 days`, `an hour`, `1h30m` or `2 weeks 3 days`.
+	fn parse_span(&mut self) -> Option<Span> {
+		let mut span = self.attempt(Self::parse_span_part)?;
+
+		while let Some(part) = self.attempt(Self::parse_span_part) {
+			span = span.plus(part)?;
+		}
+
+		Some(span)
+	}
+
+	fn parse_span_part(&mut self) -> Option<Span> {
+		let word = self.next()?;
+
+		let amount = match word {
+			"a" | "an" => Some(1.0),
+			word => word.parse::<f64>().ok(),
+		};
+
+		match amount {
+			Some(amount) => Span::new(amount, Unit::from_name(self.next()?)?),
+			None => parse_compact_span(word),
+		}
+	}
+}
+
+/// Parses spans without spaces, like `3d` or `1h30m`.
+fn parse_compact_span(word: &str) -> Option<Span> {
+	let mut span = Span::default();
+	let mut rest = word;
+
+	while !rest.is_empty() {
+		let number_end = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+		let unit_end = rest[number_end..]
+			.find(|c: char| c.is_ascii_digit())
+			.map_or(rest.len(), |i| number_end + i);
+
+		let amount = rest[..number_end].parse().ok()?;
+		let unit = Unit::from_name(&rest[number_end..unit_end])?;
+
+		span = span.plus(Span::new(amount, unit)?)?;
+		rest = &rest[unit_end..];
+	}
+
+	Some(span)
+}
+
+/// Parses `2026-12-24`, `2026/12/24`, `24.12.2026` or `24.12.` for this year.
+fn parse_numeric_date(word: &str) -> Option<DateSpec> {
+	let numbers = |text: &str, separator: char| {
+		text.split(separator)
+			.map(|n| n.parse::<u32>().ok())
+			.collect::<Option<Vec<_>>>()
+	};
+
+	let separator = ['-', '/'].into_iter().find(|s| word.contains(*s));
+
+	let (year, month, day) = match separator {
+		Some(separator) => match *numbers(word, separator)?.as_slice() {
+			[year, month, day] if (1000..=9999).contains(&year) => (Some(year as i32), month, day),
+			_ => return None,
+		},
+		None => match *numbers(word.strip_suffix('.').unwrap_or(word), '.')?.as_slice() {
+			[day, month, year] if (1000..=9999).contains(&year) => (Some(year as i32), month, day),
+			[day, month] if word.ends_with('.') => (None, month, day),
+			_ => return None,
+		},
+	};
+
+	Some(DateSpec::Absolute { year, month, day })
+}
+
+fn parse_day(word: &str) -> Option<u32> {
+	let number = ["st", "nd", "rd", "th"]
+		.iter()
+		.find_map(|s| word.strip_suffix(*s))
+		.unwrap_or(word);
+
+	number.parse().ok().filter(|d| (1..=31).contains(d))
+}
+
+/// Parses month names, which may be shortened to at least three letters.
+fn parse_month(word: &str) -> Option<u32> {
+	if word.len() < 3 {
+		return None;
+	}
+
+	let index = MONTHS.iter().position(|m| m.starts_with(word))?;
+	Some(index as u32 + 1)
+}
+
+/// Parses weekday names, which may be shortened to at least three letters.
+fn parse_weekday(word: &str) -> Option<Weekday> {
+	if word.len() < 3 {
+		return None;
+	}
+
+	WEEKDAYS
+		.iter()
+		.find(|(name, _)| name.starts_with(word))
+		.map(|(_, w)| *w)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("3d", Span::days(3))]
+	#[case("1h30m", Span::seconds(5400))]
+	#[case("2y1mo", Span::months(25))]
+	#[case("1.5h", Span::seconds(5400))]
+	#[case("1.5d", Span::seconds(129600))]
+	#[case("2w", Span::days(14))]
+	fn should_parse_compact_span(#[case] word: &str, #[case] expected: Span) {
+		assert_eq!(Some(expected), parse_compact_span(word), "{word}");
+	}
+
+	#[rstest]
+	#[case("d")]
+	#[case("3")]
+	#[case("3x")]
+	#[case("1.5mo")]
+	#[case("14:00")]
+	#[case("2026-12-24")]
+	fn should_not_parse_compact_span(#[case] word: &str) {
+		assert_eq!(None, parse_compact_span(word), "{word}");
+	}
+
+	#[rstest]
+	#[case("2026-12-24", Some(2026), 12, 24)]
+	#[case("2026/1/2", Some(2026), 1, 2)]
+	#[case("24.12.2026", Some(2026), 12, 24)]
+	#[case("24.12.", None, 12, 24)]
+	fn should_parse_numeric_date(#[case] word: &str, #[case] year: Option<i32>, #[case] month: u32, #[case] day: u32) {
+		assert_eq!(
+			Some(DateSpec::Absolute { year, month, day }),
+			parse_numeric_date(word),
+			"{word}"
+		);
+	}
+
+	#[rstest]
+	#[case("24.12")]
+	#[case("1.5")]
+	#[case("12-24")]
+	#[case("24-12-2026")]
+	#[case("2026-12-24-1")]
+	fn should_not_parse_numeric_date(#[case] word: &str) {
+		assert_eq!(None, parse_numeric_date(word), "{word}");
+	}
+
+	#[rstest]
+	#[case(DateSpec::Upcoming(Weekday::Mon), "2026-10-19")]
+	#[case(DateSpec::Next(Weekday::Mon), "2026-10-26")]
+	#[case(DateSpec::Last(Weekday::Mon), "2026-10-12")]
+	#[case(DateSpec::Upcoming(Weekday::Sun), "2026-10-25")]
+	#[case(DateSpec::Last(Weekday::Sun), "2026-10-18")]
+	#[case(DateSpec::Relative(-1), "2026-10-18")]
+	fn should_resolve_date(#[case] spec: DateSpec, #[case] expected: &str) {
+		let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+		assert_eq!(Some(expected.parse::<NaiveDate>().unwrap()), spec.resolve(today));
+	}
+}
@@ -0,0 +1,116 @@
+//! Lookup of time zones by IANA name, city or abbreviation.
+
+use chrono_tz::{Tz, TZ_VARIANTS};
+
+/// Common abbreviations. Daylight saving variants like `PDT` are fixed
+/// offsets, so `14:00 PST` means the same time all year.
+///
+/// Note that the `Etc/GMT` zones have inverted signs, `Etc/GMT+8` is UTC-8.
+const ABBREVIATIONS: &[(&str, &str)] = &[
+	("utc", "UTC"),
+	("gmt", "GMT"),
+	("z", "UTC"),
+	("pst", "Etc/GMT+8"),
+	("pdt", "Etc/GMT+7"),
+	("mst", "Etc/GMT+7"),
+	("mdt", "Etc/GMT+6"),
+	("cst", "Etc/GMT+6"),
+	("cdt", "Etc/GMT+5"),
+	("est", "Etc/GMT+5"),
+	("edt", "Etc/GMT+4"),
+	("wet", "Etc/GMT"),
+	("west", "Etc/GMT-1"),
+	("bst", "Etc/GMT-1"),
+	("cet", "Etc/GMT-1"),
+	("cest", "Etc/GMT-2"),
+	("eet", "Etc/GMT-2"),
+	("eest", "Etc/GMT-3"),
+	("msk", "Europe/Moscow"),
+	("ist", "Asia/Kolkata"),
+	("jst", "Asia/Tokyo"),
+	("kst", "Asia/Seoul"),
+	("aest", "Etc/GMT-10"),
+	("aedt", "Etc/GMT-11"),
+	("nzst", "Etc/GMT-12"),
+	("nzdt", "Etc/GMT-13"),
+];
+
+/// Finds a zone by IANA name like `Europe/Berlin`, city like `berlin` or
+/// `new york`, abbreviation like `PST`, or offset like `UTC+2`. Ignores case.
+pub fn find_zone(name: &str) -> Option<Tz> {
+	let name = name.trim().to_lowercase();
+
+	if let Some((_, zone)) = ABBREVIATIONS.iter().find(|(a, _)| *a == name) {
+		return zone.parse().ok();
+	}
+
+	if let Some(zone) = parse_offset(&name) {
+		return Some(zone);
+	}
+
+	let name = name.replace(' ', "_");
+
+	TZ_VARIANTS
+		.iter()
+		.find(|zone| zone.name().eq_ignore_ascii_case(&name))
+		.or_else(|| {
+			TZ_VARIANTS
+				.iter()
+				.find(|zone| get_city(zone).eq_ignore_ascii_case(&name))
+		})
+		.copied()
+}
+
+/// Parses offsets in whole hours, like `utc+2` or `gmt-5`.
+fn parse_offset(name: &str) -> Option<Tz> {
+	let offset = name.strip_prefix("utc").or_else(|| name.strip_prefix("gmt"))?;
+	let (sign, hours) = offset.split_at(offset.find(|c: char| c != '+' && c != '-')?);
+	let hours = hours.parse::<u8>().ok().filter(|h| *h <= 14)?;
+
+	// the Etc zones are inverted, and there is no `Etc/GMT-0`
+	let zone = match (sign, hours) {
+		(_, 0) => "Etc/GMT".to_owned(),
+		("+", hours) => format!("Etc/GMT-{hours}"),
+		("-", hours) => format!("Etc/GMT+{hours}"),
+		_ => return None,
+	};
+
+	zone.parse().ok()
+}
+
+/// Returns the last part of the zone's name, like `New_York` for `America/New_York`.
+fn get_city(zone: &Tz) -> &'static str {
+	zone.name().rsplit('/').next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	#[rstest]
+	#[case("Europe/Berlin", "Europe/Berlin")]
+	#[case("europe/berlin", "Europe/Berlin")]
+	#[case("berlin", "Europe/Berlin")]
+	#[case("new york", "America/New_York")]
+	#[case("Los_Angeles", "America/Los_Angeles")]
+	#[case("PST", "Etc/GMT+8")]
+	#[case("cest", "Etc/GMT-2")]
+	#[case("utc", "UTC")]
+	#[case("UTC+2", "Etc/GMT-2")]
+	#[case("gmt-5", "Etc/GMT+5")]
+	#[case("utc+0", "Etc/GMT")]
+	fn should_find_zone(#[case] name: &str, #[case] expected: &str) {
+		assert_eq!(Some(expected), find_zone(name).map(|z| z.name()), "{name}");
+	}
+
+	#[rstest]
+	#[case("")]
+	#[case("atlantis")]
+	#[case("utc+15")]
+	#[case("utc2")]
+	#[case("utc+-2")]
+	fn should_not_find_zone(#[case] name: &str) {
+		assert_eq!(None, find_zone(name), "{name}");
+	}
+}
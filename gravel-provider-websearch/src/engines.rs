@@ -0,0 +1,144 @@
+//! Search engines selected with bang-style shortcuts like `!gh`.
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+/// Placeholder in DuckDuckGo's bang URLs.
+const BANG_PLACEHOLDER: &str = "{{{s}}}";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Engine {
+	pub name: String,
+	#[serde(default)]
+	pub shortcuts: Vec<String>,
+	pub url_pattern: String,
+}
+
+/// Entry of a DuckDuckGo-style bang list.
+#[derive(Deserialize, Debug)]
+struct Bang {
+	/// Shortcut, without the `!`.
+	t: String,
+	/// Name of the site.
+	s: String,
+	/// URL, with the query in place of `{{{s}}}`.
+	u: String,
+}
+
+/// Engines by their shortcuts.
+#[derive(Default)]
+pub struct Engines {
+	shortcuts: HashMap<String, Arc<Engine>>,
+}
+
+impl Engines {
+	/// Adds the engines, replacing existing ones with the same shortcuts.
+	pub fn add(&mut self, engines: impl IntoIterator<Item = Engine>) {
+		for engine in engines {
+			let engine = Arc::new(engine);
+
+			for shortcut in &engine.shortcuts {
+				self.shortcuts.insert(shortcut.to_lowercase(), engine.clone());
+			}
+		}
+	}
+
+	pub fn get(&self, shortcut: &str) -> Option<Arc<Engine>> {
+		self.shortcuts.get(&shortcut.to_lowercase()).cloned()
+	}
+
+	/// Splits off a known shortcut from the start or end of the query, like
+	/// `!gh gravel` or `gravel !gh`, returning the engine and the remaining query.
+	pub fn split_query<'a>(&self, query: &'a str) -> Option<(Arc<Engine>, &'a str)> {
+		let query = query.trim();
+
+		let (bang, rest) = match query.split_once(char::is_whitespace) {
+			Some((first, rest)) if first.starts_with('!') => (first, rest),
+			_ => match query.rsplit_once(char::is_whitespace) {
+				Some((rest, last)) if last.starts_with('!') => (last, rest),
+				_ => (query, ""),
+			},
+		};
+
+		let engine = self.get(bang.strip_prefix('!')?)?;
+		Some((engine, rest.trim()))
+	}
+}
+
+/// Reads engines from a DuckDuckGo-style bang list, which is a JSON array of
+/// objects like `{ "t": "gh", "s": "GitHub", "u": "https://github.com/search?q={{{s}}}" }`.
+pub fn read_bangs(path: &Path) -> Result<Vec<Engine>> {
+	parse_bangs(&fs::read(path)?)
+}
+
+fn parse_bangs(json: &[u8]) -> Result<Vec<Engine>> {
+	let bangs = serde_json::from_slice::<Vec<Bang>>(json)?;
+
+	let engines = bangs
+		.into_iter()
+		.filter(|bang| bang.u.contains(BANG_PLACEHOLDER))
+		.map(|bang| Engine {
+			name: bang.s,
+			shortcuts: vec![bang.t],
+			url_pattern: bang.u.replace(BANG_PLACEHOLDER, "{}"),
+		})
+		.collect();
+
+	Ok(engines)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+
+	fn get_engines() -> Engines {
+		let mut engines = Engines::default();
+
+		engines.add([Engine {
+			name: String::from("GitHub"),
+			shortcuts: vec![String::from("gh"), String::from("github")],
+			url_pattern: String::from("https://github.com/search?q={}"),
+		}]);
+
+		engines
+	}
+
+	#[rstest]
+	#[case("!gh gravel", "gravel")]
+	#[case("gravel launcher !gh", "gravel launcher")]
+	#[case("!GitHub  gravel ", "gravel")]
+	#[case("!gh", "")]
+	fn should_split_query(#[case] query: &str, #[case] expected: &str) {
+		let actual = get_engines().split_query(query).map(|(e, q)| (e.name.clone(), q));
+		assert_eq!(Some((String::from("GitHub"), expected)), actual, "{query}");
+	}
+
+	#[rstest]
+	#[case("gravel")]
+	#[case("!w gravel")]
+	#[case("gravel !gh launcher")]
+	#[case("gh gravel")]
+	#[case("")]
+	fn should_not_split_query(#[case] query: &str) {
+		assert!(get_engines().split_query(query).is_none(), "{query}");
+	}
+
+	#[test]
+	fn should_parse_bangs() {
+		let json = br#"[
+			{ "t": "w", "s": "Wikipedia", "u": "https://en.wikipedia.org/wiki/Special:Search?search={{{s}}}", "d": "en.wikipedia.org" },
+			{ "t": "home", "s": "Home", "u": "https://example.com" }
+		]"#;
+
+		let engines = parse_bangs(json).unwrap();
+
+		assert_eq!(1, engines.len());
+		assert_eq!(vec![String::from("w")], engines[0].shortcuts);
+		assert_eq!(
+			"https://en.wikipedia.org/wiki/Special:Search?search={}",
+			engines[0].url_pattern
+		);
+	}
+}
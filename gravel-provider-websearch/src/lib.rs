@@ -2,10 +2,32 @@
 //!
 //! Always returns a hit with the minimum score that, when selected,
 //! opens the user's default browser and searches for the query.
+//!
+//! Other search engines can be chosen with bang-style shortcuts, like
+//! `!gh gravel` or `gravel !gh`. The hit then gets the maximum score instead.
+//! Engines are configured in a table, and can additionally be imported from
+//! a DuckDuckGo-style bang list.
+//!
+//! Optionally, search suggestions are fetched from a configurable endpoint
+//! and shown below the hit. They're fetched in the background, so typing is
+//! never blocked by slow responses.
 
-use gravel_core::{config::PluginConfigAdapter, plugin::*, scoring::MIN_SCORE, *};
+use engines::{Engine, Engines};
+use gravel_core::{
+	config::PluginConfigAdapter,
+	plugin::*,
+	scoring::{MAX_SCORE, MIN_SCORE},
+	*,
+};
 use serde::Deserialize;
-use std::sync::{mpsc::Sender, Arc};
+use std::{
+	path::PathBuf,
+	sync::{mpsc::Sender, Arc},
+};
+use suggestions::{Suggester, SuggestionsConfig};
+
+mod engines;
+mod suggestions;
 
 const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
 
@@ -17,26 +39,108 @@ pub fn register_plugins(registry: &mut PluginRegistry) {
 
 fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
 	let config = config_adapter.get::<Config>(DEFAULT_CONFIG);
+	let mut engines = Engines::default();
 
-	// this avoids a clone on every keystroke
-	let url_pattern = Box::leak(Box::new(config.url_pattern.clone()));
+	// configured engines take precedence over imported ones
+	if let Some(path) = config.bangs_file.as_deref().and_then(expand_path) {
+		match engines::read_bangs(&path) {
+			Ok(bangs) => engines.add(bangs),
+			Err(err) => log::error!("unable to read bangs from '{}': {err}", path.display()),
+		}
+	}
+
+	engines.add(config.engines.iter().cloned());
 
-	let provider = WebsearchProvider { config, url_pattern };
+	let default_engine = get_default_engine(&config, &engines);
+	let provider = WebsearchProvider {
+		suggester: Suggester::new(config.suggestions.clone()),
+		config,
+		engines,
+		default_engine,
+	};
 
 	Box::new(provider)
 }
 
+/// Returns the engine named by `default_engine`, falling back to `url_pattern`.
+fn get_default_engine(config: &Config, engines: &Engines) -> Arc<Engine> {
+	let engine = config.default_engine.as_deref().and_then(|shortcut| {
+		let engine = engines.get(shortcut);
+
+		if engine.is_none() {
+			log::error!("default engine '{shortcut}' not found, using url_pattern");
+		}
+
+		engine
+	});
+
+	engine.unwrap_or_else(|| {
+		Arc::new(Engine {
+			name: String::from("default"),
+			shortcuts: vec![],
+			url_pattern: config.url_pattern.clone(),
+		})
+	})
+}
+
+fn expand_path(path: &str) -> Option<PathBuf> {
+	shellexpand::full(path)
+		.map(|p| PathBuf::from(&*p))
+		.map_err(|err| log::error!("couldn't expand bangs_file '{path}': {err}"))
+		.ok()
+}
+
 pub struct WebsearchProvider {
 	config: Config,
-	url_pattern: &'static str,
+	engines: Engines,
+	default_engine: Arc<Engine>,
+	suggester: Suggester,
+}
+
+impl WebsearchProvider {
+	fn get_hit(&self, engine: Arc<Engine>, query: &str, subtitle: &str, score: u32) -> Arc<dyn Hit> {
+		let hit = SimpleHit::new(query, subtitle, move |h, s| do_search(&engine.url_pattern, h, s));
+
+		Arc::new(hit.with_score(score))
+	}
 }
 
 impl Provider for WebsearchProvider {
 	fn query(&self, query: &str) -> ProviderResult {
-		let hit = SimpleHit::new(query, &*self.config.subtitle, |h, s| do_search(self.url_pattern, h, s))
-			.with_score(MIN_SCORE);
+		let (engine, query, subtitle, chosen) = match self.engines.split_query(query) {
+			Some((engine, query)) => {
+				let subtitle = format!("{} - {}", self.config.subtitle, engine.name);
+				(engine, query, subtitle, true)
+			}
+			None => (self.default_engine.clone(), query, self.config.subtitle.clone(), false),
+		};
+
+		let suggestions = match query.trim() {
+			"" => vec![],
+			query => self.suggester.get(query),
+		};
+
+		// with a shortcut, the hits go to the top of the list, otherwise to the bottom
+		let top_score = match chosen {
+			true => MAX_SCORE,
+			false => MIN_SCORE + suggestions.len() as u32,
+		};
+
+		let suggestion_hits = suggestions
+			.iter()
+			.enumerate()
+			.map(|(i, suggestion)| self.get_hit(engine.clone(), suggestion, &subtitle, top_score - 1 - i as u32));
+
+		let hits = [self.get_hit(engine.clone(), query, &subtitle, top_score)]
+			.into_iter()
+			.chain(suggestion_hits)
+			.collect();
+
+		ProviderResult::new(hits)
+	}
 
-		ProviderResult::single(Arc::new(hit))
+	fn set_sender(&mut self, sender: Sender<FrontendMessage>) {
+		self.suggester.start(sender);
 	}
 }
 
@@ -56,4 +160,8 @@ fn do_search(url_pattern: &str, hit: &SimpleHit, sender: &Sender<FrontendMessage
 struct Config {
 	pub url_pattern: String,
 	pub subtitle: String,
+	pub engines: Vec<Engine>,
+	pub default_engine: Option<String>,
+	pub bangs_file: Option<String>,
+	pub suggestions: SuggestionsConfig,
 }
@@ -0,0 +1,305 @@
+//! Search suggestions from an endpoint in the OpenSearch suggestions format,
+//! like `["query", ["suggestion", ...]]`.
+//!
+//! Suggestions are fetched on a separate thread, once typing has paused for
+//! the debounce duration. When they arrive, the frontend is asked to query
+//! again, which then returns them.
+
+use anyhow::{bail, Result};
+use gravel_core::FrontendMessage;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+	cell::OnceCell,
+	sync::{
+		mpsc::{self, Receiver, RecvTimeoutError, Sender},
+		Arc, Mutex,
+	},
+	thread,
+	time::Duration,
+};
+use ureq::Agent;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SuggestionsConfig {
+	pub url: Option<String>,
+	pub max_hits: usize,
+	pub debounce: u64,
+	pub timeout: u64,
+}
+
+#[derive(Default)]
+struct State {
+	/// The latest query suggestions were requested for.
+	requested: String,
+	/// The query the current suggestions are for.
+	query: String,
+	suggestions: Vec<String>,
+}
+
+pub struct Suggester {
+	config: SuggestionsConfig,
+	state: Arc<Mutex<State>>,
+	requests: OnceCell<Sender<String>>,
+}
+
+impl Suggester {
+	pub fn new(config: SuggestionsConfig) -> Self {
+		Self {
+			config,
+			state: Arc::new(Mutex::new(State::default())),
+			requests: OnceCell::new(),
+		}
+	}
+
+	/// Spawns the thread fetching suggestions, if a URL is configured.
+	pub fn start(&self, frontend: Sender<FrontendMessage>) {
+		let Some(url_pattern) = self.config.url.clone() else {
+			return;
+		};
+
+		let (sender, receiver) = mpsc::channel();
+		let state = self.state.clone();
+		let config = self.config.clone();
+
+		thread::spawn(move || run(&receiver, &state, &config, &url_pattern, &frontend));
+		self.requests.set(sender).ok();
+	}
+
+	/// Returns the suggestions for the query if they have arrived,
+	/// otherwise requests them and returns none for now.
+	pub fn get(&self, query: &str) -> Vec<String> {
+		let Some(requests) = self.requests.get() else {
+			return vec![];
+		};
+
+		let mut state = self.state.lock().expect("thread holding the mutex can't panic");
+
+		if state.query == query {
+			return state.suggestions.clone();
+		}
+
+		// the frontend queries again once they arrive, so only request them once
+		if state.requested != query {
+			state.requested = query.to_owned();
+			requests.send(query.to_owned()).ok();
+		}
+
+		vec![]
+	}
+}
+
+fn run(
+	receiver: &Receiver<String>,
+	state: &Mutex<State>,
+	config: &SuggestionsConfig,
+	url_pattern: &str,
+	frontend: &Sender<FrontendMessage>,
+) {
+	let agent = ureq::AgentBuilder::new()
+		.timeout(Duration::from_millis(config.timeout))
+		.build();
+
+	while let Ok(mut query) = receiver.recv() {
+		// wait for typing to pause, only fetching the latest query
+		loop {
+			match receiver.recv_timeout(Duration::from_millis(config.debounce)) {
+				Ok(newer) => query = newer,
+				Err(RecvTimeoutError::Timeout) => break,
+				Err(RecvTimeoutError::Disconnected) => return,
+			}
+		}
+
+		let mut suggestions = fetch(&agent, url_pattern, &query).unwrap_or_else(|err| {
+			log::warn!("unable to fetch suggestions for '{query}': {err}");
+			vec![]
+		});
+
+		suggestions.retain(|s| *s != query);
+		suggestions.truncate(config.max_hits);
+
+		let mut state = state.lock().expect("thread holding the mutex can't panic");
+
+		// the query changed while fetching, so these are outdated
+		if state.requested != query {
+			continue;
+		}
+
+		let changed = !suggestions.is_empty();
+		state.query = query;
+		state.suggestions = suggestions;
+		drop(state);
+
+		if changed {
+			frontend.send(FrontendMessage::Refresh).ok();
+		}
+	}
+}
+
+fn fetch(agent: &Agent, url_pattern: &str, query: &str) -> Result<Vec<String>> {
+	let url = url_pattern.replace("{}", &urlencoding::encode(query));
+	log::debug!("fetching suggestions from '{url}'");
+
+	let body = agent.get(&url).call()?.into_string()?;
+	parse_suggestions(&body)
+}
+
+fn parse_suggestions(body: &str) -> Result<Vec<String>> {
+	let response = serde_json::from_str::<Vec<Value>>(body)?;
+
+	let Some(Value::Array(suggestions)) = response.get(1) else {
+		bail!("expected a response like `[\"query\", [\"suggestion\", ...]]`");
+	};
+
+	let suggestions = suggestions
+		.iter()
+		.filter_map(Value::as_str)
+		.map(str::to_owned)
+		.collect();
+
+	Ok(suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+	use std::io::{BufRead, BufReader, Write};
+	use std::net::{TcpListener, TcpStream};
+	use std::time::Instant;
+
+	/// Queries starting with this are answered after [`SLOW_DELAY`].
+	const SLOW: &str = "slow";
+	const SLOW_DELAY: Duration = Duration::from_millis(500);
+
+	/// Starts a server suggesting `"{query} suggestion"` for every query.
+	/// Returns the URL pattern and the queries requested so far.
+	fn start_server() -> (String, Arc<Mutex<Vec<String>>>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/?q={{}}", listener.local_addr().unwrap());
+		let requested = Arc::new(Mutex::new(vec![]));
+
+		let queries = requested.clone();
+		thread::spawn(move || {
+			for stream in listener.incoming().flatten() {
+				let queries = queries.clone();
+				thread::spawn(move || respond(stream, &queries));
+			}
+		});
+
+		(url, requested)
+	}
+
+	fn respond(mut stream: TcpStream, queries: &Mutex<Vec<String>>) {
+		let mut request_line = String::new();
+		BufReader::new(&stream).read_line(&mut request_line).unwrap();
+
+		let encoded = request_line.split(' ').nth(1).unwrap().trim_start_matches("/?q=");
+		let query = urlencoding::decode(encoded).unwrap().into_owned();
+		queries.lock().unwrap().push(query.clone());
+
+		if query.starts_with(SLOW) {
+			thread::sleep(SLOW_DELAY);
+		}
+
+		let body = serde_json::json!([query, [format!("{query} suggestion")]]).to_string();
+		let response = format!(
+			"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+			body.len()
+		);
+
+		stream.write_all(response.as_bytes()).ok();
+	}
+
+	fn start_suggester(url: String, timeout: u64) -> (Suggester, Receiver<FrontendMessage>) {
+		let config = SuggestionsConfig {
+			url: Some(url),
+			max_hits: 5,
+			debounce: 100,
+			timeout,
+		};
+
+		let (sender, receiver) = mpsc::channel();
+		let suggester = Suggester::new(config);
+		suggester.start(sender);
+
+		(suggester, receiver)
+	}
+
+	fn wait_for_refresh(receiver: &Receiver<FrontendMessage>, timeout: Duration) -> bool {
+		matches!(receiver.recv_timeout(timeout), Ok(FrontendMessage::Refresh))
+	}
+
+	fn get_state(suggester: &Suggester) -> (String, Vec<String>) {
+		let state = suggester.state.lock().unwrap();
+		(state.query.clone(), state.suggestions.clone())
+	}
+
+	#[test]
+	fn should_fetch_latest_query_after_debounce() {
+		let (url, requested) = start_server();
+		let (suggester, receiver) = start_suggester(url, 2000);
+
+		let start = Instant::now();
+		for query in ["g", "gr", "gra"] {
+			assert!(suggester.get(query).is_empty());
+		}
+
+		assert!(wait_for_refresh(&receiver, Duration::from_secs(2)));
+		assert!(start.elapsed() >= Duration::from_millis(100));
+
+		assert_eq!(vec!["gra suggestion"], suggester.get("gra"));
+		assert_eq!(vec!["gra"], *requested.lock().unwrap());
+	}
+
+	#[test]
+	fn should_drop_stale_responses() {
+		let (url, requested) = start_server();
+		let (suggester, receiver) = start_suggester(url, 2000);
+
+		suggester.get("slow query");
+
+		// wait for the slow request to be sent, then change the query while it's pending
+		while requested.lock().unwrap().is_empty() {
+			thread::sleep(Duration::from_millis(10));
+		}
+
+		suggester.get("other");
+
+		assert!(wait_for_refresh(&receiver, Duration::from_secs(2)));
+		assert_eq!(
+			("other".to_owned(), vec!["other suggestion".to_owned()]),
+			get_state(&suggester)
+		);
+		assert!(!wait_for_refresh(&receiver, Duration::from_millis(200)));
+	}
+
+	#[test]
+	fn should_time_out() {
+		let (url, requested) = start_server();
+		let (suggester, receiver) = start_suggester(url, 100);
+
+		suggester.get("slow query");
+
+		// the response would have arrived by now, had the request not timed out
+		assert!(!wait_for_refresh(&receiver, SLOW_DELAY * 2));
+		assert_eq!(vec!["slow query"], *requested.lock().unwrap());
+		assert_eq!(("slow query".to_owned(), vec![]), get_state(&suggester));
+	}
+
+	#[rstest]
+	#[case(r#"["gra", ["gravel", "gravy"]]"#, &["gravel", "gravy"])]
+	#[case(r#"["gra", ["gravel"], ["description"], ["https://example.com"]]"#, &["gravel"])]
+	#[case(r#"["gra", []]"#, &[])]
+	fn should_parse_suggestions(#[case] body: &str, #[case] expected: &[&str]) {
+		assert_eq!(expected, parse_suggestions(body).unwrap());
+	}
+
+	#[rstest]
+	#[case(r#"["gra"]"#)]
+	#[case(r#"{ "suggestions": [] }"#)]
+	#[case("gravel")]
+	fn should_not_parse_suggestions(#[case] body: &str) {
+		assert!(parse_suggestions(body).is_err(), "{body}");
+	}
+}
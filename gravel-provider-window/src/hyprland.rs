@@ -0,0 +1,171 @@
+//! Backend for Hyprland, using its request socket.
+//! https://wiki.hyprland.org/IPC/
+
+use crate::{Backend, Window};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::env;
+use std::io::{Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+pub struct HyprlandBackend {
+	socket: PathBuf,
+}
+
+impl HyprlandBackend {
+	/// Finds the socket of the instance given in $HYPRLAND_INSTANCE_SIGNATURE.
+	pub fn from_env() -> Result<Self> {
+		let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE").context("HYPRLAND_INSTANCE_SIGNATURE is not set")?;
+
+		// newer versions put the socket into the runtime dir, older ones into /tmp
+		let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+		let socket = [runtime_dir, Some(PathBuf::from("/tmp"))]
+			.into_iter()
+			.flatten()
+			.map(|dir| dir.join("hypr").join(&signature).join(".socket.sock"))
+			.find(|path| path.exists())
+			.context("unable to find Hyprland's socket")?;
+
+		Ok(Self::new(socket))
+	}
+
+	pub fn new(socket: PathBuf) -> Self {
+		Self { socket }
+	}
+
+	fn request(&self, command: &str) -> Result<String> {
+		let mut stream = UnixStream::connect(&self.socket)
+			.with_context(|| format!("unable to connect to {}", self.socket.display()))?;
+
+		stream.write_all(command.as_bytes())?;
+		stream.shutdown(Shutdown::Write)?;
+
+		let mut response = String::new();
+		stream.read_to_string(&mut response)?;
+
+		Ok(response)
+	}
+}
+
+impl Backend for HyprlandBackend {
+	fn windows(&self) -> Result<Vec<Window>> {
+		let clients = serde_json::from_str::<Vec<Client>>(&self.request("j/clients")?)?;
+
+		let windows = clients
+			.into_iter()
+			.filter(|c| c.mapped)
+			.filter_map(|c| {
+				Some(Window {
+					id: parse_address(&c.address)?,
+					title: c.title,
+					class: c.class,
+				})
+			})
+			.collect();
+
+		Ok(windows)
+	}
+
+	fn activate(&self, id: u64) -> Result<()> {
+		let response = self.request(&format!("dispatch focuswindow address:{id:#x}"))?;
+
+		match response.trim() {
+			"ok" => Ok(()),
+			error => bail!("{error}"),
+		}
+	}
+}
+
+/// Parses window addresses, which are formatted like `0x55d3f2c1a0b0`.
+fn parse_address(address: &str) -> Option<u64> {
+	let hex = address.strip_prefix("0x")?;
+
+	u64::from_str_radix(hex, 16).ok()
+}
+
+#[derive(Deserialize, Debug)]
+struct Client {
+	address: String,
+	mapped: bool,
+	class: String,
+	title: String,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+	use std::os::unix::net::UnixListener;
+	use std::thread::{self, JoinHandle};
+	use tempfile::TempDir;
+
+	const CLIENTS: &str = r#"[
+		{ "address": "0x55d3f2c1a0b0", "mapped": true, "hidden": false, "class": "kitty", "title": "vim", "pid": 1234 },
+		{ "address": "0x55d3f2c1b4e0", "mapped": false, "hidden": false, "class": "", "title": "", "pid": 1235 },
+		{ "address": "0x55d3f2c1c910", "mapped": true, "hidden": false, "class": "firefox", "title": "Mozilla Firefox", "pid": 1236 }
+	]"#;
+
+	/// Pretends to be Hyprland, answering a single request. Returns the request.
+	/// The socket is removed along with the returned directory.
+	fn serve(response: &'static str) -> (TempDir, PathBuf, JoinHandle<String>) {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("hyprland.sock");
+		let listener = UnixListener::bind(&path).unwrap();
+
+		let handle = thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+
+			let mut request = String::new();
+			stream.read_to_string(&mut request).unwrap();
+			stream.write_all(response.as_bytes()).unwrap();
+
+			request
+		});
+
+		(dir, path, handle)
+	}
+
+	#[rstest]
+	#[case("0x55d3f2c1a0b0", Some(0x55d3f2c1a0b0))]
+	#[case("0x0", Some(0))]
+	#[case("55d3f2c1a0b0", None)]
+	#[case("0xnope", None)]
+	fn should_parse_address(#[case] address: &str, #[case] expected: Option<u64>) {
+		assert_eq!(expected, parse_address(address));
+	}
+
+	#[test]
+	fn should_list_windows() {
+		let (_dir, path, handle) = serve(CLIENTS);
+		let windows = HyprlandBackend::new(path).windows().unwrap();
+
+		assert_eq!("j/clients", handle.join().unwrap());
+
+		let titles = windows.iter().map(|w| (w.id, &*w.title, &*w.class)).collect::<Vec<_>>();
+		let expected = [
+			(0x55d3f2c1a0b0, "vim", "kitty"),
+			(0x55d3f2c1c910, "Mozilla Firefox", "firefox"),
+		];
+
+		assert_eq!(&expected[..], titles);
+	}
+
+	#[test]
+	fn should_focus_window() {
+		let (_dir, path, handle) = serve("ok");
+		HyprlandBackend::new(path).activate(0x55d3f2c1a0b0).unwrap();
+
+		assert_eq!("dispatch focuswindow address:0x55d3f2c1a0b0", handle.join().unwrap());
+	}
+
+	#[test]
+	fn should_report_dispatch_error() {
+		let (_dir, path, handle) = serve("No such window found");
+		let result = HyprlandBackend::new(path).activate(0x1);
+		handle.join().unwrap();
+
+		assert_eq!("No such window found", result.unwrap_err().to_string());
+	}
+}
@@ -0,0 +1,145 @@
+//! Window switcher.
+//! Lists open windows and brings the selected one to the front.
+//!
+//! Supports X11 window managers implementing EWMH, as well as sway and
+//! Hyprland through their IPC sockets. The backend is detected from the
+//! environment by default.
+
+use anyhow::Result;
+use gravel_core::{config::PluginConfigAdapter, plugin::*, *};
+use serde::Deserialize;
+use std::sync::{mpsc::Sender, Arc};
+
+#[cfg(unix)]
+mod hyprland;
+#[cfg(unix)]
+mod sway;
+#[cfg(unix)]
+mod x11;
+
+const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/config.yml"));
+
+/// WM_CLASS of gravel's own window, which is never listed.
+const OWN_CLASS: &str = "gravel";
+
+pub fn register_plugins(registry: &mut PluginRegistry) {
+	let definition = plugin("window").with_provider(Box::new(get_provider));
+
+	registry.register(definition);
+}
+
+fn get_provider(config_adapter: &PluginConfigAdapter) -> Box<dyn Provider> {
+	let config = config_adapter.get::<Config>(DEFAULT_CONFIG);
+
+	let backend = get_backend(config.backend)
+		.map_err(|err| log::error!("unable to initialize window backend: {err:#}"))
+		.ok()
+		.map(Arc::from);
+
+	Box::new(WindowProvider { config, backend })
+}
+
+/// Source of windows, such as the X server or a wayland compositor.
+pub trait Backend: Send + Sync {
+	/// Returns all top-level windows.
+	fn windows(&self) -> Result<Vec<Window>>;
+
+	/// Focuses the window, switching to its workspace if needed.
+	fn activate(&self, id: u64) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window {
+	/// Backend-specific identifier.
+	pub id: u64,
+	pub title: String,
+	/// WM_CLASS on X11, app ID on wayland.
+	pub class: String,
+}
+
+pub struct WindowProvider {
+	config: Config,
+	backend: Option<Arc<dyn Backend>>,
+}
+
+impl Provider for WindowProvider {
+	fn query(&self, _query: &str) -> ProviderResult {
+		let Some(backend) = &self.backend else {
+			return ProviderResult::empty();
+		};
+
+		let windows = match backend.windows() {
+			Ok(windows) => windows,
+			Err(err) => {
+				log::error!("unable to list windows: {err:#}");
+				return ProviderResult::empty();
+			}
+		};
+
+		let hits = windows
+			.into_iter()
+			.filter(|w| w.class != OWN_CLASS)
+			.map(|w| get_hit(&self.config, backend.clone(), w))
+			.collect();
+
+		ProviderResult::new(hits)
+	}
+}
+
+fn get_hit(config: &Config, backend: Arc<dyn Backend>, window: Window) -> Arc<dyn Hit> {
+	let subtitle = config.subtitle.replace("{class}", &window.class);
+
+	let hit = SimpleHit::new(window.title, subtitle, move |_, s| activate(&*backend, window.id, s));
+	Arc::new(hit)
+}
+
+fn activate(backend: &dyn Backend, id: u64, sender: &Sender<FrontendMessage>) {
+	log::debug!("activating window {id:#x}");
+
+	// hide first, otherwise gravel may take the focus right back
+	sender.send(FrontendMessage::Hide).ok();
+
+	if let Err(err) = backend.activate(id) {
+		log::error!("unable to activate window {id:#x}: {err:#}");
+	}
+}
+
+#[cfg(unix)]
+fn get_backend(kind: BackendKind) -> Result<Box<dyn Backend>> {
+	use std::env;
+
+	let kind = match kind {
+		BackendKind::Auto if env::var_os("SWAYSOCK").is_some() => BackendKind::Sway,
+		BackendKind::Auto if env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() => BackendKind::Hyprland,
+		BackendKind::Auto => BackendKind::X11,
+		kind => kind,
+	};
+
+	log::debug!("using {kind:?} window backend");
+
+	Ok(match kind {
+		BackendKind::Sway => Box::new(sway::SwayBackend::from_env()?),
+		BackendKind::Hyprland => Box::new(hyprland::HyprlandBackend::from_env()?),
+		_ => Box::new(x11::X11Backend::connect()?),
+	})
+}
+
+#[cfg(windows)]
+fn get_backend(_kind: BackendKind) -> Result<Box<dyn Backend>> {
+	Err(anyhow::anyhow!("listing windows is not supported on Windows yet"))
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+	pub backend: BackendKind,
+	pub subtitle: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum BackendKind {
+	Auto,
+	X11,
+	Sway,
+	Hyprland,
+}
@@ -0,0 +1,221 @@
+//! Backend for sway, using its i3-compatible IPC protocol.
+//! https://man.archlinux.org/man/sway-ipc.7
+
+use crate::{Backend, Window};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+const HEADER_LEN: usize = MAGIC.len() + 8;
+
+const RUN_COMMAND: u32 = 0;
+const GET_TREE: u32 = 4;
+
+pub struct SwayBackend {
+	socket: PathBuf,
+}
+
+impl SwayBackend {
+	/// Uses the socket given in $SWAYSOCK.
+	pub fn from_env() -> Result<Self> {
+		let socket = std::env::var_os("SWAYSOCK").context("SWAYSOCK is not set")?;
+
+		Ok(Self::new(socket.into()))
+	}
+
+	pub fn new(socket: PathBuf) -> Self {
+		Self { socket }
+	}
+
+	fn request<T: DeserializeOwned>(&self, kind: u32, payload: &str) -> Result<T> {
+		let mut stream = UnixStream::connect(&self.socket)
+			.with_context(|| format!("unable to connect to {}", self.socket.display()))?;
+
+		stream.write_all(&encode(kind, payload))?;
+
+		let mut header = [0; HEADER_LEN];
+		stream.read_exact(&mut header)?;
+		let len = decode_header(&header, kind)?;
+
+		let mut body = vec![0; len];
+		stream.read_exact(&mut body)?;
+
+		Ok(serde_json::from_slice(&body)?)
+	}
+}
+
+impl Backend for SwayBackend {
+	fn windows(&self) -> Result<Vec<Window>> {
+		let tree = self.request::<Node>(GET_TREE, "")?;
+
+		let mut windows = vec![];
+		collect_windows(tree, &mut windows);
+
+		Ok(windows)
+	}
+
+	fn activate(&self, id: u64) -> Result<()> {
+		let command = format!("[con_id={id}] focus");
+		let results = self.request::<Vec<CommandResult>>(RUN_COMMAND, &command)?;
+
+		match results.into_iter().find(|r| !r.success) {
+			Some(result) => Err(anyhow!(result.error.unwrap_or_default())),
+			None => Ok(()),
+		}
+	}
+}
+
+fn encode(kind: u32, payload: &str) -> Vec<u8> {
+	let mut message = Vec::with_capacity(HEADER_LEN + payload.len());
+	message.extend_from_slice(MAGIC);
+	message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+	message.extend_from_slice(&kind.to_ne_bytes());
+	message.extend_from_slice(payload.as_bytes());
+
+	message
+}
+
+/// Validates the header and returns the length of the payload that follows.
+fn decode_header(header: &[u8; HEADER_LEN], expected_kind: u32) -> Result<usize> {
+	let (magic, rest) = header.split_at(MAGIC.len());
+	let (len, kind) = rest.split_at(4);
+
+	if magic != MAGIC {
+		bail!("invalid IPC response");
+	}
+
+	let kind = u32::from_ne_bytes(kind.try_into()?);
+	if kind != expected_kind {
+		bail!("unexpected IPC response type {kind}, expected {expected_kind}");
+	}
+
+	Ok(u32::from_ne_bytes(len.try_into()?) as usize)
+}
+
+/// Walks the tree, collecting all views. Containers, workspaces
+/// and outputs have neither an app ID nor X11 window properties.
+fn collect_windows(node: Node, windows: &mut Vec<Window>) {
+	let class = node.app_id.or(node.window_properties.and_then(|p| p.class));
+
+	if let Some(class) = class {
+		windows.push(Window {
+			id: node.id,
+			title: node.name.unwrap_or_default(),
+			class,
+		});
+	}
+
+	for child in node.nodes.into_iter().chain(node.floating_nodes) {
+		collect_windows(child, windows);
+	}
+}
+
+#[derive(Deserialize, Debug)]
+struct Node {
+	id: u64,
+	name: Option<String>,
+	app_id: Option<String>,
+	window_properties: Option<WindowProperties>,
+	#[serde(default)]
+	nodes: Vec<Node>,
+	#[serde(default)]
+	floating_nodes: Vec<Node>,
+}
+
+#[derive(Deserialize, Debug)]
+struct WindowProperties {
+	class: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CommandResult {
+	success: bool,
+	error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::os::unix::net::UnixListener;
+	use std::thread::{self, JoinHandle};
+	use tempfile::TempDir;
+
+	const TREE: &str = r#"{
+		"id": 1, "name": "root", "type": "root",
+		"nodes": [{
+			"id": 2, "name": "eDP-1", "type": "output",
+			"nodes": [{
+				"id": 3, "name": "1", "type": "workspace",
+				"nodes": [
+					{ "id": 4, "name": "vim", "type": "con", "app_id": "kitty", "nodes": [] },
+					{ "id": 5, "name": "Mozilla Firefox", "type": "con", "app_id": null,
+						"window_properties": { "class": "firefox", "instance": "Navigator" }, "nodes": [] }
+				],
+				"floating_nodes": [
+					{ "id": 6, "name": "Calculator", "type": "floating_con", "app_id": "org.gnome.Calculator" }
+				]
+			}]
+		}]
+	}"#;
+
+	/// Pretends to be sway, answering a single request. Returns the request's payload.
+	/// The socket is removed along with the returned directory.
+	fn serve(kind: u32, response: &'static str) -> (TempDir, PathBuf, JoinHandle<String>) {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("sway.sock");
+		let listener = UnixListener::bind(&path).unwrap();
+
+		let handle = thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+
+			let mut header = [0; HEADER_LEN];
+			stream.read_exact(&mut header).unwrap();
+			let len = decode_header(&header, kind).unwrap();
+
+			let mut payload = vec![0; len];
+			stream.read_exact(&mut payload).unwrap();
+			stream.write_all(&encode(kind, response)).unwrap();
+
+			String::from_utf8(payload).unwrap()
+		});
+
+		(dir, path, handle)
+	}
+
+	#[test]
+	fn should_list_windows() {
+		let (_dir, path, handle) = serve(GET_TREE, TREE);
+		let windows = SwayBackend::new(path).windows().unwrap();
+		handle.join().unwrap();
+
+		let titles = windows.iter().map(|w| (w.id, &*w.title, &*w.class)).collect::<Vec<_>>();
+		let expected = [
+			(4, "vim", "kitty"),
+			(5, "Mozilla Firefox", "firefox"),
+			(6, "Calculator", "org.gnome.Calculator"),
+		];
+
+		assert_eq!(&expected[..], titles);
+	}
+
+	#[test]
+	fn should_focus_window() {
+		let (_dir, path, handle) = serve(RUN_COMMAND, r#"[{ "success": true }]"#);
+		SwayBackend::new(path).activate(42).unwrap();
+
+		assert_eq!("[con_id=42] focus", handle.join().unwrap());
+	}
+
+	#[test]
+	fn should_report_command_error() {
+		let (_dir, path, handle) = serve(RUN_COMMAND, r#"[{ "success": false, "error": "No matching node" }]"#);
+		let result = SwayBackend::new(path).activate(42);
+		handle.join().unwrap();
+
+		assert_eq!("No matching node", result.unwrap_err().to_string());
+	}
+}
@@ -0,0 +1,174 @@
+//! Backend for X11 window managers implementing EWMH.
+
+use crate::{Backend, Window};
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{self, AtomEnum, ClientMessageEvent, ConnectionExt, EventMask};
+use x11rb::rust_connection::RustConnection;
+
+x11rb::atom_manager! {
+	Atoms: AtomsCookie {
+		_NET_CLIENT_LIST,
+		_NET_ACTIVE_WINDOW,
+		_NET_WM_NAME,
+		UTF8_STRING,
+	}
+}
+
+/// Source indication for client messages, telling the
+/// window manager the request comes from a pager.
+const SOURCE_PAGER: u32 = 2;
+
+pub struct X11Backend {
+	conn: RustConnection,
+	root: xproto::Window,
+	atoms: Atoms,
+}
+
+impl X11Backend {
+	/// Connects to the X server given in $DISPLAY.
+	pub fn connect() -> Result<Self> {
+		let (conn, screen) = x11rb::connect(None).context("unable to connect to the X server")?;
+		let root = conn.setup().roots[screen].root;
+		let atoms = Atoms::new(&conn)?.reply()?;
+
+		Ok(Self { conn, root, atoms })
+	}
+
+	fn get_title(&self, window: xproto::Window) -> Result<String> {
+		let name = self.get_string(window, self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING)?;
+
+		match name.is_empty() {
+			true => self.get_string(window, AtomEnum::WM_NAME.into(), AtomEnum::ANY.into()),
+			false => Ok(name),
+		}
+	}
+
+	fn get_class(&self, window: xproto::Window) -> Result<String> {
+		let value = self.get_property(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())?;
+
+		Ok(parse_class(&value))
+	}
+
+	fn get_string(&self, window: xproto::Window, property: xproto::Atom, kind: xproto::Atom) -> Result<String> {
+		let value = self.get_property(window, property, kind)?;
+
+		Ok(String::from_utf8_lossy(&value).into_owned())
+	}
+
+	fn get_property(&self, window: xproto::Window, property: xproto::Atom, kind: xproto::Atom) -> Result<Vec<u8>> {
+		let reply = self
+			.conn
+			.get_property(false, window, property, kind, 0, u32::MAX)?
+			.reply()?;
+
+		Ok(reply.value)
+	}
+}
+
+impl Backend for X11Backend {
+	fn windows(&self) -> Result<Vec<Window>> {
+		let (root, property) = (self.root, self.atoms._NET_CLIENT_LIST);
+		let reply = self
+			.conn
+			.get_property(false, root, property, AtomEnum::WINDOW, 0, u32::MAX)?
+			.reply()?;
+
+		let ids = reply
+			.value32()
+			.context("window manager doesn't support _NET_CLIENT_LIST")?;
+
+		let windows = ids
+			.filter_map(|id| {
+				// windows may disappear while we're looking at them, just skip those
+				let title = self.get_title(id).ok()?;
+				let class = self.get_class(id).ok()?;
+
+				Some(Window {
+					id: id as u64,
+					title,
+					class,
+				})
+			})
+			.collect();
+
+		Ok(windows)
+	}
+
+	fn activate(&self, id: u64) -> Result<()> {
+		let window = u32::try_from(id).context("invalid X11 window id")?;
+		let data = [SOURCE_PAGER, x11rb::CURRENT_TIME, 0, 0, 0];
+		let event = ClientMessageEvent::new(32, window, self.atoms._NET_ACTIVE_WINDOW, data);
+		let mask = EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY;
+
+		self.conn.send_event(false, self.root, mask, event)?;
+		self.conn.flush()?;
+
+		Ok(())
+	}
+}
+
+/// Extracts the class from a WM_CLASS value, which consists of
+/// the instance name and the class, each terminated by a null byte.
+fn parse_class(value: &[u8]) -> String {
+	let mut parts = value.split(|b| *b == 0).filter(|p| !p.is_empty());
+	let instance = parts.next().unwrap_or_default();
+	let class = parts.next().unwrap_or(instance);
+
+	String::from_utf8_lossy(class).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rstest::rstest;
+	use x11rb::protocol::xproto::{CreateWindowAux, PropMode, WindowClass};
+	use x11rb::wrapper::ConnectionExt as _;
+
+	#[rstest]
+	#[case(b"navigator\0firefox\0", "firefox")]
+	#[case(b"xterm\0XTerm\0", "XTerm")]
+	#[case(b"kitty\0", "kitty")]
+	#[case(b"", "")]
+	fn should_parse_class(#[case] value: &[u8], #[case] expected: &str) {
+		assert_eq!(expected, parse_class(value));
+	}
+
+	/// Creates a window and publishes it in _NET_CLIENT_LIST,
+	/// like a window manager would.
+	#[test]
+	#[ignore = "requires an X server, such as Xvfb"]
+	fn should_list_windows() {
+		let backend = X11Backend::connect().unwrap();
+		let (conn, root, atoms) = (&backend.conn, backend.root, &backend.atoms);
+		let window = conn.generate_id().unwrap();
+		let aux = CreateWindowAux::new();
+
+		conn.create_window(0, window, root, 0, 0, 1, 1, 0, WindowClass::INPUT_OUTPUT, 0, &aux)
+			.unwrap();
+
+		let name = "títle".as_bytes();
+		conn.change_property8(PropMode::REPLACE, window, atoms._NET_WM_NAME, atoms.UTF8_STRING, name)
+			.unwrap();
+
+		let class = b"test\0Test\0";
+		conn.change_property8(PropMode::REPLACE, window, AtomEnum::WM_CLASS, AtomEnum::STRING, class)
+			.unwrap();
+
+		let list = [window];
+		conn.change_property32(PropMode::REPLACE, root, atoms._NET_CLIENT_LIST, AtomEnum::WINDOW, &list)
+			.unwrap();
+
+		conn.sync().unwrap();
+
+		let windows = backend.windows().unwrap();
+
+		let expected = Window {
+			id: window as u64,
+			title: String::from("títle"),
+			class: String::from("Test"),
+		};
+
+		assert!(windows.contains(&expected), "{windows:?}");
+	}
+}
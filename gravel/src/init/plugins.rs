@@ -20,10 +20,16 @@ fn register_externals(registry: &mut PluginRegistry) {
 fn register_builtins(registry: &mut PluginRegistry) {
 	gravel_frontend_fltk::register_plugins(registry);
 
+	gravel_provider_browser::register_plugins(registry);
 	gravel_provider_calculator::register_plugins(registry);
+	gravel_provider_clipboard::register_plugins(registry);
 	gravel_provider_kill::register_plugins(registry);
 	gravel_provider_exec::register_plugins(registry);
+	gravel_provider_files::register_plugins(registry);
+	gravel_provider_open::register_plugins(registry);
 	gravel_provider_program::register_plugins(registry);
 	gravel_provider_system::register_plugins(registry);
+	gravel_provider_time::register_plugins(registry);
 	gravel_provider_websearch::register_plugins(registry);
+	gravel_provider_window::register_plugins(registry);
 }